@@ -0,0 +1,10 @@
+-- Your SQL goes here
+CREATE TABLE thumbnail_cache (
+   source_path VARCHAR PRIMARY KEY NOT NULL,
+   source_size INTEGER NOT NULL,
+   source_mtime INTEGER NOT NULL,
+   hash VARCHAR NOT NULL,
+   image_size INTEGER NOT NULL,
+   last_used INTEGER NOT NULL
+);
+CREATE INDEX thumbnail_cache_hash ON thumbnail_cache(hash);
@@ -0,0 +1,16 @@
+-- Your SQL goes here
+CREATE TABLE file_meshes (
+   project_file_id INTEGER PRIMARY KEY NOT NULL REFERENCES project_files(id),
+   size_x REAL NOT NULL,
+   size_y REAL NOT NULL,
+   size_z REAL NOT NULL,
+   triangle_count INTEGER NOT NULL,
+   surface_area REAL NOT NULL,
+   volume REAL NOT NULL,
+   watertight INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE TRIGGER project_files_delete_mesh AFTER DELETE ON project_files
+BEGIN
+   DELETE FROM file_meshes WHERE project_file_id = old.id;
+END;
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP TRIGGER project_files_delete_gcode;
+DROP TABLE file_gcodes;
@@ -0,0 +1,18 @@
+-- Your SQL goes here
+CREATE TABLE file_gcodes (
+   project_file_id INTEGER PRIMARY KEY NOT NULL REFERENCES project_files(id),
+   slicer TEXT,
+   print_time INTEGER,
+   filament_length REAL,
+   filament_weight REAL,
+   filament_type TEXT,
+   layer_height REAL,
+   nozzle_temperature REAL,
+   bed_temperature REAL,
+   has_thumbnail INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE TRIGGER project_files_delete_gcode AFTER DELETE ON project_files
+BEGIN
+   DELETE FROM file_gcodes WHERE project_file_id = old.id;
+END;
@@ -0,0 +1,3 @@
+-- Your SQL goes here
+ALTER TABLE projects
+    ADD missing INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,14 @@
+-- This file should undo anything in `up.sql`
+DROP TRIGGER search_projects_insert;
+DROP TRIGGER search_projects_update;
+DROP TRIGGER search_projects_delete;
+DROP TRIGGER search_files_insert;
+DROP TRIGGER search_files_update;
+DROP TRIGGER search_files_delete;
+DROP TRIGGER search_projects_tags_insert;
+DROP TRIGGER search_projects_tags_delete;
+DROP TRIGGER search_tags_update;
+DROP TRIGGER search_sources_insert;
+DROP TRIGGER search_sources_update;
+DROP TRIGGER search_sources_delete;
+DROP TABLE search_index;
@@ -0,0 +1,73 @@
+-- Your SQL goes here
+CREATE VIRTUAL TABLE search_index USING fts5(
+   project_id UNINDEXED,
+   kind UNINDEXED,
+   item_id UNINDEXED,
+   title,
+   body,
+   tokenize = 'unicode61 remove_diacritics 2',
+   prefix = '2 3'
+);
+-- names and paths count for more than notes and urls
+INSERT INTO search_index (search_index, rank) VALUES ('rank', 'bm25(0.0, 0.0, 0.0, 10.0, 1.0)');
+
+INSERT INTO search_index (project_id, kind, item_id, title, body)
+   SELECT id, 'project', id, name, coalesce(notes, '') FROM projects;
+INSERT INTO search_index (project_id, kind, item_id, title, body)
+   SELECT project_id, 'file', id, path, coalesce(notes, '') FROM project_files;
+INSERT INTO search_index (project_id, kind, item_id, title, body)
+   SELECT pt.project_id, 'tag', pt.tag_id, t.tag, '' FROM projects_tags pt JOIN tags t ON t.id = pt.tag_id;
+INSERT INTO search_index (project_id, kind, item_id, title, body)
+   SELECT project_id, 'source', id, coalesce(name, ''), url FROM project_sources;
+
+CREATE TRIGGER search_projects_insert AFTER INSERT ON projects
+BEGIN
+   INSERT INTO search_index (project_id, kind, item_id, title, body) VALUES (new.id, 'project', new.id, new.name, coalesce(new.notes, ''));
+END;
+CREATE TRIGGER search_projects_update AFTER UPDATE OF name, notes ON projects
+BEGIN
+   UPDATE search_index SET title = new.name, body = coalesce(new.notes, '') WHERE kind = 'project' AND item_id = new.id;
+END;
+CREATE TRIGGER search_projects_delete AFTER DELETE ON projects
+BEGIN
+   DELETE FROM search_index WHERE project_id = old.id;
+END;
+
+CREATE TRIGGER search_files_insert AFTER INSERT ON project_files
+BEGIN
+   INSERT INTO search_index (project_id, kind, item_id, title, body) VALUES (new.project_id, 'file', new.id, new.path, coalesce(new.notes, ''));
+END;
+CREATE TRIGGER search_files_update AFTER UPDATE OF path, notes ON project_files
+BEGIN
+   UPDATE search_index SET title = new.path, body = coalesce(new.notes, '') WHERE kind = 'file' AND item_id = new.id;
+END;
+CREATE TRIGGER search_files_delete AFTER DELETE ON project_files
+BEGIN
+   DELETE FROM search_index WHERE kind = 'file' AND item_id = old.id;
+END;
+
+CREATE TRIGGER search_projects_tags_insert AFTER INSERT ON projects_tags
+BEGIN
+   INSERT INTO search_index (project_id, kind, item_id, title, body) SELECT new.project_id, 'tag', new.tag_id, tag, '' FROM tags WHERE id = new.tag_id;
+END;
+CREATE TRIGGER search_projects_tags_delete AFTER DELETE ON projects_tags
+BEGIN
+   DELETE FROM search_index WHERE kind = 'tag' AND project_id = old.project_id AND item_id = old.tag_id;
+END;
+CREATE TRIGGER search_tags_update AFTER UPDATE OF tag ON tags
+BEGIN
+   UPDATE search_index SET title = new.tag WHERE kind = 'tag' AND item_id = new.id;
+END;
+
+CREATE TRIGGER search_sources_insert AFTER INSERT ON project_sources
+BEGIN
+   INSERT INTO search_index (project_id, kind, item_id, title, body) VALUES (new.project_id, 'source', new.id, coalesce(new.name, ''), new.url);
+END;
+CREATE TRIGGER search_sources_update AFTER UPDATE OF name, url ON project_sources
+BEGIN
+   UPDATE search_index SET title = coalesce(new.name, ''), body = new.url WHERE kind = 'source' AND item_id = new.id;
+END;
+CREATE TRIGGER search_sources_delete AFTER DELETE ON project_sources
+BEGIN
+   DELETE FROM search_index WHERE kind = 'source' AND item_id = old.id;
+END;
@@ -0,0 +1,4 @@
+-- Your SQL goes here
+ALTER TABLE projects
+    ADD added INTEGER;
+UPDATE projects SET added = CAST(strftime('%s', 'now') AS INTEGER);
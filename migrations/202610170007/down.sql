@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP TRIGGER saved_searches_delete_tags;
+DROP TABLE saved_searches_tags;
+DROP TABLE saved_searches;
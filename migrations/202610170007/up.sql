@@ -0,0 +1,19 @@
+-- Your SQL goes here
+CREATE TABLE saved_searches (
+   id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+   name VARCHAR NOT NULL,
+   query TEXT NOT NULL DEFAULT '',
+   sort VARCHAR NOT NULL DEFAULT 'relevance',
+   pinned INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE TABLE saved_searches_tags (
+   saved_search_id INTEGER REFERENCES saved_searches(id) NOT NULL,
+   tag_id INTEGER REFERENCES tags(id) NOT NULL,
+   PRIMARY KEY(saved_search_id, tag_id)
+);
+
+CREATE TRIGGER saved_searches_delete_tags AFTER DELETE ON saved_searches
+BEGIN
+   DELETE FROM saved_searches_tags WHERE saved_search_id = old.id;
+END;
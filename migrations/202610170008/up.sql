@@ -0,0 +1,12 @@
+-- Your SQL goes here
+CREATE TABLE collections (
+   id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+   name VARCHAR NOT NULL
+);
+
+CREATE TABLE collections_projects (
+   collection_id INTEGER REFERENCES collections(id) NOT NULL,
+   project_id INTEGER REFERENCES projects(id) NOT NULL,
+   position INTEGER NOT NULL,
+   PRIMARY KEY(collection_id, project_id)
+);
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX tags_parent;
+DROP TABLE tag_aliases;
+ALTER TABLE tags
+    DROP COLUMN parent_id;
@@ -0,0 +1,9 @@
+-- Your SQL goes here
+ALTER TABLE tags
+    ADD parent_id INTEGER REFERENCES tags(id);
+
+CREATE TABLE tag_aliases (
+   alias VARCHAR PRIMARY KEY NOT NULL COLLATE NOCASE,
+   tag_id INTEGER NOT NULL REFERENCES tags(id)
+);
+CREATE INDEX tags_parent ON tags(parent_id);
@@ -0,0 +1,17 @@
+-- This file should undo anything in `up.sql`
+DROP TRIGGER search_files_update;
+CREATE TRIGGER search_files_update AFTER UPDATE OF path, notes ON project_files
+BEGIN
+   UPDATE search_index SET title = new.path, body = coalesce(new.notes, '') WHERE kind = 'file' AND item_id = new.id;
+END;
+
+DELETE FROM project_files WHERE removed IS NOT NULL;
+DROP INDEX project_files_hash;
+ALTER TABLE project_files
+    DROP COLUMN removed;
+ALTER TABLE project_files
+    DROP COLUMN mtime;
+ALTER TABLE project_files
+    DROP COLUMN size;
+ALTER TABLE project_files
+    DROP COLUMN hash;
@@ -0,0 +1,18 @@
+-- Your SQL goes here
+ALTER TABLE project_files
+    ADD hash TEXT;
+ALTER TABLE project_files
+    ADD size INTEGER;
+ALTER TABLE project_files
+    ADD mtime INTEGER;
+ALTER TABLE project_files
+    ADD removed INTEGER;
+CREATE INDEX project_files_hash ON project_files(hash);
+
+DROP TRIGGER search_files_update;
+CREATE TRIGGER search_files_update AFTER UPDATE OF path, notes, project_id, removed ON project_files
+BEGIN
+   DELETE FROM search_index WHERE kind = 'file' AND item_id = new.id;
+   INSERT INTO search_index (project_id, kind, item_id, title, body)
+      SELECT new.project_id, 'file', new.id, new.path, coalesce(new.notes, '') WHERE new.removed IS NULL;
+END;
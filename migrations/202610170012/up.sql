@@ -0,0 +1,6 @@
+-- Your SQL goes here
+CREATE TABLE related_projects (
+   project_id INTEGER REFERENCES projects(id) NOT NULL,
+   related_id INTEGER REFERENCES projects(id) NOT NULL,
+   PRIMARY KEY(project_id, related_id)
+);
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE print_job_photos;
+DROP INDEX print_jobs_file;
+DROP TABLE print_jobs;
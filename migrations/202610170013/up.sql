@@ -0,0 +1,20 @@
+-- Your SQL goes here
+CREATE TABLE print_jobs (
+   id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+   project_file_id INTEGER NOT NULL REFERENCES project_files(id),
+   printed VARCHAR NOT NULL,
+   printer VARCHAR,
+   material VARCHAR,
+   color VARCHAR,
+   duration INTEGER,
+   outcome VARCHAR NOT NULL,
+   failure_reason TEXT,
+   notes TEXT
+);
+CREATE INDEX print_jobs_file ON print_jobs(project_file_id);
+
+CREATE TABLE print_job_photos (
+   id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+   print_job_id INTEGER NOT NULL REFERENCES print_jobs(id),
+   path VARCHAR NOT NULL
+);
@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE print_jobs
+    DROP COLUMN filament_used;
+ALTER TABLE print_jobs
+    DROP COLUMN spool_id;
+DROP TABLE spools;
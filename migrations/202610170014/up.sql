@@ -0,0 +1,16 @@
+-- Your SQL goes here
+CREATE TABLE spools (
+   id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+   brand VARCHAR NOT NULL,
+   material VARCHAR NOT NULL,
+   color VARCHAR NOT NULL,
+   diameter REAL NOT NULL DEFAULT 1.75,
+   initial_weight REAL NOT NULL,
+   remaining_weight REAL NOT NULL,
+   price REAL
+);
+
+ALTER TABLE print_jobs
+    ADD spool_id INTEGER REFERENCES spools(id);
+ALTER TABLE print_jobs
+    ADD filament_used REAL;
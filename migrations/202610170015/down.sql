@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX project_status_history_project;
+DROP TABLE project_status_history;
+ALTER TABLE projects
+    DROP COLUMN status;
@@ -0,0 +1,11 @@
+-- Your SQL goes here
+ALTER TABLE projects
+    ADD COLUMN status VARCHAR;
+
+CREATE TABLE project_status_history (
+   id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+   project_id INTEGER REFERENCES projects(id) NOT NULL,
+   status VARCHAR,
+   changed VARCHAR NOT NULL
+);
+CREATE INDEX project_status_history_project ON project_status_history(project_id);
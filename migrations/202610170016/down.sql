@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX print_queue_file;
+DROP TABLE print_queue;
@@ -0,0 +1,11 @@
+-- Your SQL goes here
+CREATE TABLE print_queue (
+   id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+   project_file_id INTEGER REFERENCES project_files(id) NOT NULL,
+   quantity INTEGER NOT NULL DEFAULT 1,
+   printed INTEGER NOT NULL DEFAULT 0,
+   priority INTEGER NOT NULL DEFAULT 0,
+   position INTEGER NOT NULL DEFAULT 0,
+   printer VARCHAR
+);
+CREATE INDEX print_queue_file ON print_queue(project_file_id);
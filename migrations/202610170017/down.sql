@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX project_files_project_path;
+DROP INDEX projects_path;
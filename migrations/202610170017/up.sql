@@ -0,0 +1,38 @@
+-- Your SQL goes here
+CREATE TEMP TABLE duplicate_projects AS
+   SELECT p.id AS id, (SELECT min(k.id) FROM projects k WHERE k.path = p.path) AS keep_id
+   FROM projects p WHERE p.id != (SELECT min(k.id) FROM projects k WHERE k.path = p.path);
+
+INSERT OR IGNORE INTO projects_tags (project_id, tag_id)
+   SELECT d.keep_id, pt.tag_id FROM projects_tags pt JOIN duplicate_projects d ON d.id = pt.project_id;
+DELETE FROM projects_tags WHERE project_id IN (SELECT id FROM duplicate_projects);
+INSERT INTO search_index (project_id, kind, item_id, title, body)
+   SELECT d.keep_id, 'source', s.id, coalesce(s.name, ''), s.url FROM project_sources s JOIN duplicate_projects d ON d.id = s.project_id;
+UPDATE project_sources SET project_id = (SELECT keep_id FROM duplicate_projects WHERE id = project_id)
+   WHERE project_id IN (SELECT id FROM duplicate_projects);
+INSERT OR IGNORE INTO collections_projects (collection_id, project_id, position)
+   SELECT cp.collection_id, d.keep_id, cp.position FROM collections_projects cp JOIN duplicate_projects d ON d.id = cp.project_id;
+DELETE FROM collections_projects WHERE project_id IN (SELECT id FROM duplicate_projects);
+DELETE FROM related_projects WHERE project_id IN (SELECT id FROM duplicate_projects) OR related_id IN (SELECT id FROM duplicate_projects);
+UPDATE project_status_history SET project_id = (SELECT keep_id FROM duplicate_projects WHERE id = project_id)
+   WHERE project_id IN (SELECT id FROM duplicate_projects);
+UPDATE project_files SET project_id = (SELECT keep_id FROM duplicate_projects WHERE id = project_id)
+   WHERE project_id IN (SELECT id FROM duplicate_projects);
+DELETE FROM projects WHERE id IN (SELECT id FROM duplicate_projects);
+DROP TABLE duplicate_projects;
+
+CREATE TEMP TABLE duplicate_files AS
+   SELECT f.id AS id, (SELECT min(k.id) FROM project_files k WHERE k.project_id = f.project_id AND k.path = f.path AND k.removed IS NULL) AS keep_id
+   FROM project_files f
+   WHERE f.removed IS NULL
+      AND f.id != (SELECT min(k.id) FROM project_files k WHERE k.project_id = f.project_id AND k.path = f.path AND k.removed IS NULL);
+
+UPDATE print_jobs SET project_file_id = (SELECT keep_id FROM duplicate_files WHERE id = project_file_id)
+   WHERE project_file_id IN (SELECT id FROM duplicate_files);
+UPDATE print_queue SET project_file_id = (SELECT keep_id FROM duplicate_files WHERE id = project_file_id)
+   WHERE project_file_id IN (SELECT id FROM duplicate_files);
+DELETE FROM project_files WHERE id IN (SELECT id FROM duplicate_files);
+DROP TABLE duplicate_files;
+
+CREATE UNIQUE INDEX projects_path ON projects(path);
+CREATE UNIQUE INDEX project_files_project_path ON project_files(project_id, path) WHERE removed IS NULL;
@@ -0,0 +1,4 @@
+-- Your SQL goes here
+ALTER TABLE print_jobs
+    ADD filament_deducted REAL;
+UPDATE print_jobs SET filament_deducted = filament_used WHERE spool_id IS NOT NULL;
@@ -0,0 +1,3 @@
+-- Your SQL goes here
+ALTER TABLE print_jobs
+    ADD queue_item_id INTEGER REFERENCES print_queue(id);
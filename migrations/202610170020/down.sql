@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP TRIGGER project_files_delete_metadata_failure;
+DROP TABLE file_metadata_failures;
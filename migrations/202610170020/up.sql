@@ -0,0 +1,11 @@
+-- Your SQL goes here
+CREATE TABLE file_metadata_failures (
+   project_file_id INTEGER PRIMARY KEY NOT NULL REFERENCES project_files(id),
+   size INTEGER NOT NULL,
+   mtime INTEGER NOT NULL
+);
+
+CREATE TRIGGER project_files_delete_metadata_failure AFTER DELETE ON project_files
+BEGIN
+   DELETE FROM file_metadata_failures WHERE project_file_id = old.id;
+END;
@@ -24,12 +24,52 @@ use fs::{create_dir_all, write, read_to_string};
 use std::path::PathBuf;
 use std::string::ToString;
 use iced::Theme;
+use crate::models::material::Material;
+use crate::models::printer_profile::PrinterProfile;
+use crate::models::scan_settings::ScanSettings;
+use crate::models::tag_rule::TagRule;
+use crate::renderer::{Camera, RenderSettings, Shading};
 
 
     #[derive(Debug, Serialize, Deserialize, Clone)]
     pub struct Config {
         pub theme: Option<String>,
         pub print_paths: Option<Vec<String>>,
+        /// Width and height in pixels of generated model thumbnails.
+        pub thumbnail_size: Option<u32>,
+        /// Camera angles in degrees used for generated model thumbnails.
+        pub thumbnail_yaw: Option<f32>,
+        pub thumbnail_pitch: Option<f32>,
+        pub thumbnail_shading: Option<Shading>,
+        /// Number of thumbnails rendered in parallel, defaults to the number of CPUs.
+        pub thumbnail_workers: Option<usize>,
+        /// Keep thumbnails in the config directory instead of a `.3DManager` folder in each project.
+        pub thumbnail_cache: Option<bool>,
+        pub thumbnail_cache_size_mb: Option<u64>,
+        /// Use the preview a slicer stored in 3MF files instead of rendering the model.
+        pub embedded_thumbnails: Option<bool>,
+        /// Printers models are checked against for build volume fit.
+        pub printers: Option<Vec<PrinterProfile>>,
+        /// Rules tagging projects from their directory and file names while scanning.
+        pub tag_rules: Option<Vec<TagRule>>,
+        /// How projects are found in each print path, print paths without settings use the defaults.
+        pub scan_settings: Option<Vec<ScanSettings>>,
+        /// Spools with less filament left than this many grams are shown as running low.
+        pub low_spool_grams: Option<f64>,
+        /// Filament prices and densities for cost estimates.
+        pub materials: Option<Vec<Material>>,
+        /// Material assumed for models and G-code that don't say what they're printed in.
+        pub default_material: Option<String>,
+        /// Price of a kWh of electricity.
+        pub electricity_rate: Option<f64>,
+        /// Average power the printer draws while printing, in watts.
+        pub printer_wattage: Option<f64>,
+        /// Share of a model's volume that gets printed once infill is taken into account.
+        pub model_fill: Option<f64>,
+        /// Put in front of prices, e.g. "$" or "€".
+        pub currency: Option<String>,
+        /// Statuses a project moves through, in order, shown as the columns of the board.
+        pub workflow_states: Option<Vec<String>>,
     }
 
     impl Config {
@@ -82,6 +122,9 @@ use iced::Theme;
             if self.print_paths.is_some() {
                 self.print_paths.as_mut().unwrap().retain(|value| *value != path.to_string());
             }
+            if let Some(scan_settings) = self.scan_settings.as_mut() {
+                scan_settings.retain(|settings| settings.print_path != path);
+            }
         }
         pub fn print_path_empty_or_none(&mut self) -> bool {
             if self.print_paths.is_none() { return true; }
@@ -90,6 +133,130 @@ use iced::Theme;
             }
             false
         }
+        pub fn render_settings(&self) -> RenderSettings {
+            let defaults = RenderSettings::default();
+            RenderSettings {
+                size: self.thumbnail_size.unwrap_or(defaults.size),
+                camera: Camera {
+                    yaw: self.thumbnail_yaw.unwrap_or(defaults.camera.yaw),
+                    pitch: self.thumbnail_pitch.unwrap_or(defaults.camera.pitch),
+                    ..defaults.camera
+                },
+                shading: self.thumbnail_shading.unwrap_or(defaults.shading),
+            }
+        }
+        pub fn get_thumbnail_workers(&self) -> usize {
+            self.thumbnail_workers
+                .filter(|workers| *workers > 0)
+                .unwrap_or(std::thread::available_parallelism().map(|count| count.get()).unwrap_or(2))
+        }
+        pub fn use_thumbnail_cache(&self) -> bool {
+            self.thumbnail_cache.unwrap_or(false)
+        }
+        pub fn get_thumbnail_cache_size_mb(&self) -> u64 {
+            self.thumbnail_cache_size_mb.unwrap_or(500)
+        }
+        pub fn use_embedded_thumbnails(&self) -> bool {
+            self.embedded_thumbnails.unwrap_or(true)
+        }
+        pub fn get_printers(&self) -> Vec<PrinterProfile> {
+            self.printers.clone().unwrap_or_default()
+        }
+        pub fn get_printer(&self, name: &str) -> Option<PrinterProfile> {
+            self.get_printers().into_iter().find(|printer| printer.name == name)
+        }
+        /**
+         * Adds the printer, replacing any existing printer with the same name.
+         */
+        pub fn add_printer(&mut self, printer: PrinterProfile) {
+            let printers = self.printers.get_or_insert_with(Vec::new);
+            match printers.iter().position(|existing| existing.name == printer.name) {
+                Some(pos) => printers[pos] = printer,
+                None => printers.push(printer),
+            }
+        }
+        pub fn remove_printer(&mut self, name: &str) {
+            if let Some(printers) = self.printers.as_mut() {
+                printers.retain(|printer| printer.name != name);
+            }
+        }
+        pub fn get_tag_rules(&self) -> Vec<TagRule> {
+            self.tag_rules.clone().unwrap_or_default()
+        }
+        pub fn add_tag_rule(&mut self, rule: TagRule) {
+            self.tag_rules.get_or_insert_with(Vec::new).push(rule);
+        }
+        pub fn remove_tag_rule(&mut self, index: usize) {
+            if let Some(tag_rules) = self.tag_rules.as_mut() && index < tag_rules.len() {
+                tag_rules.remove(index);
+            }
+        }
+        pub fn get_low_spool_grams(&self) -> f64 {
+            self.low_spool_grams.unwrap_or(100.0)
+        }
+        pub fn get_materials(&self) -> Vec<Material> {
+            self.materials.clone().unwrap_or(Material::defaults())
+        }
+        /**
+         * The material matching the name, ignoring case, falling back to the default material.
+         */
+        pub fn get_material(&self, name: Option<&str>) -> Option<Material> {
+            let materials = self.get_materials();
+            let default_material = self.get_default_material();
+            let name = name.map(|name| name.trim()).filter(|name| !name.is_empty()).unwrap_or(default_material.as_str());
+            materials.iter().find(|material| material.name.eq_ignore_ascii_case(name))
+                .or(materials.iter().find(|material| material.name.eq_ignore_ascii_case(&default_material)))
+                .cloned()
+        }
+        /**
+         * Adds the material, replacing any existing material with the same name.
+         */
+        pub fn add_material(&mut self, material: Material) {
+            let materials = self.materials.get_or_insert_with(Material::defaults);
+            match materials.iter().position(|existing| existing.name.eq_ignore_ascii_case(&material.name)) {
+                Some(pos) => materials[pos] = material,
+                None => materials.push(material),
+            }
+        }
+        pub fn remove_material(&mut self, name: &str) {
+            self.materials.get_or_insert_with(Material::defaults).retain(|material| material.name != name);
+        }
+        pub fn get_default_material(&self) -> String {
+            self.default_material.clone().unwrap_or("PLA".to_string())
+        }
+        pub fn get_electricity_rate(&self) -> f64 {
+            self.electricity_rate.unwrap_or(0.30)
+        }
+        pub fn get_printer_wattage(&self) -> f64 {
+            self.printer_wattage.unwrap_or(120.0)
+        }
+        pub fn get_model_fill(&self) -> f64 {
+            self.model_fill.unwrap_or(0.35)
+        }
+        pub fn get_currency(&self) -> String {
+            self.currency.clone().unwrap_or("$".to_string())
+        }
+        pub fn get_workflow_states(&self) -> Vec<String> {
+            self.workflow_states.clone()
+                .filter(|states| !states.is_empty())
+                .unwrap_or(["Idea", "To Print", "Printing", "Done", "Failed"].map(String::from).to_vec())
+        }
+        pub fn get_scan_settings(&self, print_path: &str) -> ScanSettings {
+            self.scan_settings.iter().flatten()
+                .find(|settings| settings.print_path == print_path)
+                .cloned()
+                .unwrap_or(ScanSettings::new(print_path))
+        }
+        /**
+         * Replaces the settings for the print path, adding them if it had none.
+         */
+        pub fn set_scan_settings(&mut self, settings: ScanSettings) {
+            let scan_settings = self.scan_settings.get_or_insert_with(Vec::new);
+            match scan_settings.iter().position(|existing| existing.print_path == settings.print_path) {
+                Some(pos) => scan_settings[pos] = settings,
+                None => scan_settings.push(settings),
+            }
+        }
         pub fn get_config_dir() -> Option<PathBuf> {
             let mut config_dir = dirs::config_dir().unwrap().to_path_buf();
             config_dir.push("ThreeDPrintManager");
@@ -0,0 +1,309 @@
+/*
+ * Copyright (c) 2025-2026 Mitch Tuck
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+//! Rough material and electricity costs of printing a project's files, worked out from the
+//! G-code a slicer wrote or, for models not sliced yet, from the model's volume.
+
+use std::f64::consts::PI;
+use std::path::Path;
+use crate::config::Config;
+use crate::gcode::format_duration;
+use crate::models::file::ProjectFile;
+use crate::models::project::Project;
+
+/// Filament diameter in mm used to turn a length of filament into a weight.
+const FILAMENT_DIAMETER: f64 = 1.75;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CostEstimate {
+    /// Name of the material the estimate is priced in.
+    pub material: String,
+    /// Grams of filament used.
+    pub grams: f64,
+    /// Print time from the G-code, models have none.
+    pub seconds: Option<i64>,
+    pub material_cost: f64,
+    pub energy_cost: f64,
+}
+
+impl CostEstimate {
+    pub fn total(&self) -> f64 {
+        self.material_cost + self.energy_cost
+    }
+}
+
+/**
+ * What printing the file would cost, None when it isn't sliced G-code or a measured model.
+ */
+pub fn estimate_file(file: &ProjectFile, config: &Config) -> Option<CostEstimate> {
+    if let Some(gcode) = file.gcode.as_ref() {
+        let material = config.get_material(gcode.filament_type.as_deref())?;
+        let grams = gcode.filament_weight.or(gcode.filament_length.map(|length| {
+            let cross_section = PI * (FILAMENT_DIAMETER / 2.0).powi(2);
+            length * cross_section / 1000.0 * material.density
+        }))?;
+        let hours = gcode.print_time.unwrap_or(0) as f64 / 3600.0;
+        return Some(CostEstimate {
+            material: material.name,
+            grams,
+            seconds: gcode.print_time,
+            material_cost: grams / 1000.0 * material.price_per_kg,
+            energy_cost: config.get_printer_wattage() / 1000.0 * hours * config.get_electricity_rate(),
+        });
+    }
+    let mesh = file.mesh.as_ref().filter(|mesh| mesh.volume != 0.0)?;
+    let material = config.get_material(None)?;
+    let grams = mesh.volume.abs() / 1000.0 * material.density * config.get_model_fill();
+    Some(CostEstimate {
+        material: material.name,
+        grams,
+        seconds: None,
+        material_cost: grams / 1000.0 * material.price_per_kg,
+        energy_cost: 0.0,
+    })
+}
+
+/**
+ * Estimates for the files making up a print of the whole project. When the project has sliced
+ * G-code only that is counted, the models it was sliced from would otherwise be counted twice.
+ */
+pub fn project_estimates(project: &Project, config: &Config) -> Vec<(ProjectFile, CostEstimate)> {
+    let has_gcode = project.files.iter().any(|file| file.gcode.is_some());
+    project.files.iter()
+        .filter(|file| if has_gcode { file.gcode.is_some() } else { file.mesh.is_some() })
+        .filter_map(|file| estimate_file(file, config).map(|estimate| (file.clone(), estimate)))
+        .collect()
+}
+
+/**
+ * Adds up the estimates, print time is only totalled from files that have one.
+ */
+pub fn total(estimates: &[(ProjectFile, CostEstimate)]) -> Option<CostEstimate> {
+    if estimates.is_empty() {
+        return None;
+    }
+    let seconds: Vec<i64> = estimates.iter().filter_map(|(_, estimate)| estimate.seconds).collect();
+    let mut materials: Vec<&str> = estimates.iter().map(|(_, estimate)| estimate.material.as_str()).collect();
+    materials.sort();
+    materials.dedup();
+    Some(CostEstimate {
+        material: materials.join(", "),
+        grams: estimates.iter().map(|(_, estimate)| estimate.grams).sum(),
+        seconds: if seconds.is_empty() { None } else { Some(seconds.iter().sum()) },
+        material_cost: estimates.iter().map(|(_, estimate)| estimate.material_cost).sum(),
+        energy_cost: estimates.iter().map(|(_, estimate)| estimate.energy_cost).sum(),
+    })
+}
+
+pub fn format_money(amount: f64, config: &Config) -> String {
+    format!("{}{:.2}", config.get_currency(), amount)
+}
+
+/**
+ * A plain text quote for printing the project for someone else, listing each file's filament,
+ * time and cost followed by the totals and the rates used.
+ */
+pub fn quote(project: &Project, config: &Config) -> String {
+    let estimates = project_estimates(project, config);
+    let mut lines = vec![
+        format!("Quote: {}", project.name),
+        String::new(),
+    ];
+    if estimates.is_empty() {
+        lines.push("No sliced G-code or measured models to estimate from.".to_string());
+        return lines.join("\n") + "\n";
+    }
+    let names: Vec<String> = estimates.iter()
+        .map(|(file, _)| Path::new(&file.path).file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or(file.path.clone()))
+        .collect();
+    let width = names.iter().map(|name| name.chars().count()).max().unwrap_or(0).max(4);
+    lines.push(format!("{:<width$}  {:<8}  {:>9}  {:>10}  {:>10}", "File", "Material", "Filament", "Time", "Cost"));
+    for (name, (_, estimate)) in names.iter().zip(estimates.iter()) {
+        lines.push(format!(
+            "{:<width$}  {:<8}  {:>9}  {:>10}  {:>10}",
+            name,
+            estimate.material,
+            format!("{:.1} g", estimate.grams),
+            estimate.seconds.map(format_duration).unwrap_or("-".to_string()),
+            format_money(estimate.total(), config),
+        ));
+    }
+    let total = total(&estimates).unwrap();
+    lines.push(String::new());
+    lines.push(format!("Filament: {:.1} g", total.grams));
+    if let Some(seconds) = total.seconds {
+        lines.push(format!("Print time: {}", format_duration(seconds)));
+    }
+    lines.push(format!("Material: {}", format_money(total.material_cost, config)));
+    lines.push(format!("Electricity: {}", format_money(total.energy_cost, config)));
+    lines.push(format!("Total: {}", format_money(total.total(), config)));
+    lines.push(String::new());
+    let mut rates: Vec<String> = config.get_materials().iter()
+        .filter(|material| estimates.iter().any(|(_, estimate)| estimate.material == material.name))
+        .map(|material| format!("{} {}/kg", material.name, format_money(material.price_per_kg, config)))
+        .collect();
+    rates.push(format!("electricity {}/kWh at {:.0} W", format_money(config.get_electricity_rate(), config), config.get_printer_wattage()));
+    lines.push(format!("Rates: {}", rates.join(", ")));
+    if !project.files.iter().any(|file| file.gcode.is_some()) {
+        lines.push(format!("Estimated from model volume at {:.0}% fill, slicing will give a closer figure.", config.get_model_fill() * 100.0));
+    }
+    lines.join("\n") + "\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::gcode_info::GcodeInfo;
+    use crate::models::material::Material;
+    use crate::models::mesh_info::MeshInfo;
+
+    fn test_config() -> Config {
+        let mut config = toml::from_str::<Config>("").unwrap();
+        config.materials = Some(vec![Material::new("PLA", 20.0, 1.25), Material::new("PETG", 30.0, 1.25)]);
+        config.electricity_rate = Some(0.5);
+        config.printer_wattage = Some(200.0);
+        config.model_fill = Some(0.5);
+        config
+    }
+
+    fn gcode_file(path: &str, filament_type: Option<&str>, weight: Option<f64>, length: Option<f64>, seconds: Option<i64>) -> ProjectFile {
+        ProjectFile {
+            path: path.to_string(),
+            gcode: Some(GcodeInfo {
+                project_file_id: 0,
+                slicer: None,
+                print_time: seconds,
+                filament_length: length,
+                filament_weight: weight,
+                filament_type: filament_type.map(|name| name.to_string()),
+                layer_height: None,
+                nozzle_temperature: None,
+                bed_temperature: None,
+                has_thumbnail: false,
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn model_file(path: &str, volume: f64) -> ProjectFile {
+        ProjectFile {
+            path: path.to_string(),
+            mesh: Some(MeshInfo {
+                project_file_id: 0,
+                size_x: 20.0,
+                size_y: 20.0,
+                size_z: 20.0,
+                triangle_count: 12,
+                surface_area: 2400.0,
+                volume,
+                watertight: true,
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "{actual} != {expected}");
+    }
+
+    #[test]
+    fn estimates_gcode_from_its_filament_weight() {
+        let file = gcode_file("/prints/boat/boat.gcode", Some("PETG"), Some(50.0), Some(1000.0), Some(7200));
+        let estimate = estimate_file(&file, &test_config()).unwrap();
+        assert_eq!(estimate.material, "PETG");
+        assert_close(estimate.grams, 50.0);
+        assert_eq!(estimate.seconds, Some(7200));
+        assert_close(estimate.material_cost, 1.5);
+        //200 W for 2 hours at 0.5 a kWh
+        assert_close(estimate.energy_cost, 0.2);
+        assert_close(estimate.total(), 1.7);
+    }
+
+    #[test]
+    fn estimates_gcode_from_its_filament_length() {
+        //unknown materials are priced as the default material
+        let file = gcode_file("/prints/boat/boat.gcode", Some("Unobtainium"), None, Some(1000.0), None);
+        let estimate = estimate_file(&file, &test_config()).unwrap();
+        assert_eq!(estimate.material, "PLA");
+        let grams = PI * 0.875_f64.powi(2) * 1000.0 / 1000.0 * 1.25;
+        assert_close(estimate.grams, grams);
+        assert_close(estimate.material_cost, grams / 1000.0 * 20.0);
+        assert_eq!(estimate.seconds, None);
+        assert_close(estimate.energy_cost, 0.0);
+    }
+
+    #[test]
+    fn gcode_without_filament_has_no_estimate() {
+        let file = gcode_file("/prints/boat/boat.gcode", None, None, None, Some(60));
+        assert_eq!(estimate_file(&file, &test_config()), None);
+    }
+
+    #[test]
+    fn estimates_models_from_their_volume() {
+        //inside out meshes have a negative volume
+        let estimate = estimate_file(&model_file("/prints/cube/cube.stl", -8000.0), &test_config()).unwrap();
+        assert_eq!(estimate.material, "PLA");
+        //8 cm³ at 1.25 g/cm³ and half of it printed
+        assert_close(estimate.grams, 5.0);
+        assert_close(estimate.material_cost, 0.1);
+        assert_close(estimate.energy_cost, 0.0);
+        assert_eq!(estimate.seconds, None);
+        assert_eq!(estimate_file(&model_file("/prints/cube/cube.stl", 0.0), &test_config()), None);
+        assert_eq!(estimate_file(&ProjectFile::default(), &test_config()), None);
+    }
+
+    #[test]
+    fn sliced_projects_only_count_their_gcode() {
+        let project = Project {
+            name: "Boat".to_string(),
+            files: vec![
+                model_file("/prints/boat/hull.stl", 8000.0),
+                gcode_file("/prints/boat/hull.gcode", Some("PLA"), Some(50.0), None, Some(3600)),
+                gcode_file("/prints/boat/mast.gcode", Some("PETG"), Some(10.0), None, None),
+            ],
+            ..Default::default()
+        };
+        let config = test_config();
+        let estimates = project_estimates(&project, &config);
+        assert_eq!(estimates.iter().map(|(file, _)| file.path.as_str()).collect::<Vec<_>>(), vec!["/prints/boat/hull.gcode", "/prints/boat/mast.gcode"]);
+        let total = total(&estimates).unwrap();
+        assert_eq!(total.material, "PETG, PLA");
+        assert_close(total.grams, 60.0);
+        assert_eq!(total.seconds, Some(3600));
+        assert_close(total.material_cost, 1.3);
+        assert_close(total.energy_cost, 0.1);
+
+        let text = quote(&project, &config);
+        assert!(text.starts_with("Quote: Boat\n"));
+        assert!(text.contains("hull.gcode"));
+        assert!(!text.contains("hull.stl"));
+        assert!(text.contains("Filament: 60.0 g\n"));
+        assert!(text.contains("Total: $1.40\n"));
+        assert!(text.contains("Rates: PLA $20.00/kg, PETG $30.00/kg, electricity $0.50/kWh at 200 W\n"));
+        assert!(!text.contains("model volume"));
+    }
+
+    #[test]
+    fn unsliced_projects_are_quoted_from_model_volume() {
+        let project = Project { name: "Cube".to_string(), files: vec![model_file("/prints/cube/cube.stl", 8000.0)], ..Default::default() };
+        let text = quote(&project, &test_config());
+        assert!(text.contains("Total: $0.10\n"));
+        assert!(text.contains("Estimated from model volume at 50% fill"));
+        let empty = Project { name: "Empty".to_string(), ..Default::default() };
+        assert_eq!(total(&project_estimates(&empty, &test_config())), None);
+        assert!(quote(&empty, &test_config()).contains("No sliced G-code or measured models"));
+    }
+}
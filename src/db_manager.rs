@@ -14,19 +14,50 @@
  * You should have received a copy of the GNU General Public License
  * along with this program.  If not, see <http://www.gnu.org/licenses/>.
  */
-use rusqlite::{params, Connection, Result};
+use std::cell::OnceCell;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension, Result, Row, ToSql};
 use rust_embed::{Embed};
 #[allow(unused)]
 use log::{error, warn, info, debug, trace};
+use crate::file_hash::{self, FileIdentity};
 use crate::models;
 use models::project::Project;
 use models::project_tag::ProjectTag;
 use models::file::ProjectFile;
+use models::gcode_info::GcodeInfo;
+use models::mesh_info::MeshInfo;
+use models::project_filter::ProjectFilter;
+use crate::models::collection::Collection;
+use crate::models::duplicate_group::DuplicateGroup;
+use crate::models::print_job::{PrintHistory, PrintJob, PrintOutcome};
 use crate::models::project_source::ProjectSource;
+use crate::models::queue_item::{QueueItem, QueuePriority};
+use crate::models::saved_search::SavedSearch;
+use crate::models::spool::Spool;
+use crate::models::status_change::StatusChange;
+use crate::models::thumbnail_cache_entry::ThumbnailCacheEntry;
+use crate::search::{self, SearchSnippet};
+use crate::search::query::{SearchQuery, Sort, SortKey};
 
 pub struct DbManager {
     connection: Connection,
+    /// Whether the full text index can be searched, checked the first time it's needed.
+    full_text_search: OnceCell<bool>,
 }
+
+/// Columns read by project_file_from_row, joined with the mesh and G-code metadata when there is any.
+const PROJECT_FILE_SELECT: &str = "SELECT pf.id, pf.path, pf.notes, pf.project_id, pf.isdefault, \
+    fm.size_x, fm.size_y, fm.size_z, fm.triangle_count, fm.surface_area, fm.volume, fm.watertight, \
+    fg.project_file_id, fg.slicer, fg.print_time, fg.filament_length, fg.filament_weight, fg.filament_type, \
+    fg.layer_height, fg.nozzle_temperature, fg.bed_temperature, fg.has_thumbnail \
+    FROM project_files pf LEFT JOIN file_meshes fm ON fm.project_file_id = pf.id \
+    LEFT JOIN file_gcodes fg ON fg.project_file_id = pf.id";
+/// Id, path, hash, size and modification time of a file as it was last scanned.
+type RecordedFile = (i32, String, Option<String>, Option<i64>, Option<i64>);
 #[derive(Embed)]
 #[folder = "migrations/"]
 struct Migrations;
@@ -34,33 +65,62 @@ struct Migrations;
 impl DbManager {
     pub fn new(connection_string: String) -> DbManager {
         let conn = Connection::open(connection_string).unwrap();
+        //thumbnail workers open their own connections, wait for each other instead of failing
+        let _ = conn.busy_timeout(Duration::from_secs(5));
         let _ = conn.execute(
             "create table if not exists _migrations (version VARCHAR(50) NOT NULL, run_on TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL)",
             params![]
         );
-        DbManager { connection: conn }
+        DbManager { connection: conn, full_text_search: OnceCell::new() }
 
     }
     pub fn run_migration(&self) {
         let current_version = self.connection.query_one("SELECT * FROM _migrations ORDER BY version DESC LIMIT 1", params![], |row| {
-            Ok(row.get::<usize, String>(0)?)
+            row.get::<usize, String>(0)
         }).unwrap_or("0".to_string()).parse::<i64>().unwrap();
         debug!("current version: {}", current_version);
         for file in Migrations::iter() {
             let file_parts = file.split("/").collect::<Vec<&str>>();
             if current_version < file_parts[0].parse::<i64>().unwrap() && file_parts[1] == "up.sql" {
                 info!("Running migration {}", file_parts[0]);
-                let current_file = Migrations::get(&file.to_string()).unwrap();
+                let current_file = Migrations::get(&file).unwrap();
                 let sql_to_run = std::str::from_utf8(&current_file.data).unwrap();
-                let _ = self.connection.execute_batch(sql_to_run);
-                let _ = self.connection.execute("INSERT INTO _migrations (version) VALUES (?)", &[&file_parts[0]]);
+                //later migrations build on this one, so stop and try again next start
+                if let Err(e) = self.run_migration_file(sql_to_run, file_parts[0]) {
+                    error!("Migration {} failed: {}", file_parts[0], e);
+                    return;
+                }
             }
         }
     }
 
+    /**
+     * Run one migration and record it, nothing is kept when any of it fails.
+     */
+    fn run_migration_file(&self, sql: &str, version: &str) -> Result<()> {
+        let transaction = self.connection.unchecked_transaction()?;
+        transaction.execute_batch(sql)?;
+        transaction.execute("INSERT INTO _migrations (version) VALUES (?)", [version])?;
+        transaction.commit()
+    }
+
+    /**
+     * Whether SQLite has FTS5 and the search index was created, without it searches fall back
+     * to LIKE and are not ranked.
+     */
+    fn has_full_text_search(&self) -> bool {
+        *self.full_text_search.get_or_init(|| {
+            let available = self.connection.prepare("SELECT rowid FROM search_index WHERE search_index MATCH 'a' LIMIT 0").is_ok();
+            if !available {
+                warn!("Full text search is not available, searching without it");
+            }
+            available
+        })
+    }
+
     pub fn get_project(&self, id: i32) -> Project {
         let mut stmt = self.connection.prepare(
-            "SELECT id, name, path, notes FROM projects where id = ?1",
+            "SELECT id, name, path, notes, missing, status FROM projects where id = ?1",
         ).unwrap();
 
         let mut project = stmt.query_one([id], |row| {
@@ -72,6 +132,8 @@ impl DbManager {
                 tags: vec![],
                 files: vec![],
                 sources: vec![],
+                missing: row.get(4)?,
+                status: row.get(5)?,
             })
         }).unwrap();
 
@@ -81,37 +143,169 @@ impl DbManager {
         project
     }
 
-    pub fn get_filtered_projects(&self, name: Option<String>, path: Option<String>, tags: Option<Vec<ProjectTag>>) -> Vec<Project> {
-        let mut sql = "select p.* from projects p".to_string();
-        //add joins if needed
-        if tags.is_some() {
-            sql.push_str(" JOIN projects_tags pt ON pt.project_id = p.id");
+    /**
+     * The query listing the projects a filter matches, with its parameters.
+     */
+    fn filtered_projects_sql(filter: &ProjectFilter, full_text_search: bool) -> (String, Vec<Box<dyn ToSql>>) {
+        let mut sql = "SELECT p.id, p.name, p.path, p.notes, p.missing, p.status FROM projects p".to_string();
+        let mut conditions: Vec<String> = vec![];
+        let mut values: Vec<Box<dyn ToSql>> = vec![];
+        let mut order = "p.name".to_string();
+        if let Some(query) = &filter.query {
+            //every word has to be somewhere in the project, not necessarily in the same field
+            for term in query.terms.iter() {
+                conditions.push(Self::term_sql(term, full_text_search, &mut values));
+            }
+            for term in query.excluded_terms.iter() {
+                conditions.push(format!("NOT {}", Self::term_sql(term, full_text_search, &mut values)));
+            }
+            //one of the tags in each group, tags are matched by name or alias so any case works
+            for group in query.tags.iter() {
+                let tag_ids = Self::tag_names_sql(group, &mut values);
+                conditions.push(format!("EXISTS (SELECT 1 FROM projects_tags pt WHERE pt.project_id = p.id AND pt.tag_id IN ({}))", tag_ids));
+            }
+            if !query.excluded_tags.is_empty() {
+                let tag_ids = Self::tag_names_sql(&query.excluded_tags, &mut values);
+                conditions.push(format!("NOT EXISTS (SELECT 1 FROM projects_tags pt WHERE pt.project_id = p.id AND pt.tag_id IN ({}))", tag_ids));
+            }
+            if let Some(name) = &query.name {
+                values.push(Box::new(name.clone()));
+                conditions.push(format!("instr(lower(p.name), lower(?{})) > 0", values.len()));
+            }
+            if let Some(path) = &query.path {
+                values.push(Box::new(path.clone()));
+                conditions.push(format!("instr(lower(p.path), lower(?{})) > 0", values.len()));
+            }
+        }
+        let sort = filter.query.as_ref().and_then(|query| query.sort).or(filter.sort).unwrap_or(Sort::new(SortKey::Relevance));
+        let direction = if sort.descending { "DESC" } else { "ASC" };
+        match sort.key {
+            SortKey::Relevance if full_text_search => {
+                if let Some(any_term) = filter.query.as_ref().and_then(|query| search::fts_any(&query.terms)) {
+                    values.push(Box::new(any_term));
+                    sql.push_str(format!(" LEFT JOIN (SELECT project_id, sum(rank) AS score FROM search_index \
+                        WHERE search_index MATCH ?{} GROUP BY project_id) s ON s.project_id = p.id", values.len()).as_str());
+                    //rank is negative with the best match lowest
+                    order = format!("s.score {}, p.name", direction);
+                }
+            }
+            SortKey::Relevance => {}
+            SortKey::Name => order = format!("p.name {}", direction),
+            SortKey::Added => order = format!("p.added {}, p.name", direction),
+        }
+        if let Some(path) = &filter.path {
+            values.push(Box::new(path.clone()));
+            conditions.push(format!("p.path = ?{}", values.len()));
         }
-        if tags.is_some() || path.is_some() || name.is_some() {
-            sql.push_str(" WHERE");
+        if let Some(collection_id) = filter.collection {
+            values.push(Box::new(collection_id));
+            sql.push_str(format!(" JOIN collections_projects cp ON cp.project_id = p.id AND cp.collection_id = ?{}", values.len()).as_str());
         }
-        if name.is_some() {
-            sql.push_str(format!(" name LIKE '%{}%'", name.clone().unwrap()).as_str());
+        if let Some(print_history) = filter.print_history {
+            let jobs = "FROM print_jobs j JOIN project_files pf ON pf.id = j.project_file_id WHERE pf.project_id = p.id";
+            conditions.push(match print_history {
+                PrintHistory::NeverPrinted => format!("NOT EXISTS (SELECT 1 {})", jobs),
+                PrintHistory::Printed => format!("EXISTS (SELECT 1 {})", jobs),
+                PrintHistory::FailedLastTime => format!("(SELECT j.outcome {} ORDER BY j.printed DESC, j.id DESC LIMIT 1) = 'failed'", jobs),
+            });
         }
-        if path.is_some() {
-            if name.is_some() {
-                sql.push_str(" AND");
+        if let Some(status) = &filter.status {
+            if status.is_empty() {
+                conditions.push("p.status IS NULL".to_string());
+            } else {
+                values.push(Box::new(status.clone()));
+                conditions.push(format!("lower(p.status) = lower(?{})", values.len()));
             }
-            sql.push_str(format!(" path = '{}'", path.clone().unwrap()).as_str());
         }
-
-        if tags.is_some() {
-            if path.is_some() || name.is_some() {
-                sql.push_str(" AND");
+        if let Some(missing) = filter.missing {
+            values.push(Box::new(missing));
+            conditions.push(format!("p.missing = ?{}", values.len()));
+        }
+        if let Some(tags) = &filter.tags {
+            //a tag also matches projects tagged with anything below it
+            for tag in tags.iter() {
+                values.push(Box::new(tag.id));
+                conditions.push(format!("EXISTS (SELECT 1 FROM projects_tags pt WHERE pt.project_id = p.id AND pt.tag_id IN \
+                    (WITH RECURSIVE d(id) AS (SELECT ?{} UNION SELECT t.id FROM tags t JOIN d ON t.parent_id = d.id) SELECT id FROM d))", values.len()));
             }
-            let my_tags = tags.unwrap();
-            let my_tags_id:Vec<String>= my_tags.iter().map(|tag| tag.id.to_string()).collect();
-            sql.push_str(format!(" pt.tag_id IN ({}) GROUP BY p.id HAVING COUNT(DISTINCT pt.tag_id) = {}", my_tags_id.join(",").to_string(), my_tags.len()).as_str());
         }
-        sql.push_str(" ORDER BY p.name");
+        if let Some(bed) = &filter.fits_bed {
+            values.push(Box::new(bed.x));
+            let x = values.len();
+            values.push(Box::new(bed.y));
+            let y = values.len();
+            values.push(Box::new(bed.z.unwrap_or(f64::MAX)));
+            let z = values.len();
+            //at least one measured model and none that are too big, either way round on the bed
+            conditions.push("EXISTS (SELECT 1 FROM project_files pf JOIN file_meshes fm ON fm.project_file_id = pf.id WHERE pf.project_id = p.id AND pf.removed IS NULL)".to_string());
+            conditions.push(format!("NOT EXISTS (SELECT 1 FROM project_files pf JOIN file_meshes fm ON fm.project_file_id = pf.id \
+                WHERE pf.project_id = p.id AND pf.removed IS NULL AND (fm.size_z > ?{z} OR NOT ((fm.size_x <= ?{x} AND fm.size_y <= ?{y}) OR (fm.size_y <= ?{x} AND fm.size_x <= ?{y}))))"));
+        }
+        if let Some(printer) = &filter.fits_printer {
+            let volume = printer.sorted_volume();
+            values.push(Box::new(volume[0]));
+            let small = values.len();
+            values.push(Box::new(volume[1]));
+            let middle = values.len();
+            values.push(Box::new(volume[2]));
+            let large = values.len();
+            //compare model sides against build volume sides smallest to largest so any rotation counts
+            conditions.push("EXISTS (SELECT 1 FROM project_files pf JOIN file_meshes fm ON fm.project_file_id = pf.id WHERE pf.project_id = p.id AND pf.removed IS NULL)".to_string());
+            conditions.push(format!("NOT EXISTS (SELECT 1 FROM project_files pf JOIN file_meshes fm ON fm.project_file_id = pf.id \
+                WHERE pf.project_id = p.id AND pf.removed IS NULL AND (min(fm.size_x, fm.size_y, fm.size_z) > ?{small} \
+                OR fm.size_x + fm.size_y + fm.size_z - min(fm.size_x, fm.size_y, fm.size_z) - max(fm.size_x, fm.size_y, fm.size_z) > ?{middle} \
+                OR max(fm.size_x, fm.size_y, fm.size_z) > ?{large}))"));
+        }
+        if !conditions.is_empty() {
+            sql.push_str(" WHERE ");
+            sql.push_str(conditions.join(" AND ").as_str());
+        }
+        if filter.collection.is_some() {
+            order = "cp.position".to_string();
+        }
+        sql.push_str(format!(" ORDER BY {}", order).as_str());
+        (sql, values)
+    }
+
+    /**
+     * A condition for projects containing a word or phrase, in the full text index or, without
+     * it, in the same fields the index covers.
+     */
+    fn term_sql(term: &str, full_text_search: bool, values: &mut Vec<Box<dyn ToSql>>) -> String {
+        if full_text_search {
+            values.push(Box::new(search::fts_term(term)));
+            return format!("p.id IN (SELECT project_id FROM search_index WHERE search_index MATCH ?{})", values.len());
+        }
+        values.push(Box::new(search::like_term(term)));
+        let index = values.len();
+        //coalesce so a missing note doesn't make the whole condition NULL when it's negated
+        let like = |column: &str| format!("coalesce({}, '') LIKE ?{} ESCAPE '\\'", column, index);
+        format!("({} OR {} OR EXISTS (SELECT 1 FROM project_files pf WHERE pf.project_id = p.id AND pf.removed IS NULL AND ({} OR {})) \
+            OR EXISTS (SELECT 1 FROM projects_tags pt JOIN tags t ON t.id = pt.tag_id WHERE pt.project_id = p.id AND {}) \
+            OR EXISTS (SELECT 1 FROM project_sources ps WHERE ps.project_id = p.id AND ({} OR {})))",
+            like("p.name"), like("p.notes"), like("pf.path"), like("pf.notes"), like("t.tag"), like("ps.name"), like("ps.url"))
+    }
+
+    /**
+     * A query for the ids of the tags with these names or aliases and everything below them.
+     */
+    fn tag_names_sql(names: &[String], values: &mut Vec<Box<dyn ToSql>>) -> String {
+        let mut placeholders = vec![];
+        for name in names.iter() {
+            values.push(Box::new(name.clone()));
+            placeholders.push(format!("lower(?{})", values.len()));
+        }
+        let placeholders = placeholders.join(", ");
+        format!("WITH RECURSIVE d(id) AS (SELECT id FROM tags WHERE lower(tag) IN ({placeholders}) \
+            UNION SELECT tag_id FROM tag_aliases WHERE lower(alias) IN ({placeholders}) \
+            UNION SELECT t.id FROM tags t JOIN d ON t.parent_id = d.id) SELECT id FROM d")
+    }
+
+    pub fn get_filtered_projects(&self, filter: &ProjectFilter) -> Vec<Project> {
+        let (sql, values) = Self::filtered_projects_sql(filter, self.has_full_text_search());
         debug!("{}", sql);
         let mut stmt = self.connection.prepare(sql.as_str(),).unwrap();
-        let projects :Vec<Project> = stmt.query_map([], |row| {
+        let projects :Vec<Project> = stmt.query_map(params_from_iter(values.iter()), |row| {
             Ok(Project {
                 id: row.get(0)?,
                 name: row.get(1)?,
@@ -120,50 +314,109 @@ impl DbManager {
                 tags: vec![],
                 files: vec![],
                 sources: vec![],
+                missing: row.get(4)?,
+                status: row.get(5)?,
             })
-        }).unwrap().into_iter().map(|r| r.unwrap()).collect();
-
-        /*for mut project in &projects.into_iter() {
-            project.files = self.project_get_files(project.id);
-            project.tags = self.project_get_tags(project.id);
-            project.sources = self.project_get_sources(project.id);
-        }*/
-        let my_projects = projects.iter().map(|p| {
+        }).unwrap().map(|r| r.unwrap()).collect();
+
+        projects.iter().map(|p| {
             let mut proj = p.clone();
             proj.sources = self.project_get_sources(proj.id);
             proj.files = self.project_get_files(proj.id);
             proj.tags = self.project_get_tags(proj.id);
             proj
-        }).collect();
-        my_projects
+        }).collect()
+    }
+
+    /**
+     * How many projects a filter matches, without loading them.
+     */
+    pub fn count_filtered_projects(&self, filter: &ProjectFilter) -> usize {
+        let (sql, values) = Self::filtered_projects_sql(filter, self.has_full_text_search());
+        let mut stmt = self.connection.prepare(format!("SELECT count(*) FROM ({})", sql).as_str()).unwrap();
+        let count: i64 = stmt.query_row(params_from_iter(values.iter()), |row| row.get(0)).unwrap();
+        count as usize
+    }
+
+    /**
+     * The best matching snippet for each project matching the search words, keyed by project id.
+     */
+    pub fn get_search_snippets(&self, query: &SearchQuery) -> HashMap<i32, SearchSnippet> {
+        let mut snippets = HashMap::new();
+        if !self.has_full_text_search() {
+            return snippets;
+        }
+        let Some(any_term) = search::fts_any(&query.terms) else { return snippets };
+        let mut stmt = self.connection.prepare(
+            "SELECT project_id, kind, snippet(search_index, -1, ?2, ?3, '…', 8) FROM search_index \
+            WHERE search_index MATCH ?1 ORDER BY rank",
+        ).unwrap();
+        let rows = stmt.query_map(params![any_term, search::HIGHLIGHT_START, search::HIGHLIGHT_END], |row| {
+            Ok((row.get::<usize, i32>(0)?, SearchSnippet { kind: row.get(1)?, text: row.get(2)? }))
+        }).unwrap();
+        for (project_id, snippet) in rows.map(|r| r.unwrap()) {
+            snippets.entry(project_id).or_insert(snippet);
+        }
+        snippets
+    }
+
+    fn project_file_from_row(row: &Row) -> Result<ProjectFile> {
+        let id: i32 = row.get(0)?;
+        let size_x: Option<f64> = row.get(5)?;
+        let mesh = match size_x {
+            Some(size_x) => Some(MeshInfo {
+                project_file_id: id,
+                size_x,
+                size_y: row.get(6)?,
+                size_z: row.get(7)?,
+                triangle_count: row.get(8)?,
+                surface_area: row.get(9)?,
+                volume: row.get(10)?,
+                watertight: row.get(11)?,
+            }),
+            None => None,
+        };
+        let gcode_file_id: Option<i32> = row.get(12)?;
+        let gcode = match gcode_file_id {
+            Some(project_file_id) => Some(GcodeInfo {
+                project_file_id,
+                slicer: row.get(13)?,
+                print_time: row.get(14)?,
+                filament_length: row.get(15)?,
+                filament_weight: row.get(16)?,
+                filament_type: row.get(17)?,
+                layer_height: row.get(18)?,
+                nozzle_temperature: row.get(19)?,
+                bed_temperature: row.get(20)?,
+                has_thumbnail: row.get(21)?,
+            }),
+            None => None,
+        };
+        Ok(ProjectFile {
+            id,
+            path: row.get(1)?,
+            notes: row.get(2)?,
+            project_id: row.get(3)?,
+            default: row.get(4)?,
+            mesh,
+            gcode,
+        })
     }
 
     pub fn project_get_files(&self, project_id: i32) -> Vec<ProjectFile> {
         let mut files_stmt = self.connection.prepare(
-            "SELECT id, path, notes, project_id, isdefault FROM project_files WHERE project_id = ?1 ORDER BY path",
-        ).unwrap();
-        let files :Vec<ProjectFile> = files_stmt.query_map([project_id], |row| {
-            Ok(ProjectFile {
-                id: row.get(0).unwrap(),
-                path: row.get(1).unwrap(),
-                notes: row.get(2).unwrap(),
-                project_id: row.get(3).unwrap(),
-                default: row.get(4).unwrap(),
-            })
-        }).unwrap().into_iter().map(|r| r.unwrap()).collect();
+            format!("{} WHERE pf.project_id = ?1 AND pf.removed IS NULL ORDER BY pf.path", PROJECT_FILE_SELECT).as_str(),
+        ).unwrap();
+        let files :Vec<ProjectFile> = files_stmt.query_map([project_id], Self::project_file_from_row)
+            .unwrap().map(|r| r.unwrap()).collect();
         files
     }
 
     pub fn project_get_tags(&self, project_id: i32) -> Vec<ProjectTag> {
         let mut tags_stmt = self.connection.prepare(
-            "SELECT t.id, t.tag FROM projects_tags pt LEFT JOIN tags t on pt.tag_id = t.id WHERE pt.project_id = ?1",
+            "SELECT t.id, t.tag, t.color, t.parent_id FROM projects_tags pt LEFT JOIN tags t on pt.tag_id = t.id WHERE pt.project_id = ?1",
         ).unwrap();
-        let tags :Vec<ProjectTag> = tags_stmt.query_map([project_id], |row| {
-            Ok(ProjectTag{
-                id: row.get(0).unwrap(),
-                tag: row.get(1).unwrap(),
-            })
-        }).unwrap().into_iter().map(|r| r.unwrap()).collect();
+        let tags :Vec<ProjectTag> = tags_stmt.query_map([project_id], Self::tag_from_row).unwrap().map(|r| r.unwrap()).collect();
         tags
     }
 
@@ -178,45 +431,557 @@ impl DbManager {
                 project_id: row.get(2)?,
                 name: row.get(3)?,
             })
-        }).unwrap().into_iter().map(|r| r.unwrap()).collect();
+        }).unwrap().map(|r| r.unwrap()).collect();
         sources
     }
     pub fn create_project(&self, project: Project) -> Result<Project> {
         self.connection.execute(
-            "INSERT INTO projects (name, path, notes) VALUES (?1, ?2, ?3)", params![project.name, project.path, project.notes],
+            "INSERT INTO projects (name, path, notes, added) VALUES (?1, ?2, ?3, CAST(strftime('%s', 'now') AS INTEGER))", params![project.name, project.path, project.notes],
         )?;
         let last_id = i32::try_from(self.connection.last_insert_rowid()).unwrap();
 
         Ok(self.get_project(last_id))
     }
 
-    pub fn update_project_files(&self, project: Project, file_system_files: Vec<String>) {
-        //get existing files for project
+    pub fn project_exists(&self, id: i32) -> bool {
+        self.connection.query_one("SELECT count(*) FROM projects WHERE id = ?1", [id], |row| row.get::<usize, i64>(0))
+            .is_ok_and(|count| count > 0)
+    }
+    pub fn get_project_by_path(&self, path: &str) -> Option<Project> {
+        let filter = ProjectFilter { path: Some(path.to_string()), ..Default::default() };
+        self.get_filtered_projects(&filter).into_iter().next()
+    }
+    pub fn set_project_missing(&self, project_id: i32, missing: bool) {
         let mut stmt = self.connection.prepare(
-            "SELECT path FROM project_files pf where project_id = ?1",
+            "UPDATE projects SET missing = ?2 WHERE id = ?1",
         ).unwrap();
+        let _ = stmt.execute(params![project_id, missing]);
+    }
 
-        let files_query_results = stmt.query_map([project.id], |row| {
-            row.get::<usize, String>(0)
-        }).unwrap();
-        let existing_files: Vec<String> = files_query_results.into_iter().map(|r| r.unwrap()).collect();
-        //files_query_results.
-        let files_to_add: Vec<_> = file_system_files.clone().into_iter().filter(|item| !existing_files.contains(item)).collect();
-        let files_to_delete: Vec<_> = existing_files.clone().into_iter().filter(|item| !file_system_files.contains(item)).collect();
-        let mut add_files_stmt = self.connection.prepare(
-            "INSERT INTO project_files (project_id, path) VALUES (?1, ?2)",
-        ).unwrap();
-        for path in files_to_add.clone() {
-            let _ = add_files_stmt.execute((project.id, path));
-        };
-        let mut delete_files_stmt = self.connection.prepare(
-            "DELETE FROM project_files WHERE project_id = ?1 AND path = ?2;",
+    /**
+     * Make the project's files match what's on disk, returns how many were added and removed.
+     * Files are recognised by their content, so one renamed here or moved in from another project
+     * keeps its row along with its notes, default flag and anything recorded against it.  Files
+     * that disappear are only marked removed, that way a move noticed in the other project first
+     * still finds them, purge_removed_files deletes them for good.  `batch_started` is when the
+     * sync this is part of started, files removed from other projects before then are left alone.
+     */
+    pub fn update_project_files(&self, project: Project, file_system_files: Vec<String>, batch_started: i64) -> (usize, usize) {
+        //get existing files for project
+        let mut stmt = self.connection.prepare(
+            "SELECT id, path, hash, size, mtime FROM project_files pf WHERE project_id = ?1 AND removed IS NULL",
         ).unwrap();
-        for path in files_to_delete.clone() {
-            let _ = delete_files_stmt.execute((project.id, path));
-        };
-        info!("{} added files: {:?}", project.name, files_to_add);
+        let existing_files: Vec<RecordedFile> = stmt.query_map([project.id], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?))
+        }).unwrap().map(|r| r.unwrap()).collect();
+        let files_to_add: Vec<String> = file_system_files.iter()
+            .filter(|path| !existing_files.iter().any(|(_, existing, ..)| existing == *path))
+            .cloned()
+            .collect();
+        let mut files_to_delete = vec![];
+        for (id, path, hash, size, mtime) in existing_files {
+            if !file_system_files.contains(&path) {
+                let _ = self.connection.execute(
+                    "UPDATE project_files SET removed = ?2 WHERE id = ?1",
+                    params![id, file_hash::now_secs()],
+                );
+                files_to_delete.push(path);
+                continue;
+            }
+            //only hash again when the file changed since it was last seen
+            let Ok(metadata) = fs::metadata(&path) else { continue };
+            if (hash.is_none() || !FileIdentity::is_current(size, mtime, &metadata))
+                && let Ok(identity) = FileIdentity::read(&path) {
+                self.set_file_identity(id, &identity);
+            }
+        }
+        let mut files_added = vec![];
+        let mut files_moved = vec![];
+        for path in files_to_add {
+            let identity = FileIdentity::read(&path).ok();
+            let file_id = match identity.as_ref().and_then(|identity| self.find_moved_file(project.id, &path, identity, batch_started)) {
+                Some((file_id, old_path)) => {
+                    self.move_project_file(file_id, project.id, &path);
+                    files_moved.push((old_path, path));
+                    file_id
+                }
+                None => {
+                    if let Err(e) = self.connection.execute(
+                        "INSERT INTO project_files (project_id, path) VALUES (?1, ?2)",
+                        params![project.id, path],
+                    ) {
+                        error!("Could not add file {}: {}", path, e);
+                        continue;
+                    }
+                    files_added.push(path);
+                    self.connection.last_insert_rowid() as i32
+                }
+            };
+            if let Some(identity) = identity {
+                self.set_file_identity(file_id, &identity);
+            }
+        }
+        files_to_delete.retain(|path| !files_moved.iter().any(|(old_path, _)| old_path == path));
+        info!("{} added files: {:?}", project.name, files_added);
+        info!("{} moved files: {:?}", project.name, files_moved);
         info!("{} deleted files: {:?}", project.name, files_to_delete);
+        (files_added.len(), files_to_delete.len())
+    }
+    fn set_file_identity(&self, file_id: i32, identity: &FileIdentity) {
+        let _ = self.connection.execute(
+            "UPDATE project_files SET hash = ?2, size = ?3, mtime = ?4 WHERE id = ?1",
+            params![file_id, identity.hash, identity.size, identity.mtime],
+        );
+    }
+    /**
+     * A file with the same content that is no longer where it was recorded or was removed from
+     * this very path, removed files and files in this project first.  Files from other projects
+     * only count when they went missing in this sync, otherwise a new copy of an old download
+     * would take over the notes and prints of a file deleted weeks ago.  Returns its id and old path.
+     */
+    fn find_moved_file(&self, project_id: i32, path: &str, identity: &FileIdentity, batch_started: i64) -> Option<(i32, String)> {
+        let mut stmt = self.connection.prepare(
+            "SELECT id, path FROM project_files WHERE hash = ?1 AND size = ?2 AND (removed IS NOT NULL OR project_id != ?3) \
+            AND (project_id = ?3 OR removed IS NULL OR removed >= ?4) \
+            ORDER BY project_id = ?3 DESC, removed DESC",
+        ).unwrap();
+        stmt.query_map(params![identity.hash, identity.size, project_id, batch_started], |row| Ok((row.get(0)?, row.get::<usize, String>(1)?)))
+            .unwrap()
+            .map(|r| r.unwrap())
+            .find(|(_, old_path)| old_path == path || !Path::new(old_path).exists())
+    }
+    /**
+     * Give a file row its new path, a file moved from another project only stays the default
+     * when this project doesn't have one.
+     */
+    fn move_project_file(&self, file_id: i32, project_id: i32, path: &str) {
+        let _ = self.connection.execute(
+            "UPDATE project_files SET path = ?3, project_id = ?2, removed = NULL, \
+            isdefault = isdefault AND (project_id = ?2 OR NOT EXISTS \
+                (SELECT 1 FROM project_files WHERE project_id = ?2 AND isdefault = 1 AND removed IS NULL)) \
+            WHERE id = ?1",
+            params![file_id, project_id, path],
+        );
+    }
+    /**
+     * Forget files removed before the given time, returns how many were deleted.  Files that
+     * have been printed are kept for their print history.
+     */
+    pub fn purge_removed_files(&self, removed_before: i64) -> usize {
+        self.connection.execute(
+            "DELETE FROM project_files WHERE removed < ?1 AND id NOT IN (SELECT project_file_id FROM print_jobs) \
+            AND id NOT IN (SELECT project_file_id FROM print_queue)",
+            [removed_before],
+        ).unwrap_or(0)
+    }
+    /**
+     * Model files whose content turns up in more than one project, the biggest first.
+     */
+    pub fn get_duplicate_files(&self) -> Vec<DuplicateGroup> {
+        let mut stmt = self.connection.prepare(
+            "SELECT hash, size FROM project_files WHERE removed IS NULL AND hash IS NOT NULL AND size > 0 \
+            GROUP BY hash, size HAVING count(DISTINCT project_id) > 1 ORDER BY size * (count(*) - 1) DESC",
+        ).unwrap();
+        let hashes: Vec<(String, i64)> = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap().map(|r| r.unwrap()).collect();
+        let mut names_stmt = self.connection.prepare("SELECT id, name FROM projects").unwrap();
+        let project_names: HashMap<i32, String> = names_stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap().map(|r| r.unwrap()).collect();
+        let mut files_stmt = self.connection.prepare(
+            format!("{} WHERE pf.hash = ?1 AND pf.removed IS NULL ORDER BY pf.path", PROJECT_FILE_SELECT).as_str(),
+        ).unwrap();
+        hashes.into_iter()
+            .map(|(hash, size)| {
+                let files = files_stmt.query_map([&hash], Self::project_file_from_row)
+                    .unwrap()
+                    .map(|r| r.unwrap())
+                    .map(|file| {
+                        let project_name = project_names.get(&file.project_id).cloned().unwrap_or_default();
+                        (file, project_name)
+                    })
+                    .collect();
+                DuplicateGroup { hash, size, files }
+            })
+            .filter(|group| group.files.iter().any(|(file, _)| file.is_model_type()))
+            .collect()
+    }
+    /**
+     * Record a print, an empty date means today.  The filament used comes off the spool, a spool
+     * can't go below empty so what was actually taken off is kept to give back if the print is
+     * deleted.  Returns the new job's id.
+     */
+    pub fn add_print_job(&self, print_job: &PrintJob) -> Result<i32> {
+        let transaction = self.connection.unchecked_transaction()?;
+        let filament_deducted: Option<f64> = match (print_job.spool_id, print_job.filament_used) {
+            (Some(spool_id), Some(grams)) => transaction.query_one(
+                "SELECT max(0, min(remaining_weight, ?2)) FROM spools WHERE id = ?1",
+                params![spool_id, grams],
+                |row| row.get(0),
+            ).optional()?,
+            _ => None,
+        };
+        let queue_item_id = if print_job.outcome == PrintOutcome::Success {
+            Self::queue_count_print(&transaction, print_job.project_file_id)?
+        } else {
+            None
+        };
+        transaction.execute(
+            "INSERT INTO print_jobs (project_file_id, printed, printer, material, color, duration, outcome, failure_reason, notes, \
+            spool_id, filament_used, filament_deducted, queue_item_id) \
+            VALUES (?1, coalesce(nullif(?2, ''), date('now', 'localtime')), ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+            params![print_job.project_file_id, print_job.printed, print_job.printer, print_job.material, print_job.color,
+                print_job.duration, print_job.outcome.keyword(), print_job.failure_reason, print_job.notes,
+                print_job.spool_id, print_job.filament_used, filament_deducted, queue_item_id],
+        )?;
+        let print_job_id = transaction.last_insert_rowid() as i32;
+        if let (Some(spool_id), Some(grams)) = (print_job.spool_id, filament_deducted) {
+            transaction.execute(
+                "UPDATE spools SET remaining_weight = remaining_weight - ?2 WHERE id = ?1",
+                params![spool_id, grams],
+            )?;
+        }
+        for photo in print_job.photos.iter() {
+            transaction.execute(
+                "INSERT INTO print_job_photos (print_job_id, path) VALUES (?1, ?2)",
+                params![print_job_id, photo],
+            )?;
+        }
+        transaction.commit()?;
+        Ok(print_job_id)
+    }
+    /**
+     * Every print of the project's files with the path of the file printed, newest first.
+     */
+    pub fn get_project_print_jobs(&self, project_id: i32) -> Vec<(PrintJob, String)> {
+        let mut stmt = self.connection.prepare(
+            "SELECT j.id, j.project_file_id, j.printed, j.printer, j.material, j.color, j.duration, j.outcome, j.failure_reason, j.notes, \
+            j.spool_id, j.filament_used, pf.path \
+            FROM print_jobs j JOIN project_files pf ON pf.id = j.project_file_id WHERE pf.project_id = ?1 ORDER BY j.printed DESC, j.id DESC",
+        ).unwrap();
+        let jobs: Vec<(PrintJob, String)> = stmt.query_map([project_id], |row| Ok((Self::print_job_from_row(row)?, row.get(12)?)))
+            .unwrap().map(|r| r.unwrap()).collect();
+        let mut photos_stmt = self.connection.prepare(
+            "SELECT path FROM print_job_photos WHERE print_job_id = ?1 ORDER BY id",
+        ).unwrap();
+        jobs.into_iter()
+            .map(|(mut job, path)| {
+                job.photos = photos_stmt.query_map([job.id], |row| row.get(0)).unwrap().map(|r| r.unwrap()).collect();
+                (job, path)
+            })
+            .collect()
+    }
+    fn print_job_from_row(row: &Row) -> Result<PrintJob> {
+        let outcome: String = row.get(7)?;
+        Ok(PrintJob {
+            id: row.get(0)?,
+            project_file_id: row.get(1)?,
+            printed: row.get(2)?,
+            printer: row.get(3)?,
+            material: row.get(4)?,
+            color: row.get(5)?,
+            duration: row.get(6)?,
+            outcome: PrintOutcome::parse(&outcome).unwrap_or(PrintOutcome::Success),
+            failure_reason: row.get(8)?,
+            notes: row.get(9)?,
+            photos: vec![],
+            spool_id: row.get(10)?,
+            filament_used: row.get(11)?,
+        })
+    }
+    /**
+     * Forget a print, the filament taken off its spool goes back on and the queue item it was
+     * counted on loses the print.
+     */
+    pub fn delete_print_job(&self, print_job_id: i32) {
+        let Ok(transaction) = self.connection.unchecked_transaction() else { return };
+        if let Err(e) = transaction.execute(
+            "UPDATE spools SET remaining_weight = min(initial_weight, remaining_weight + \
+                (SELECT filament_deducted FROM print_jobs WHERE id = ?1)) \
+            WHERE id = (SELECT spool_id FROM print_jobs WHERE id = ?1 AND filament_deducted IS NOT NULL)",
+            [print_job_id],
+        ) {
+            error!("Could not give the filament of print {} back: {}", print_job_id, e);
+            return;
+        }
+        for sql in [
+            "UPDATE print_queue SET printed = max(0, printed - 1) WHERE id = (SELECT queue_item_id FROM print_jobs WHERE id = ?1)",
+            "DELETE FROM print_job_photos WHERE print_job_id = ?1",
+            "DELETE FROM print_jobs WHERE id = ?1",
+        ] {
+            if let Err(e) = transaction.execute(sql, [print_job_id]) {
+                error!("Could not delete print {}: {}", print_job_id, e);
+                return;
+            }
+        }
+        let _ = transaction.commit();
+    }
+    /**
+     * Queue copies of a file, adding to the file's unfinished queue item with the same priority
+     * when it has one.
+     */
+    pub fn add_to_queue(&self, project_file_id: i32, quantity: i32, priority: QueuePriority) {
+        let added = self.connection.execute(
+            "UPDATE print_queue SET quantity = quantity + ?2 WHERE id = \
+                (SELECT id FROM print_queue WHERE project_file_id = ?1 AND priority = ?3 AND printed < quantity ORDER BY position LIMIT 1)",
+            params![project_file_id, quantity, priority.value()],
+        ).unwrap_or(0);
+        if added == 0 {
+            let _ = self.connection.execute(
+                "INSERT INTO print_queue (project_file_id, quantity, priority, position) \
+                VALUES (?1, ?2, ?3, (SELECT coalesce(max(position) + 1, 0) FROM print_queue))",
+                params![project_file_id, quantity, priority.value()],
+            );
+        }
+    }
+    /**
+     * Everything queued, highest priority first and then in queue order.
+     */
+    pub fn get_print_queue(&self) -> Vec<QueueItem> {
+        let mut stmt = self.connection.prepare(
+            "SELECT q.id, q.project_file_id, q.quantity, q.printed, q.priority, q.printer, p.name FROM print_queue q \
+            JOIN project_files pf ON pf.id = q.project_file_id JOIN projects p ON p.id = pf.project_id \
+            ORDER BY q.priority DESC, q.position",
+        ).unwrap();
+        stmt.query_map([], |row| {
+            Ok(QueueItem {
+                id: row.get(0)?,
+                file: self.get_project_file_by_id(row.get(1)?),
+                project_name: row.get(6)?,
+                quantity: row.get(2)?,
+                printed: row.get(3)?,
+                priority: QueuePriority::from_value(row.get(4)?),
+                printer: row.get(5)?,
+            })
+        }).unwrap().map(|r| r.unwrap()).collect()
+    }
+    pub fn update_queue_item(&self, item: &QueueItem) {
+        let _ = self.connection.execute(
+            "UPDATE print_queue SET quantity = ?2, printed = ?3, priority = ?4, printer = ?5 WHERE id = ?1",
+            params![item.id, item.quantity, item.printed, item.priority.value(), item.printer],
+        );
+    }
+    pub fn delete_queue_item(&self, item_id: i32) -> Result<()> {
+        let transaction = self.connection.unchecked_transaction()?;
+        for sql in [
+            "UPDATE print_jobs SET queue_item_id = NULL WHERE queue_item_id = ?1",
+            "DELETE FROM print_queue WHERE id = ?1",
+        ] {
+            transaction.execute(sql, [item_id])?;
+        }
+        transaction.commit()
+    }
+    /**
+     * Remove every item that has been printed as many times as wanted, returns how many went.
+     */
+    pub fn clear_finished_queue_items(&self) -> usize {
+        let _ = self.connection.execute(
+            "UPDATE print_jobs SET queue_item_id = NULL WHERE queue_item_id IN (SELECT id FROM print_queue WHERE printed >= quantity)",
+            [],
+        );
+        self.connection.execute("DELETE FROM print_queue WHERE printed >= quantity", []).unwrap_or(0)
+    }
+    /**
+     * Move a queue item to the index in queue order, renumbering the positions to match.
+     */
+    pub fn move_queue_item(&self, item_id: i32, index: usize) -> Result<()> {
+        let transaction = self.connection.unchecked_transaction()?;
+        let mut item_ids: Vec<i32> = transaction
+            .prepare("SELECT id FROM print_queue ORDER BY priority DESC, position")?
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<_>>()?;
+        let Some(current) = item_ids.iter().position(|id| *id == item_id) else { return Ok(()) };
+        item_ids.remove(current);
+        item_ids.insert(index.min(item_ids.len()), item_id);
+        {
+            let mut update_stmt = transaction.prepare("UPDATE print_queue SET position = ?2 WHERE id = ?1")?;
+            for (position, id) in item_ids.iter().enumerate() {
+                update_stmt.execute(params![id, position as i64])?;
+            }
+        }
+        transaction.commit()
+    }
+    /**
+     * Count a print of the file against the first unfinished queue item for it, returns the
+     * item's id so deleting the print takes it off the same item.
+     */
+    fn queue_count_print(connection: &Connection, project_file_id: i32) -> Result<Option<i32>> {
+        let item_id: Option<i32> = connection.query_one(
+            "SELECT id FROM print_queue WHERE project_file_id = ?1 AND printed < quantity ORDER BY priority DESC, position LIMIT 1",
+            [project_file_id],
+            |row| row.get(0),
+        ).optional()?;
+        if let Some(item_id) = item_id {
+            connection.execute("UPDATE print_queue SET printed = printed + 1 WHERE id = ?1", [item_id])?;
+        }
+        Ok(item_id)
+    }
+    pub fn get_spools(&self) -> Vec<Spool> {
+        let mut stmt = self.connection.prepare(
+            "SELECT id, brand, material, color, diameter, initial_weight, remaining_weight, price FROM spools \
+            ORDER BY material, brand, color",
+        ).unwrap();
+        let spools: Vec<Spool> = stmt.query_map([], Self::spool_from_row).unwrap().map(|r| r.unwrap()).collect();
+        spools
+    }
+    /**
+     * Spools with less than the given grams left, emptiest first.
+     */
+    pub fn get_low_spools(&self, grams: f64) -> Vec<Spool> {
+        let mut stmt = self.connection.prepare(
+            "SELECT id, brand, material, color, diameter, initial_weight, remaining_weight, price FROM spools \
+            WHERE remaining_weight < ?1 ORDER BY remaining_weight",
+        ).unwrap();
+        let spools: Vec<Spool> = stmt.query_map([grams], Self::spool_from_row).unwrap().map(|r| r.unwrap()).collect();
+        spools
+    }
+    fn spool_from_row(row: &Row) -> Result<Spool> {
+        Ok(Spool {
+            id: row.get(0)?,
+            brand: row.get(1)?,
+            material: row.get(2)?,
+            color: row.get(3)?,
+            diameter: row.get(4)?,
+            initial_weight: row.get(5)?,
+            remaining_weight: row.get(6)?,
+            price: row.get(7)?,
+        })
+    }
+    /**
+     * Adds the spool, or updates it when it already has an id.  Returns its id.
+     */
+    pub fn save_spool(&self, spool: &Spool) -> i32 {
+        if spool.id > 0 {
+            let _ = self.connection.execute(
+                "UPDATE spools SET brand = ?2, material = ?3, color = ?4, diameter = ?5, initial_weight = ?6, \
+                remaining_weight = ?7, price = ?8 WHERE id = ?1",
+                params![spool.id, spool.brand, spool.material, spool.color, spool.diameter, spool.initial_weight,
+                    spool.remaining_weight, spool.price],
+            );
+            return spool.id;
+        }
+        let _ = self.connection.execute(
+            "INSERT INTO spools (brand, material, color, diameter, initial_weight, remaining_weight, price) \
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![spool.brand, spool.material, spool.color, spool.diameter, spool.initial_weight,
+                spool.remaining_weight, spool.price],
+        );
+        self.connection.last_insert_rowid() as i32
+    }
+    /**
+     * Remove a spool, prints made with it keep how much filament they used.
+     */
+    pub fn delete_spool(&self, spool_id: i32) -> Result<()> {
+        let transaction = self.connection.unchecked_transaction()?;
+        for sql in [
+            "UPDATE print_jobs SET spool_id = NULL WHERE spool_id = ?1",
+            "DELETE FROM spools WHERE id = ?1",
+        ] {
+            transaction.execute(sql, [spool_id])?;
+        }
+        transaction.commit()
+    }
+    /**
+     * Move the project to a workflow state, or clear it, recording when it happened.
+     * Nothing is recorded when the project is already in that state.
+     */
+    pub fn set_project_status(&self, project_id: i32, status: Option<&str>) {
+        let changed = self.connection.execute(
+            "UPDATE projects SET status = ?2 WHERE id = ?1 AND status IS NOT ?2",
+            params![project_id, status],
+        ).unwrap_or(0);
+        if changed > 0 {
+            let _ = self.connection.execute(
+                "INSERT INTO project_status_history (project_id, status, changed) VALUES (?1, ?2, datetime('now', 'localtime'))",
+                params![project_id, status],
+            );
+        }
+    }
+    /**
+     * Every status the project has been moved to, newest first.
+     */
+    pub fn get_project_status_history(&self, project_id: i32) -> Vec<StatusChange> {
+        let mut stmt = self.connection.prepare(
+            "SELECT id, project_id, status, changed FROM project_status_history WHERE project_id = ?1 ORDER BY changed DESC, id DESC",
+        ).unwrap();
+        stmt.query_map([project_id], |row| {
+            Ok(StatusChange {
+                id: row.get(0)?,
+                project_id: row.get(1)?,
+                status: row.get(2)?,
+                changed: row.get(3)?,
+            })
+        }).unwrap().map(|r| r.unwrap()).collect()
+    }
+    pub fn get_related_projects(&self, project_id: i32) -> Vec<Project> {
+        let mut stmt = self.connection.prepare(
+            "SELECT related_id FROM related_projects WHERE project_id = ?1 \
+            UNION SELECT project_id FROM related_projects WHERE related_id = ?1",
+        ).unwrap();
+        let ids: Vec<i32> = stmt.query_map([project_id], |row| row.get(0)).unwrap().map(|r| r.unwrap()).collect();
+        let mut projects: Vec<Project> = ids.into_iter().map(|id| self.get_project(id)).collect();
+        projects.sort_by_key(|project| project.name.to_lowercase());
+        projects
+    }
+    /**
+     * Mark every pair of the projects as related, each pair is stored once with the lower id first.
+     */
+    pub fn relate_projects(&self, project_ids: &[i32]) {
+        for project_id in project_ids {
+            for related_id in project_ids.iter().filter(|related_id| *related_id > project_id) {
+                let _ = self.connection.execute(
+                    "INSERT OR IGNORE INTO related_projects (project_id, related_id) VALUES (?1, ?2)",
+                    params![project_id, related_id],
+                );
+            }
+        }
+    }
+    pub fn unrelate_projects(&self, project_id: i32, related_id: i32) {
+        let _ = self.connection.execute(
+            "DELETE FROM related_projects WHERE (project_id = ?1 AND related_id = ?2) OR (project_id = ?2 AND related_id = ?1)",
+            params![project_id, related_id],
+        );
+    }
+    /**
+     * Point a project at a new directory, keeping file notes by moving the file paths along with it.
+     * Returns false, changing nothing, when the directory is already another project.
+     */
+    pub fn relink_project(&self, project: &Project, new_path: &str) -> bool {
+        let Ok(transaction) = self.connection.unchecked_transaction() else { return false };
+        let relinked = transaction.execute(
+            "UPDATE project_files SET path = ?3 || substr(path, length(?2) + 1) \
+            WHERE project_id = ?1 AND substr(path, 1, length(?2)) = ?2",
+            params![project.id, project.path, new_path],
+        ).is_ok() && transaction.execute(
+            "UPDATE projects SET path = ?2, missing = 0 WHERE id = ?1",
+            params![project.id, new_path],
+        ).is_ok();
+        relinked && transaction.commit().is_ok()
+    }
+    /**
+     * Remove a project and everything recorded about it, all of it or nothing.  Files on disk
+     * are not touched.
+     */
+    pub fn delete_project(&self, project_id: i32) {
+        let Ok(transaction) = self.connection.unchecked_transaction() else { return };
+        for sql in [
+            "DELETE FROM projects_tags WHERE project_id = ?1",
+            "DELETE FROM project_sources WHERE project_id = ?1",
+            "DELETE FROM collections_projects WHERE project_id = ?1",
+            "DELETE FROM project_status_history WHERE project_id = ?1",
+            "DELETE FROM related_projects WHERE project_id = ?1 OR related_id = ?1",
+            "DELETE FROM print_job_photos WHERE print_job_id IN \
+                (SELECT j.id FROM print_jobs j JOIN project_files pf ON pf.id = j.project_file_id WHERE pf.project_id = ?1)",
+            "DELETE FROM print_jobs WHERE project_file_id IN (SELECT id FROM project_files WHERE project_id = ?1)",
+            "DELETE FROM print_queue WHERE project_file_id IN (SELECT id FROM project_files WHERE project_id = ?1)",
+            "DELETE FROM project_files WHERE project_id = ?1",
+            "DELETE FROM projects WHERE id = ?1",
+        ] {
+            if let Err(e) = transaction.execute(sql, [project_id]) {
+                error!("Could not delete project {}: {}", project_id, e);
+                return;
+            }
+        }
+        let _ = transaction.commit();
     }
     pub fn project_remove_tag(&self, project: Project, tag: ProjectTag) -> Project {
         let mut stmt = self.connection.prepare(
@@ -226,17 +991,17 @@ impl DbManager {
         stmt.execute(params![project.id, tag.id]).unwrap();
         self.get_project(project.id)
     }
+    /**
+     * Tag a project, the tag can be a path like "Games > Warhammer > Terrain" and aliases are
+     * resolved to their tag.  Missing tags are created.
+     */
     pub fn project_add_tag(&self, project: Project, tag: String) -> Project {
-        let mut my_tag = self.get_tag_by_tag(tag.clone());
-        if my_tag.is_err() {
-            my_tag = self.add_tag(tag.clone());
-        }
-        let my_tag_2 = my_tag.unwrap();
+        let Some(my_tag_2) = self.get_or_add_tag_path(&tag) else { return self.get_project(project.id) };
         let mut proj_have_tag_stmt = self.connection.prepare(
             "SELECT count(*) FROM projects_tags WHERE project_id = ?1 AND tag_id = ?2",
         ).unwrap();
         let tag_count:Result<i32> = proj_have_tag_stmt.query_one([project.id, my_tag_2.id], |row | {
-            Ok(row.get(0)?)
+            row.get(0)
         });
         if tag_count.unwrap() == 0 {
             let mut stmt = self.connection.prepare(
@@ -247,49 +1012,218 @@ impl DbManager {
         self.get_project(project.id)
     }
 
+    /**
+     * Find a tag by name or alias ignoring case, top level tags win when the name is used more than once.
+     */
     pub fn get_tag_by_tag(&self, tag: String) -> Result<ProjectTag> {
+        let mut alias_stmt = self.connection.prepare(
+            "SELECT t.id, t.tag, t.color, t.parent_id FROM tag_aliases a JOIN tags t ON t.id = a.tag_id WHERE a.alias = ?1",
+        )?;
+        if let Some(alias) = alias_stmt.query_one([tag.clone()], Self::tag_from_row).optional()? {
+            return Ok(alias);
+        }
         let mut stmt = self.connection.prepare(
-            "Select id, tag FROM tags WHERE tag = ?1 LIMIT 1",
+            "Select id, tag, color, parent_id FROM tags WHERE lower(tag) = lower(?1) ORDER BY parent_id IS NOT NULL, id LIMIT 1",
         )?;
-        let my_tag = stmt.query_one([tag.clone()], |row| {
-            Ok(ProjectTag {
-                id: row.get(0)?,
-                tag: row.get(1)?,
-            })
-        });
-        my_tag
+        stmt.query_one([tag.clone()], Self::tag_from_row)
+    }
+    /**
+     * Find a tag directly under a parent by name or alias ignoring case.
+     */
+    fn get_child_tag(&self, parent_id: i32, tag: &str) -> Option<ProjectTag> {
+        let mut stmt = self.connection.prepare(
+            "SELECT id, tag, color, parent_id FROM tags WHERE parent_id = ?1 AND lower(tag) = lower(?2) \
+            UNION ALL SELECT t.id, t.tag, t.color, t.parent_id FROM tag_aliases a JOIN tags t ON t.id = a.tag_id \
+            WHERE t.parent_id = ?1 AND a.alias = ?2 LIMIT 1",
+        ).unwrap();
+        stmt.query_one(params![parent_id, tag], Self::tag_from_row).optional().unwrap()
+    }
+    /**
+     * The tag at the end of a typed path, creating any levels that don't exist yet.
+     * The first level can be any existing tag, later ones have to be children of the level before.
+     */
+    pub fn get_or_add_tag_path(&self, path: &str) -> Option<ProjectTag> {
+        let mut parent: Option<ProjectTag> = None;
+        for name in ProjectTag::split_path(path) {
+            let existing = match &parent {
+                Some(parent) => self.get_child_tag(parent.id, &name),
+                None => self.get_tag_by_tag(name.clone()).ok(),
+            };
+            parent = match existing {
+                Some(tag) => Some(tag),
+                None => Some(self.add_tag(name, parent.map(|parent| parent.id)).ok()?),
+            };
+        }
+        parent
     }
     pub fn get_tag_by_id(&self, id: i32) -> Result<ProjectTag> {
         let mut stmt = self.connection.prepare(
-            "Select id, tag FROM tags WHERE id = ?1 LIMIT 1",
+            "Select id, tag, color, parent_id FROM tags WHERE id = ?1 LIMIT 1",
         )?;
-        let my_tag = stmt.query_one([id], |row| {
-            Ok(ProjectTag {
-                id: row.get(0)?,
-                tag: row.get(1)?,
-            })
-        });
-        my_tag
+        stmt.query_one([id], Self::tag_from_row)
     }
-    pub fn add_tag(&self, tag: String) -> Result<ProjectTag> {
+    pub fn add_tag(&self, tag: String, parent_id: Option<i32>) -> Result<ProjectTag> {
         let mut add_stmt = self.connection.prepare(
-            "INSERT INTO tags (tag) VALUES (?1)"
+            "INSERT INTO tags (tag, parent_id) VALUES (?1, ?2)"
         )?;
-        add_stmt.execute([tag.clone()])?;
+        add_stmt.execute(params![tag, parent_id])?;
         let last_id = i32::try_from(self.connection.last_insert_rowid()).unwrap();
         self.get_tag_by_id(last_id)
     }
 
     pub fn get_tag_list(&self) -> Vec<ProjectTag> {
         let mut stmt = self.connection.prepare(
-            "Select id, tag FROM tags ORDER BY tag",
+            "Select id, tag, color, parent_id FROM tags ORDER BY lower(tag)",
+        ).unwrap();
+        stmt.query_map([], Self::tag_from_row).unwrap().map(|r| r.unwrap()).collect()
+    }
+
+    fn tag_from_row(row: &Row) -> Result<ProjectTag> {
+        Ok(ProjectTag {
+            id: row.get(0)?,
+            tag: row.get(1)?,
+            color: row.get(2)?,
+            parent_id: row.get(3)?,
+        })
+    }
+
+    /**
+     * Every tag with the number of projects using it.
+     */
+    pub fn get_tag_usage(&self) -> Vec<(ProjectTag, usize)> {
+        let mut stmt = self.connection.prepare(
+            "SELECT t.id, t.tag, t.color, t.parent_id, count(pt.project_id) FROM tags t \
+            LEFT JOIN projects_tags pt ON pt.tag_id = t.id GROUP BY t.id ORDER BY lower(t.tag)",
         ).unwrap();
         stmt.query_map([], |row| {
-            Ok(ProjectTag{
-                id: row.get(0)?,
-                tag: row.get(1)?,
-            })
-        }).unwrap().into_iter().map(|r| r.unwrap()).collect()
+            Ok((Self::tag_from_row(row)?, row.get::<usize, i64>(4)? as usize))
+        }).unwrap().map(|r| r.unwrap()).collect()
+    }
+
+    pub fn rename_tag(&self, tag_id: i32, name: &str) {
+        let _ = self.connection.execute("UPDATE tags SET tag = ?2 WHERE id = ?1", params![tag_id, name]);
+    }
+
+    pub fn set_tag_color(&self, tag_id: i32, color: Option<String>) {
+        let _ = self.connection.execute("UPDATE tags SET color = ?2 WHERE id = ?1", params![tag_id, color]);
+    }
+
+    /**
+     * Move every use of one tag over to another and delete the first, its children move up a
+     * level like when it's deleted.  Refused when the tag to keep is below the merged one.
+     */
+    pub fn merge_tag(&self, from_tag_id: i32, into_tag_id: i32) -> bool {
+        if from_tag_id == into_tag_id || self.is_tag_below(into_tag_id, from_tag_id) {
+            return false;
+        }
+        let Ok(transaction) = self.connection.unchecked_transaction() else { return false };
+        //the old name becomes an alias so typing it again finds the tag it was merged into
+        for sql in [
+            "INSERT OR IGNORE INTO projects_tags (project_id, tag_id) SELECT project_id, ?2 FROM projects_tags WHERE tag_id = ?1",
+            "INSERT OR IGNORE INTO saved_searches_tags (saved_search_id, tag_id) SELECT saved_search_id, ?2 FROM saved_searches_tags WHERE tag_id = ?1",
+            "UPDATE tag_aliases SET tag_id = ?2 WHERE tag_id = ?1",
+            "INSERT OR IGNORE INTO tag_aliases (alias, tag_id) SELECT tag, ?2 FROM tags WHERE id = ?1",
+        ] {
+            if let Err(e) = transaction.execute(sql, params![from_tag_id, into_tag_id]) {
+                error!("Could not merge tag {} into {}: {}", from_tag_id, into_tag_id, e);
+                return false;
+            }
+        }
+        Self::delete_tag_rows(&transaction, from_tag_id) && transaction.commit().is_ok()
+    }
+
+    /**
+     * Delete a tag and take it off every project and saved search, its children move up a level.
+     */
+    pub fn delete_tag(&self, tag_id: i32) {
+        let Ok(transaction) = self.connection.unchecked_transaction() else { return };
+        if Self::delete_tag_rows(&transaction, tag_id) {
+            let _ = transaction.commit();
+        }
+    }
+
+    fn delete_tag_rows(connection: &Connection, tag_id: i32) -> bool {
+        for sql in [
+            "DELETE FROM projects_tags WHERE tag_id = ?1",
+            "DELETE FROM saved_searches_tags WHERE tag_id = ?1",
+            "DELETE FROM tag_aliases WHERE tag_id = ?1",
+            "UPDATE tags SET parent_id = (SELECT parent_id FROM tags WHERE id = ?1) WHERE parent_id = ?1",
+            "DELETE FROM tags WHERE id = ?1",
+        ] {
+            if let Err(e) = connection.execute(sql, [tag_id]) {
+                error!("Could not delete tag {}: {}", tag_id, e);
+                return false;
+            }
+        }
+        true
+    }
+
+    /**
+     * Whether the tag is the other tag or somewhere below it.
+     */
+    fn is_tag_below(&self, tag_id: i32, ancestor_id: i32) -> bool {
+        let mut stmt = self.connection.prepare(
+            "WITH RECURSIVE descendants(id) AS (SELECT ?1 UNION SELECT t.id FROM tags t JOIN descendants d ON t.parent_id = d.id) \
+            SELECT count(*) FROM descendants WHERE id = ?2",
+        ).unwrap();
+        let found: i64 = stmt.query_one(params![ancestor_id, tag_id], |row| row.get(0)).unwrap();
+        found > 0
+    }
+
+    /**
+     * Delete the tags no project uses any more, returns how many went.  Parents are kept while
+     * they still have children.
+     */
+    pub fn delete_unused_tags(&self) -> usize {
+        let unused = "SELECT id FROM tags WHERE id NOT IN (SELECT tag_id FROM projects_tags) \
+            AND id NOT IN (SELECT parent_id FROM tags WHERE parent_id IS NOT NULL)";
+        for table in ["saved_searches_tags", "tag_aliases"] {
+            let _ = self.connection.execute(format!("DELETE FROM {} WHERE tag_id IN ({})", table, unused).as_str(), []);
+        }
+        self.connection.execute(format!("DELETE FROM tags WHERE id IN ({})", unused).as_str(), []).unwrap_or(0)
+    }
+
+    /**
+     * Move a tag under another one, or to the top level.  Refused when the new parent is the
+     * tag itself or one of its children.
+     */
+    pub fn set_tag_parent(&self, tag_id: i32, parent_id: Option<i32>) -> bool {
+        if let Some(parent_id) = parent_id && self.is_tag_below(parent_id, tag_id) {
+            return false;
+        }
+        let _ = self.connection.execute("UPDATE tags SET parent_id = ?2 WHERE id = ?1", params![tag_id, parent_id]);
+        true
+    }
+
+    pub fn get_tag_aliases(&self, tag_id: i32) -> Vec<String> {
+        let mut stmt = self.connection.prepare("SELECT alias FROM tag_aliases WHERE tag_id = ?1 ORDER BY alias").unwrap();
+        stmt.query_map([tag_id], |row| row.get(0)).unwrap().map(|r| r.unwrap()).collect()
+    }
+
+    /**
+     * Make another name resolve to a tag.  Fails when the name is already a tag or an alias.
+     */
+    pub fn add_tag_alias(&self, tag_id: i32, alias: &str) -> std::result::Result<(), String> {
+        if let Ok(existing) = self.get_tag_by_tag(alias.to_string()) {
+            return Err(format!("{} is already used by the tag {}", alias, existing.tag));
+        }
+        self.connection.execute("INSERT INTO tag_aliases (alias, tag_id) VALUES (?1, ?2)", params![alias, tag_id])
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+
+    /**
+     * The tag an alias resolves to, ignoring case.
+     */
+    pub fn get_alias_tag(&self, alias: &str) -> Option<ProjectTag> {
+        let mut stmt = self.connection.prepare(
+            "SELECT t.id, t.tag, t.color, t.parent_id FROM tag_aliases a JOIN tags t ON t.id = a.tag_id WHERE a.alias = ?1",
+        ).unwrap();
+        stmt.query_one([alias], Self::tag_from_row).optional().unwrap()
+    }
+
+    pub fn remove_tag_alias(&self, alias: &str) {
+        let _ = self.connection.execute("DELETE FROM tag_aliases WHERE alias = ?1", [alias]);
     }
 
     pub fn update_project_file(&self, project_file:ProjectFile) -> ProjectFile {
@@ -314,19 +1248,68 @@ impl DbManager {
     }
     pub fn get_project_file_by_id(&self, id: i32) -> ProjectFile {
         let mut files_stmt = self.connection.prepare(
-            "SELECT id, path, notes, project_id, isdefault FROM project_files WHERE id = ?1 LIMIT 1",
-        ).unwrap();
-        let file :ProjectFile = files_stmt.query_one([id], |row| {
-            Ok(ProjectFile {
-                id: row.get(0).unwrap(),
-                path: row.get(1).unwrap(),
-                notes: row.get(2).unwrap(),
-                project_id: row.get(3).unwrap(),
-                default: row.get(4).unwrap(),
-            })
-        }).unwrap();
+            format!("{} WHERE pf.id = ?1 LIMIT 1", PROJECT_FILE_SELECT).as_str(),
+        ).unwrap();
+        let file :ProjectFile = files_stmt.query_one([id], Self::project_file_from_row).unwrap();
         file
     }
+    /**
+     * Model files that have not been measured yet.
+     */
+    pub fn get_files_missing_mesh_info(&self) -> Vec<ProjectFile> {
+        let mut files_stmt = self.connection.prepare(
+            format!("{} WHERE fm.project_file_id IS NULL AND pf.removed IS NULL ORDER BY pf.path", PROJECT_FILE_SELECT).as_str(),
+        ).unwrap();
+        files_stmt.query_map([], Self::project_file_from_row)
+            .unwrap().map(|r| r.unwrap()).filter(|file| file.is_model_type()).collect()
+    }
+    /**
+     * G-code files that have not had their slicer metadata read yet.
+     */
+    pub fn get_files_missing_gcode_info(&self) -> Vec<ProjectFile> {
+        let mut files_stmt = self.connection.prepare(
+            format!("{} WHERE fg.project_file_id IS NULL AND pf.removed IS NULL ORDER BY pf.path", PROJECT_FILE_SELECT).as_str(),
+        ).unwrap();
+        files_stmt.query_map([], Self::project_file_from_row)
+            .unwrap().map(|r| r.unwrap()).filter(|file| file.is_gcode_type()).collect()
+    }
+    pub fn save_gcode_info(&self, gcode_info: &GcodeInfo) {
+        let mut stmt = self.connection.prepare(
+            "INSERT OR REPLACE INTO file_gcodes (project_file_id, slicer, print_time, filament_length, filament_weight, \
+            filament_type, layer_height, nozzle_temperature, bed_temperature, has_thumbnail) \
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        ).unwrap();
+        let _ = stmt.execute(params![gcode_info.project_file_id, gcode_info.slicer, gcode_info.print_time,
+            gcode_info.filament_length, gcode_info.filament_weight, gcode_info.filament_type, gcode_info.layer_height,
+            gcode_info.nozzle_temperature, gcode_info.bed_temperature, gcode_info.has_thumbnail]);
+    }
+    pub fn save_mesh_info(&self, mesh_info: &MeshInfo) {
+        let mut stmt = self.connection.prepare(
+            "INSERT OR REPLACE INTO file_meshes (project_file_id, size_x, size_y, size_z, triangle_count, surface_area, volume, watertight) \
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        ).unwrap();
+        let _ = stmt.execute(params![mesh_info.project_file_id, mesh_info.size_x, mesh_info.size_y, mesh_info.size_z,
+            mesh_info.triangle_count, mesh_info.surface_area, mesh_info.volume, mesh_info.watertight]);
+    }
+    /**
+     * Size and modification time of a file the last time its metadata couldn't be read.
+     */
+    pub fn get_metadata_failure(&self, project_file_id: i32) -> Option<(i64, i64)> {
+        self.connection.query_row(
+            "SELECT size, mtime FROM file_metadata_failures WHERE project_file_id = ?1",
+            [project_file_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        ).optional().unwrap_or(None)
+    }
+    pub fn save_metadata_failure(&self, project_file_id: i32, size: i64, mtime: i64) {
+        let _ = self.connection.execute(
+            "INSERT OR REPLACE INTO file_metadata_failures (project_file_id, size, mtime) VALUES (?1, ?2, ?3)",
+            params![project_file_id, size, mtime],
+        );
+    }
+    pub fn clear_metadata_failure(&self, project_file_id: i32) {
+        let _ = self.connection.execute("DELETE FROM file_metadata_failures WHERE project_file_id = ?1", [project_file_id]);
+    }
     pub fn update_project(&self, project: Project) -> Project {
         let mut stmt = self.connection.prepare(
             "UPDATE projects SET name = ?1, notes = ?2, path = ?3 WHERE id = ?4",
@@ -341,4 +1324,400 @@ impl DbManager {
         let _ = stmt.execute([name, url, project.id.to_string()]);
         self.get_project(project.id)
     }
-}
\ No newline at end of file
+
+    /**
+     * Saved searches, pinned ones first.
+     */
+    pub fn get_saved_searches(&self) -> Vec<SavedSearch> {
+        let mut stmt = self.connection.prepare(
+            "SELECT id, name, query, sort, pinned FROM saved_searches ORDER BY pinned DESC, name",
+        ).unwrap();
+        let saved_searches: Vec<SavedSearch> = stmt.query_map([], |row| {
+            Ok(SavedSearch {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                query: row.get(2)?,
+                tags: vec![],
+                sort: Sort::parse(&row.get::<usize, String>(3)?).unwrap_or(Sort::new(SortKey::Relevance)),
+                pinned: row.get(4)?,
+            })
+        }).unwrap().map(|r| r.unwrap()).collect();
+        saved_searches.into_iter().map(|mut saved_search| {
+            saved_search.tags = self.saved_search_get_tags(saved_search.id);
+            saved_search
+        }).collect()
+    }
+
+    fn saved_search_get_tags(&self, saved_search_id: i32) -> Vec<ProjectTag> {
+        let mut stmt = self.connection.prepare(
+            "SELECT t.id, t.tag, t.color, t.parent_id FROM saved_searches_tags sst JOIN tags t ON sst.tag_id = t.id WHERE sst.saved_search_id = ?1 ORDER BY t.tag",
+        ).unwrap();
+        let tags: Vec<ProjectTag> = stmt.query_map([saved_search_id], Self::tag_from_row).unwrap().map(|r| r.unwrap()).collect();
+        tags
+    }
+
+    pub fn create_saved_search(&self, saved_search: &SavedSearch) -> Result<()> {
+        let transaction = self.connection.unchecked_transaction()?;
+        transaction.execute(
+            "INSERT INTO saved_searches (name, query, sort, pinned) VALUES (?1, ?2, ?3, ?4)",
+            params![saved_search.name, saved_search.query, saved_search.sort.to_string(), saved_search.pinned],
+        )?;
+        let saved_search_id = transaction.last_insert_rowid();
+        for tag in saved_search.tags.iter() {
+            transaction.execute(
+                "INSERT INTO saved_searches_tags (saved_search_id, tag_id) VALUES (?1, ?2)", params![saved_search_id, tag.id],
+            )?;
+        }
+        transaction.commit()
+    }
+
+    pub fn rename_saved_search(&self, saved_search_id: i32, name: &str) {
+        let _ = self.connection.execute("UPDATE saved_searches SET name = ?2 WHERE id = ?1", params![saved_search_id, name]);
+    }
+
+    pub fn set_saved_search_pinned(&self, saved_search_id: i32, pinned: bool) {
+        let _ = self.connection.execute("UPDATE saved_searches SET pinned = ?2 WHERE id = ?1", params![saved_search_id, pinned]);
+    }
+
+    pub fn delete_saved_search(&self, saved_search_id: i32) {
+        let _ = self.connection.execute("DELETE FROM saved_searches WHERE id = ?1", [saved_search_id]);
+    }
+
+    pub fn get_collections(&self) -> Vec<Collection> {
+        let mut stmt = self.connection.prepare(
+            "SELECT c.id, c.name, count(cp.project_id) FROM collections c \
+            LEFT JOIN collections_projects cp ON cp.collection_id = c.id GROUP BY c.id ORDER BY c.name",
+        ).unwrap();
+        let collections: Vec<Collection> = stmt.query_map([], Self::collection_from_row).unwrap().map(|r| r.unwrap()).collect();
+        collections
+    }
+
+    /**
+     * The collections a project has been added to.
+     */
+    pub fn project_get_collections(&self, project_id: i32) -> Vec<Collection> {
+        let mut stmt = self.connection.prepare(
+            "SELECT c.id, c.name, (SELECT count(*) FROM collections_projects WHERE collection_id = c.id) FROM collections c \
+            JOIN collections_projects cp ON cp.collection_id = c.id WHERE cp.project_id = ?1 ORDER BY c.name",
+        ).unwrap();
+        let collections: Vec<Collection> = stmt.query_map([project_id], Self::collection_from_row).unwrap().map(|r| r.unwrap()).collect();
+        collections
+    }
+
+    fn collection_from_row(row: &Row) -> Result<Collection> {
+        Ok(Collection {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            project_count: row.get::<usize, i64>(2)? as usize,
+        })
+    }
+
+    pub fn create_collection(&self, name: &str) -> i32 {
+        let _ = self.connection.execute("INSERT INTO collections (name) VALUES (?1)", [name]);
+        i32::try_from(self.connection.last_insert_rowid()).unwrap()
+    }
+
+    pub fn rename_collection(&self, collection_id: i32, name: &str) {
+        let _ = self.connection.execute("UPDATE collections SET name = ?2 WHERE id = ?1", params![collection_id, name]);
+    }
+
+    /**
+     * Delete a collection, the projects in it are left alone.
+     */
+    pub fn delete_collection(&self, collection_id: i32) -> Result<()> {
+        let transaction = self.connection.unchecked_transaction()?;
+        for sql in [
+            "DELETE FROM collections_projects WHERE collection_id = ?1",
+            "DELETE FROM collections WHERE id = ?1",
+        ] {
+            transaction.execute(sql, [collection_id])?;
+        }
+        transaction.commit()
+    }
+
+    /**
+     * Add a project to the end of a collection, nothing happens when it's already in there.
+     */
+    pub fn collection_add_project(&self, collection_id: i32, project_id: i32) {
+        let _ = self.connection.execute(
+            "INSERT OR IGNORE INTO collections_projects (collection_id, project_id, position) \
+            VALUES (?1, ?2, (SELECT coalesce(max(position), -1) + 1 FROM collections_projects WHERE collection_id = ?1))",
+            params![collection_id, project_id],
+        );
+    }
+
+    pub fn collection_remove_project(&self, collection_id: i32, project_id: i32) {
+        let _ = self.connection.execute(
+            "DELETE FROM collections_projects WHERE collection_id = ?1 AND project_id = ?2", params![collection_id, project_id],
+        );
+    }
+
+    /**
+     * Move a project to a new index in the collection, the positions are renumbered from zero.
+     */
+    pub fn collection_move_project(&self, collection_id: i32, project_id: i32, index: usize) {
+        let mut stmt = self.connection.prepare(
+            "SELECT project_id FROM collections_projects WHERE collection_id = ?1 ORDER BY position",
+        ).unwrap();
+        let mut project_ids: Vec<i32> = stmt.query_map([collection_id], |row| row.get(0)).unwrap().map(|r| r.unwrap()).collect();
+        let Some(current) = project_ids.iter().position(|id| *id == project_id) else { return };
+        project_ids.remove(current);
+        project_ids.insert(index.min(project_ids.len()), project_id);
+        let mut update_stmt = self.connection.prepare(
+            "UPDATE collections_projects SET position = ?3 WHERE collection_id = ?1 AND project_id = ?2",
+        ).unwrap();
+        for (position, id) in project_ids.iter().enumerate() {
+            let _ = update_stmt.execute(params![collection_id, id, position as i64]);
+        }
+    }
+
+    pub fn get_thumbnail_cache_entry(&self, source_path: &str) -> Option<ThumbnailCacheEntry> {
+        let mut stmt = self.connection.prepare(
+            "SELECT source_path, source_size, source_mtime, hash, image_size, last_used FROM thumbnail_cache WHERE source_path = ?1",
+        ).unwrap();
+        stmt.query_one([source_path], |row| {
+            Ok(ThumbnailCacheEntry {
+                source_path: row.get(0)?,
+                source_size: row.get(1)?,
+                source_mtime: row.get(2)?,
+                hash: row.get(3)?,
+                image_size: row.get(4)?,
+                last_used: row.get(5)?,
+            })
+        }).optional().unwrap_or(None)
+    }
+    pub fn save_thumbnail_cache_entry(&self, entry: ThumbnailCacheEntry) {
+        let mut stmt = self.connection.prepare(
+            "INSERT OR REPLACE INTO thumbnail_cache (source_path, source_size, source_mtime, hash, image_size, last_used) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        ).unwrap();
+        let _ = stmt.execute(params![entry.source_path, entry.source_size, entry.source_mtime, entry.hash, entry.image_size, entry.last_used]);
+    }
+    pub fn touch_thumbnail_cache_entry(&self, source_path: &str, last_used: i64) {
+        let mut stmt = self.connection.prepare(
+            "UPDATE thumbnail_cache SET last_used = ?1 WHERE source_path = ?2",
+        ).unwrap();
+        let _ = stmt.execute(params![last_used, source_path]);
+    }
+    /**
+     * One row per cached image as (hash, image size), least recently used first.
+     */
+    pub fn get_thumbnail_cache_usage(&self) -> Vec<(String, i64)> {
+        let mut stmt = self.connection.prepare(
+            "SELECT hash, MAX(image_size) FROM thumbnail_cache GROUP BY hash ORDER BY MAX(last_used)",
+        ).unwrap();
+        stmt.query_map([], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        }).unwrap().map(|r| r.unwrap()).collect()
+    }
+    pub fn delete_thumbnail_cache_hash(&self, hash: &str) {
+        let mut stmt = self.connection.prepare(
+            "DELETE FROM thumbnail_cache WHERE hash = ?1",
+        ).unwrap();
+        let _ = stmt.execute([hash]);
+    }
+    pub fn clear_thumbnail_cache(&self) {
+        let _ = self.connection.execute("DELETE FROM thumbnail_cache", params![]);
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_db(full_text_search: bool) -> DbManager {
+        let mut db = DbManager::new(":memory:".to_string());
+        db.run_migration();
+        db.full_text_search = OnceCell::from(full_text_search);
+        for (name, notes) in [("Dragon", ""), ("Boat", "scaled to 50% for the dragon"), ("Bench_y", "")] {
+            let project = Project { name: name.to_string(), path: format!("/prints/{}", name), notes: notes.to_string(), ..Default::default() };
+            let project = db.create_project(project).unwrap();
+            db.connection.execute(
+                "INSERT INTO project_files (project_id, path) VALUES (?1, ?2)",
+                params![project.id, format!("/prints/{}/model.stl", name)],
+            ).unwrap();
+        }
+        db
+    }
+
+    /**
+     * An empty directory of the test's own under the temp directory, for tests that need files on disk.
+     */
+    fn test_dir(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("3dmanager-test-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn write_file(path: &Path, contents: &str) -> String {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+        path.to_str().unwrap().to_string()
+    }
+
+    fn test_project(db: &DbManager, dir: &Path) -> Project {
+        let project = Project { name: dir.file_name().unwrap().to_str().unwrap().to_string(), path: dir.to_str().unwrap().to_string(), ..Default::default() };
+        db.create_project(project).unwrap()
+    }
+
+    fn print_job(project_file_id: i32) -> PrintJob {
+        PrintJob {
+            id: 0,
+            project_file_id,
+            printed: "2026-01-02".to_string(),
+            printer: None,
+            material: None,
+            color: None,
+            duration: None,
+            outcome: PrintOutcome::Success,
+            failure_reason: None,
+            notes: None,
+            photos: vec![],
+            spool_id: None,
+            filament_used: None,
+        }
+    }
+
+    fn names(db: &DbManager, text: &str) -> Vec<String> {
+        let filter = ProjectFilter { query: Some(SearchQuery::parse(text).unwrap()), ..Default::default() };
+        let mut names: Vec<String> = db.get_filtered_projects(&filter).into_iter().map(|project| project.name).collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn every_migration_runs() {
+        let db = test_db(true);
+        let migrations = Migrations::iter().filter(|file| file.ends_with("up.sql")).count();
+        let recorded: i64 = db.connection.query_one("SELECT count(*) FROM _migrations", [], |row| row.get(0)).unwrap();
+        assert_eq!(recorded as usize, migrations);
+        assert!(db.has_full_text_search());
+    }
+
+    #[test]
+    fn search_without_full_text_matches_the_same_projects() {
+        for full_text_search in [true, false] {
+            let db = test_db(full_text_search);
+            assert_eq!(names(&db, "dragon"), vec!["Boat", "Dragon"], "full text {}", full_text_search);
+            assert_eq!(names(&db, "-dragon"), vec!["Bench_y"], "full text {}", full_text_search);
+            assert_eq!(names(&db, "bench"), vec!["Bench_y"], "full text {}", full_text_search);
+            assert_eq!(names(&db, "model").len(), 3, "full text {}", full_text_search);
+        }
+    }
+
+    #[test]
+    fn search_without_full_text_escapes_wildcards() {
+        let db = test_db(false);
+        assert_eq!(names(&db, "50%"), vec!["Boat"]);
+        assert_eq!(names(&db, "h_y"), vec!["Bench_y"]);
+        assert!(names(&db, "0_").is_empty());
+        assert!(db.get_search_snippets(&SearchQuery::parse("dragon").unwrap()).is_empty());
+    }
+
+    #[test]
+    fn renamed_file_keeps_its_notes_default_and_prints() {
+        let db = test_db(true);
+        let dir = test_dir("rename");
+        let project = test_project(&db, &dir);
+        let old_path = write_file(&dir.join("part.stl"), "solid part");
+        assert_eq!(db.update_project_files(project.clone(), vec![old_path.clone()], file_hash::now_secs()), (1, 0));
+        let mut file = db.project_get_files(project.id).remove(0);
+        file.notes = Some("print with brim".to_string());
+        file.default = true;
+        db.update_project_file(file.clone());
+        db.add_print_job(&print_job(file.id)).unwrap();
+
+        let new_path = dir.join("renamed").join("part v2.stl");
+        fs::create_dir_all(new_path.parent().unwrap()).unwrap();
+        fs::rename(&old_path, &new_path).unwrap();
+        let new_path = new_path.to_str().unwrap().to_string();
+        assert_eq!(db.update_project_files(project.clone(), vec![new_path.clone()], file_hash::now_secs()), (0, 0));
+
+        let files = db.project_get_files(project.id);
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].id, file.id);
+        assert_eq!(files[0].path, new_path);
+        assert_eq!(files[0].notes.as_deref(), Some("print with brim"));
+        assert!(files[0].default);
+        assert_eq!(db.get_project_print_jobs(project.id).len(), 1);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn file_moved_to_another_project_keeps_its_row() {
+        let db = test_db(true);
+        let dir = test_dir("move");
+        let (from_dir, to_dir) = (dir.join("from"), dir.join("to"));
+        let from = test_project(&db, &from_dir);
+        let to = test_project(&db, &to_dir);
+        let first = write_file(&from_dir.join("first.stl"), "solid first");
+        let second = write_file(&from_dir.join("second.stl"), "solid second");
+        db.update_project_files(from.clone(), vec![first.clone(), second.clone()], file_hash::now_secs());
+        let old_ids: Vec<i32> = db.project_get_files(from.id).iter().map(|file| file.id).collect();
+
+        let batch_started = file_hash::now_secs();
+        fs::create_dir_all(&to_dir).unwrap();
+        fs::rename(&first, to_dir.join("first.stl")).unwrap();
+        fs::rename(&second, to_dir.join("second.stl")).unwrap();
+        let moved = vec![to_dir.join("first.stl").to_str().unwrap().to_string(), to_dir.join("second.stl").to_str().unwrap().to_string()];
+        //the old project notices first for one file and the new project for the other
+        db.update_project_files(from.clone(), vec![second.clone()], batch_started);
+        db.update_project_files(to.clone(), moved.clone(), batch_started);
+        db.update_project_files(from.clone(), vec![], batch_started);
+
+        assert!(db.project_get_files(from.id).is_empty());
+        let files = db.project_get_files(to.id);
+        assert_eq!(files.iter().map(|file| file.path.clone()).collect::<Vec<_>>(), moved);
+        assert_eq!(files.iter().map(|file| file.id).collect::<Vec<_>>(), old_ids);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn new_copy_of_an_old_removed_file_gets_its_own_row() {
+        let db = test_db(true);
+        let dir = test_dir("old-removed");
+        let (old_dir, new_dir) = (dir.join("old"), dir.join("new"));
+        let old_project = test_project(&db, &old_dir);
+        let new_project = test_project(&db, &new_dir);
+        let old_path = write_file(&old_dir.join("model.stl"), "solid model");
+        db.update_project_files(old_project.clone(), vec![old_path.clone()], file_hash::now_secs());
+        let old_file = db.project_get_files(old_project.id).remove(0);
+        db.add_print_job(&print_job(old_file.id)).unwrap();
+        fs::remove_file(&old_path).unwrap();
+        db.update_project_files(old_project.clone(), vec![], file_hash::now_secs());
+        db.connection.execute("UPDATE project_files SET removed = removed - 86400 WHERE id = ?1", [old_file.id]).unwrap();
+
+        let new_path = write_file(&new_dir.join("model.stl"), "solid model");
+        assert_eq!(db.update_project_files(new_project.clone(), vec![new_path], file_hash::now_secs()), (1, 0));
+        let files = db.project_get_files(new_project.id);
+        assert_eq!(files.len(), 1);
+        assert_ne!(files[0].id, old_file.id);
+        assert!(db.get_project_print_jobs(new_project.id).is_empty());
+        assert_eq!(db.get_project_print_jobs(old_project.id).len(), 1);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn queue_items_move_and_delete() {
+        let db = test_db(true);
+        let file_ids: Vec<i32> = db.connection.prepare("SELECT id FROM project_files ORDER BY id").unwrap()
+            .query_map([], |row| row.get(0)).unwrap().map(|r| r.unwrap()).collect();
+        for file_id in &file_ids {
+            db.add_to_queue(*file_id, 2, QueuePriority::Normal);
+        }
+        let queue_files = |db: &DbManager| db.get_print_queue().iter().map(|item| item.file.id).collect::<Vec<_>>();
+        let moved = db.get_print_queue()[2].id;
+        db.move_queue_item(moved, 0).unwrap();
+        assert_eq!(queue_files(&db), vec![file_ids[2], file_ids[0], file_ids[1]]);
+        db.move_queue_item(moved, 10).unwrap();
+        assert_eq!(queue_files(&db), file_ids);
+
+        //prints counted against a removed item are kept
+        let print_job_id = db.add_print_job(&print_job(file_ids[0])).unwrap();
+        let item = db.get_print_queue().remove(0);
+        assert_eq!(item.printed, 1);
+        db.delete_queue_item(item.id).unwrap();
+        assert_eq!(queue_files(&db), vec![file_ids[1], file_ids[2]]);
+        let queue_item_id: Option<i32> = db.connection.query_one("SELECT queue_item_id FROM print_jobs WHERE id = ?1", [print_job_id], |row| row.get(0)).unwrap();
+        assert_eq!(queue_item_id, None);
+    }
+}
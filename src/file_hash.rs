@@ -0,0 +1,78 @@
+/*
+ * Copyright (c) 2025-2026 Mitch Tuck
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+use std::fs::{self, File, Metadata};
+use std::io::{self, Read};
+use std::time::{SystemTime, UNIX_EPOCH};
+use sha2::{Digest, Sha256};
+
+/// Size, modification time and content hash of a file, enough to recognise it after a rename or move.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileIdentity {
+    pub size: i64,
+    pub mtime: i64,
+    pub hash: String,
+}
+
+impl FileIdentity {
+    pub fn read(path: &str) -> io::Result<FileIdentity> {
+        let metadata = fs::metadata(path)?;
+        Ok(FileIdentity {
+            size: metadata.len() as i64,
+            mtime: modified_secs(&metadata),
+            hash: content_hash(path)?,
+        })
+    }
+
+    /**
+     * Whether the file still has the size and modification time it had when it was hashed.
+     */
+    pub fn is_current(size: Option<i64>, mtime: Option<i64>, metadata: &Metadata) -> bool {
+        size == Some(metadata.len() as i64) && mtime == Some(modified_secs(metadata))
+    }
+}
+
+/**
+ * Hex encoded SHA-256 of the file contents, read in chunks so large models don't sit in memory.
+ */
+pub fn content_hash(path: &str) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(hasher.finalize().iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+/**
+ * Modification time in whole seconds since the epoch, 0 if the platform can't tell us.
+ */
+pub fn modified_secs(metadata: &Metadata) -> i64 {
+    metadata.modified().map(system_time_secs).unwrap_or(0)
+}
+
+pub fn now_secs() -> i64 {
+    system_time_secs(SystemTime::now())
+}
+
+fn system_time_secs(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH).map(|duration| duration.as_secs() as i64).unwrap_or(0)
+}
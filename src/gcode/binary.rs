@@ -0,0 +1,225 @@
+/*
+ * Copyright (c) 2025-2026 Mitch Tuck
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! PrusaSlicer's binary G-code.  The file is a list of blocks, the metadata and thumbnail
+//! blocks all come before the G-code blocks so reading stops at the first G-code block.
+
+use std::io::{self, Read, Seek, SeekFrom};
+use flate2::read::ZlibDecoder;
+#[allow(unused)]
+use log::{error, warn, info, debug, trace};
+use super::{Gcode, GcodeThumbnail, ThumbnailFormat};
+
+pub const MAGIC: [u8; 4] = *b"GCDE";
+
+const BLOCK_FILE_METADATA: u16 = 0;
+const BLOCK_GCODE: u16 = 1;
+const BLOCK_SLICER_METADATA: u16 = 2;
+const BLOCK_PRINTER_METADATA: u16 = 3;
+const BLOCK_PRINT_METADATA: u16 = 4;
+const BLOCK_THUMBNAIL: u16 = 5;
+
+const COMPRESSION_NONE: u16 = 0;
+const COMPRESSION_DEFLATE: u16 = 1;
+
+const CHECKSUM_CRC32: u16 = 1;
+
+pub fn parse<R: Read + Seek>(reader: &mut R) -> io::Result<Gcode> {
+    let mut header = [0u8; 10];
+    reader.read_exact(&mut header)?;
+    if header[0..4] != MAGIC {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "not a binary G-code file"));
+    }
+    let checksum_size = if read_u16(&header, 8) == CHECKSUM_CRC32 { 4 } else { 0 };
+
+    let mut gcode = Gcode::default();
+    loop {
+        let mut block_header = [0u8; 8];
+        if reader.read_exact(&mut block_header).is_err() {
+            break;
+        }
+        let block_type = read_u16(&block_header, 0);
+        let compression = read_u16(&block_header, 2);
+        let uncompressed_size = read_u32(&block_header, 4) as u64;
+        let stored_size = if compression == COMPRESSION_NONE {
+            uncompressed_size
+        } else {
+            let mut size = [0u8; 4];
+            reader.read_exact(&mut size)?;
+            read_u32(&size, 0) as u64
+        };
+        if block_type == BLOCK_GCODE {
+            break;
+        }
+        let mut parameters = vec![0u8; if block_type == BLOCK_THUMBNAIL { 6 } else { 2 }];
+        reader.read_exact(&mut parameters)?;
+        let mut stored = vec![];
+        reader.take(stored_size).read_to_end(&mut stored)?;
+        if stored.len() as u64 != stored_size {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "binary G-code block is cut short"));
+        }
+        reader.seek(SeekFrom::Current(checksum_size))?;
+
+        let data = match compression {
+            COMPRESSION_NONE => stored,
+            COMPRESSION_DEFLATE => {
+                //the header's size isn't trusted for the allocation, one byte over is enough to tell it was wrong
+                let mut data = vec![];
+                ZlibDecoder::new(stored.as_slice()).take(uncompressed_size + 1).read_to_end(&mut data)?;
+                if data.len() as u64 != uncompressed_size {
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, "binary G-code block doesn't match its size"));
+                }
+                data
+            }
+            _ => {
+                debug!("Skipping binary G-code block with unsupported compression {}", compression);
+                continue;
+            }
+        };
+        match block_type {
+            BLOCK_FILE_METADATA | BLOCK_SLICER_METADATA | BLOCK_PRINTER_METADATA | BLOCK_PRINT_METADATA => {
+                for line in String::from_utf8_lossy(&data).lines() {
+                    if let Some((key, value)) = line.split_once('=') {
+                        gcode.apply_setting(key, value);
+                    }
+                }
+            }
+            BLOCK_THUMBNAIL => {
+                let format = match read_u16(&parameters, 0) {
+                    0 => ThumbnailFormat::Png,
+                    1 => ThumbnailFormat::Jpg,
+                    _ => ThumbnailFormat::Qoi,
+                };
+                gcode.thumbnails.push(GcodeThumbnail {
+                    width: read_u16(&parameters, 2) as u32,
+                    height: read_u16(&parameters, 4) as u32,
+                    format,
+                    data,
+                });
+            }
+            _ => {}
+        }
+    }
+    Ok(gcode)
+}
+
+fn read_u16(data: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([data[offset], data[offset + 1]])
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([data[offset], data[offset + 1], data[offset + 2], data[offset + 3]])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Cursor, Write};
+    use flate2::Compression;
+    use flate2::write::ZlibEncoder;
+
+    fn file_header() -> Vec<u8> {
+        let mut data = MAGIC.to_vec();
+        data.extend(1u32.to_le_bytes());
+        data.extend(CHECKSUM_CRC32.to_le_bytes());
+        data
+    }
+
+    fn block(block_type: u16, compression: u16, uncompressed_size: u32, parameters: &[u8], stored: &[u8]) -> Vec<u8> {
+        let mut data = vec![];
+        data.extend(block_type.to_le_bytes());
+        data.extend(compression.to_le_bytes());
+        data.extend(uncompressed_size.to_le_bytes());
+        if compression != COMPRESSION_NONE {
+            data.extend((stored.len() as u32).to_le_bytes());
+        }
+        data.extend(parameters);
+        data.extend(stored);
+        data.extend([0u8; 4]);
+        data
+    }
+
+    fn deflate(data: &[u8]) -> Vec<u8> {
+        let mut encoder = ZlibEncoder::new(vec![], Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn parse_bytes(data: Vec<u8>) -> io::Result<Gcode> {
+        parse(&mut Cursor::new(data))
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        assert_eq!(parse_bytes(b"; G-code file".to_vec()).unwrap_err().kind(), io::ErrorKind::InvalidData);
+        assert!(parse_bytes(b"GCD".to_vec()).is_err());
+    }
+
+    #[test]
+    fn reads_metadata_and_thumbnails() {
+        let metadata = b"Producer=PrusaSlicer 2.7.0\nlayer_height=0.2\n";
+        let printer = b"filament_type=PETG;PLA\nbed_temperature=85\n";
+        let thumbnail = [1u8, 2, 3, 4];
+        let mut data = file_header();
+        data.extend(block(BLOCK_FILE_METADATA, COMPRESSION_NONE, metadata.len() as u32, &[0, 0], metadata));
+        data.extend(block(BLOCK_PRINTER_METADATA, COMPRESSION_DEFLATE, printer.len() as u32, &[0, 0], &deflate(printer)));
+        data.extend(block(BLOCK_THUMBNAIL, COMPRESSION_NONE, 4, &[1, 0, 16, 0, 12, 0], &thumbnail));
+        data.extend(block(BLOCK_GCODE, COMPRESSION_NONE, 3, &[0, 0], b"G28"));
+        let gcode = parse_bytes(data).unwrap();
+        assert_eq!(gcode.slicer.as_deref(), Some("PrusaSlicer 2.7.0"));
+        assert_eq!(gcode.layer_height, Some(0.2));
+        assert_eq!(gcode.filament_type.as_deref(), Some("PETG"));
+        assert_eq!(gcode.bed_temperature, Some(85.0));
+        assert_eq!(gcode.thumbnails.len(), 1);
+        assert_eq!(gcode.thumbnails[0].format, ThumbnailFormat::Jpg);
+        assert_eq!((gcode.thumbnails[0].width, gcode.thumbnails[0].height), (16, 12));
+        assert_eq!(gcode.thumbnails[0].data, thumbnail);
+    }
+
+    #[test]
+    fn stops_at_the_first_gcode_block() {
+        let metadata = b"layer_height=0.2\n";
+        let mut data = file_header();
+        data.extend(block(BLOCK_GCODE, COMPRESSION_NONE, 3, &[0, 0], b"G28"));
+        data.extend(block(BLOCK_PRINT_METADATA, COMPRESSION_NONE, metadata.len() as u32, &[0, 0], metadata));
+        assert_eq!(parse_bytes(data).unwrap().layer_height, None);
+    }
+
+    #[test]
+    fn rejects_a_block_bigger_than_its_header_says() {
+        let metadata = b"layer_height=0.2\n";
+        let mut data = file_header();
+        data.extend(block(BLOCK_PRINT_METADATA, COMPRESSION_DEFLATE, 4, &[0, 0], &deflate(metadata)));
+        assert_eq!(parse_bytes(data).unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn rejects_a_block_smaller_than_its_header_says() {
+        let metadata = b"layer_height=0.2\n";
+        let mut data = file_header();
+        data.extend(block(BLOCK_PRINT_METADATA, COMPRESSION_DEFLATE, u32::MAX, &[0, 0], &deflate(metadata)));
+        assert_eq!(parse_bytes(data).unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn rejects_a_cut_short_block() {
+        let mut data = file_header();
+        data.extend(block(BLOCK_PRINT_METADATA, COMPRESSION_NONE, 100, &[0, 0], b"layer_height=0.2"));
+        data.truncate(data.len() - 4);
+        assert_eq!(parse_bytes(data).unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+    }
+}
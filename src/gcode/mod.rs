@@ -0,0 +1,431 @@
+/*
+ * Copyright (c) 2025-2026 Mitch Tuck
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Reads the slicer metadata out of sliced files.  PrusaSlicer, SuperSlicer, OrcaSlicer,
+//! Bambu Studio and Cura all leave comments describing the print, either in a header at
+//! the top or a config block at the end, so only those two parts of the file are read.
+
+mod binary;
+
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use regex::Regex;
+
+/// Bytes read from the top of a text G-code file, big enough for the header and thumbnails.
+const HEAD_BYTES: u64 = 4 * 1024 * 1024;
+/// Bytes read from the end of a text G-code file, where PrusaSlicer and Orca put their config.
+const TAIL_BYTES: u64 = 512 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ThumbnailFormat {
+    Png,
+    Jpg,
+    Qoi,
+}
+
+/// A preview image embedded by the slicer, still in its encoded form.
+#[derive(Debug, Clone)]
+pub struct GcodeThumbnail {
+    pub width: u32,
+    pub height: u32,
+    pub format: ThumbnailFormat,
+    pub data: Vec<u8>,
+}
+
+impl GcodeThumbnail {
+    pub fn to_rgba(&self) -> Option<image::RgbaImage> {
+        let format = match self.format {
+            ThumbnailFormat::Png => image::ImageFormat::Png,
+            ThumbnailFormat::Jpg => image::ImageFormat::Jpeg,
+            ThumbnailFormat::Qoi => image::ImageFormat::Qoi,
+        };
+        image::load_from_memory_with_format(&self.data, format).ok().map(|image| image.to_rgba8())
+    }
+}
+
+/// What the slicer recorded about a print.  Lengths are millimeters, weights grams,
+/// temperatures celsius and the print time is in seconds.
+#[derive(Debug, Clone, Default)]
+pub struct Gcode {
+    pub slicer: Option<String>,
+    pub print_time: Option<i64>,
+    pub filament_length: Option<f64>,
+    pub filament_weight: Option<f64>,
+    pub filament_type: Option<String>,
+    pub layer_height: Option<f64>,
+    pub nozzle_temperature: Option<f64>,
+    pub bed_temperature: Option<f64>,
+    pub thumbnails: Vec<GcodeThumbnail>,
+    /// Temperatures from the first heating commands, used when the comments don't say.
+    commanded_nozzle_temperature: Option<f64>,
+    commanded_bed_temperature: Option<f64>,
+}
+
+impl Gcode {
+    /**
+     * Load the metadata from a .gcode or .bgcode file, binary files are detected by their magic bytes.
+     */
+    pub fn load(path: &str) -> io::Result<Gcode> {
+        let mut file = File::open(path)?;
+        let mut magic = [0u8; 4];
+        let is_binary = file.read_exact(&mut magic).is_ok() && magic == binary::MAGIC;
+        file.seek(SeekFrom::Start(0))?;
+        let mut gcode = if is_binary {
+            binary::parse(&mut file)?
+        } else {
+            parse_text_file(&mut file)?
+        };
+        gcode.nozzle_temperature = gcode.nozzle_temperature.or(gcode.commanded_nozzle_temperature);
+        gcode.bed_temperature = gcode.bed_temperature.or(gcode.commanded_bed_temperature);
+        Ok(gcode)
+    }
+
+    pub fn is_gcode_file(path: &str) -> bool {
+        let extension = Path::new(path).extension().and_then(|ext| ext.to_str()).unwrap_or("").to_lowercase();
+        matches!(extension.as_str(), "gcode" | "gco" | "bgcode")
+    }
+
+    /**
+     * The biggest embedded preview, slicers usually include a tiny one for printer screens as well.
+     */
+    pub fn largest_thumbnail(&self) -> Option<&GcodeThumbnail> {
+        self.thumbnails.iter().max_by_key(|thumbnail| thumbnail.width as u64 * thumbnail.height as u64)
+    }
+
+    /**
+     * Record one metadata setting.  Keys are matched case insensitively so the comment
+     * header, the config block and bgcode metadata blocks can all share this.
+     */
+    fn apply_setting(&mut self, key: &str, value: &str) {
+        let key = key.trim().to_lowercase();
+        let value = value.trim();
+        if value.is_empty() {
+            return;
+        }
+        match key.as_str() {
+            "producer" => set_once(&mut self.slicer, Some(value.to_string())),
+            "estimated printing time (normal mode)" | "total estimated time" | "time" => {
+                set_once(&mut self.print_time, parse_duration(value))
+            }
+            "filament used [mm]" | "total filament length [mm]" => set_once(&mut self.filament_length, sum_list(value)),
+            // Cura reports meters, e.g. "1.2345m"
+            "filament used" => set_once(&mut self.filament_length, sum_list(&value.replace('m', "")).map(|meters| meters * 1000.0)),
+            "total filament used [g]" | "filament used [g]" | "total filament weight [g]" => {
+                set_once(&mut self.filament_weight, sum_list(value))
+            }
+            "filament_type" => set_once(&mut self.filament_type, first_in_list(value).map(|kind| kind.to_string())),
+            "layer_height" | "layer height" => set_once(&mut self.layer_height, first_number(value)),
+            "temperature" | "nozzle_temperature" | "extruder_train.0.initial_temperature" => {
+                set_once(&mut self.nozzle_temperature, first_number(value))
+            }
+            "bed_temperature" | "hot_plate_temp" | "build_plate.initial_temperature" => {
+                set_once(&mut self.bed_temperature, first_number(value))
+            }
+            _ => {}
+        }
+    }
+
+    /**
+     * Handle the text of a comment line, without the leading ';'.
+     */
+    fn apply_comment(&mut self, comment: &str) {
+        let comment = comment.trim();
+        let lower = comment.to_lowercase();
+        for prefix in ["generated by ", "generated with "] {
+            if lower.starts_with(prefix) {
+                let producer = &comment[prefix.len()..];
+                let producer = producer.split(" on ").next().unwrap_or(producer);
+                self.apply_setting("producer", &producer.replace("Cura_SteamEngine", "Cura"));
+                return;
+            }
+        }
+        if lower.starts_with("bambustudio") {
+            self.apply_setting("producer", &comment.replace("BambuStudio", "Bambu Studio"));
+            return;
+        }
+        // Bambu Studio packs several settings into one line separated by ';'
+        for part in comment.split(';') {
+            if let Some(pos) = part.find(['=', ':']) {
+                self.apply_setting(&part[..pos], &part[pos + 1..]);
+            }
+        }
+    }
+
+    /**
+     * Remember the first non zero heater command so files without a settings comment still get temperatures.
+     */
+    fn apply_command(&mut self, command: &str) {
+        let mut words = command.split_whitespace();
+        let target = match words.next().map(|word| word.to_uppercase()) {
+            Some(word) if word == "M104" || word == "M109" => &mut self.commanded_nozzle_temperature,
+            Some(word) if word == "M140" || word == "M190" => &mut self.commanded_bed_temperature,
+            _ => return,
+        };
+        if target.is_some() {
+            return;
+        }
+        let temperature = words
+            .find_map(|word| word.strip_prefix('S').or(word.strip_prefix('R')))
+            .and_then(|value| value.parse::<f64>().ok())
+            .filter(|value| *value > 0.0);
+        *target = temperature;
+    }
+}
+
+fn set_once<T>(field: &mut Option<T>, value: Option<T>) {
+    if field.is_none() {
+        *field = value;
+    }
+}
+
+/**
+ * Parse "1d 2h 3m 4s" style durations, a bare number is taken as seconds.  Durations too
+ * long to count in seconds give None.
+ */
+pub fn parse_duration(value: &str) -> Option<i64> {
+    if let Ok(seconds) = value.trim().parse::<f64>() {
+        return (seconds.is_finite() && seconds.abs() < i64::MAX as f64).then_some(seconds as i64);
+    }
+    let regex = Regex::new(r"(\d+)\s*([dhms])").unwrap();
+    let mut total = None;
+    for captures in regex.captures_iter(value) {
+        let amount: i64 = captures[1].parse().ok()?;
+        let unit = match &captures[2] {
+            "d" => 86400,
+            "h" => 3600,
+            "m" => 60,
+            _ => 1,
+        };
+        total = Some(amount.checked_mul(unit)?.checked_add(total.unwrap_or(0))?);
+    }
+    total
+}
+
+/**
+ * Format seconds the way slicers do, "1h 2m".
+ */
+pub fn format_duration(seconds: i64) -> String {
+    let days = seconds / 86400;
+    let hours = seconds % 86400 / 3600;
+    let minutes = seconds % 3600 / 60;
+    match (days, hours) {
+        (0, 0) => format!("{}m {}s", minutes, seconds % 60),
+        (0, _) => format!("{}h {}m", hours, minutes),
+        _ => format!("{}d {}h {}m", days, hours, minutes),
+    }
+}
+
+/// Multi extruder values are comma separated, totals are the sum of all extruders.
+fn sum_list(value: &str) -> Option<f64> {
+    let numbers: Vec<f64> = value.split(',').filter_map(|part| part.trim().parse::<f64>().ok()).collect();
+    if numbers.is_empty() {
+        return None;
+    }
+    Some(numbers.iter().sum())
+}
+
+fn first_in_list(value: &str) -> Option<&str> {
+    value.split([',', ';']).map(|part| part.trim().trim_matches('"')).find(|part| !part.is_empty())
+}
+
+fn first_number(value: &str) -> Option<f64> {
+    first_in_list(value)?.parse().ok()
+}
+
+fn parse_text_file(file: &mut File) -> io::Result<Gcode> {
+    let length = file.metadata()?.len();
+    let mut gcode = Gcode::default();
+    let mut head = vec![];
+    file.take(HEAD_BYTES).read_to_end(&mut head)?;
+    parse_text(&mut gcode, &String::from_utf8_lossy(&head));
+    if length > HEAD_BYTES {
+        let tail_start = length.saturating_sub(TAIL_BYTES).max(HEAD_BYTES);
+        file.seek(SeekFrom::Start(tail_start))?;
+        let mut tail = vec![];
+        file.read_to_end(&mut tail)?;
+        let tail = String::from_utf8_lossy(&tail);
+        // the first line is probably cut in half
+        let tail = tail.split_once('\n').map(|(_, rest)| rest).unwrap_or("");
+        parse_text(&mut gcode, tail);
+    }
+    Ok(gcode)
+}
+
+fn parse_text(gcode: &mut Gcode, text: &str) {
+    let mut thumbnail: Option<(GcodeThumbnail, String)> = None;
+    for line in text.lines() {
+        let line = line.trim();
+        let Some(comment) = line.strip_prefix(';') else {
+            let command = line.split(';').next().unwrap_or("");
+            gcode.apply_command(command);
+            continue;
+        };
+        let comment = comment.trim();
+        if let Some((current, encoded)) = thumbnail.as_mut() {
+            if is_thumbnail_marker(comment, "end") {
+                if let Ok(data) = STANDARD.decode(encoded.as_bytes()) {
+                    current.data = data;
+                    gcode.thumbnails.push(current.clone());
+                }
+                thumbnail = None;
+            } else {
+                encoded.push_str(comment);
+            }
+            continue;
+        }
+        if is_thumbnail_marker(comment, "begin") {
+            thumbnail = thumbnail_start(comment).map(|started| (started, String::new()));
+            continue;
+        }
+        gcode.apply_comment(comment);
+    }
+}
+
+/**
+ * Matches "thumbnail begin", "thumbnail_JPG begin", "thumbnail_QOI end" and so on.
+ */
+fn is_thumbnail_marker(comment: &str, marker: &str) -> bool {
+    let mut words = comment.split_whitespace();
+    let is_thumbnail = words.next().is_some_and(|word| word.to_lowercase().starts_with("thumbnail"));
+    is_thumbnail && words.next() == Some(marker)
+}
+
+/**
+ * Parse "thumbnail_JPG begin 300x300 12345" into an empty thumbnail of the right shape.
+ */
+fn thumbnail_start(comment: &str) -> Option<GcodeThumbnail> {
+    let mut words = comment.split_whitespace();
+    let format = match words.next()?.to_lowercase().as_str() {
+        "thumbnail" | "thumbnail_png" => ThumbnailFormat::Png,
+        "thumbnail_jpg" => ThumbnailFormat::Jpg,
+        "thumbnail_qoi" => ThumbnailFormat::Qoi,
+        _ => return None,
+    };
+    words.next();
+    let (width, height) = words.next()?.split_once('x')?;
+    Some(GcodeThumbnail {
+        width: width.parse().ok()?,
+        height: height.parse().ok()?,
+        format,
+        data: vec![],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(parse_duration("1d 2h 3m 4s"), Some(93784));
+        assert_eq!(parse_duration("2h 5m"), Some(7500));
+        assert_eq!(parse_duration("3600"), Some(3600));
+        assert_eq!(parse_duration("90.5"), Some(90));
+        assert_eq!(parse_duration("soon"), None);
+        assert_eq!(parse_duration(""), None);
+    }
+
+    #[test]
+    fn rejects_durations_that_overflow() {
+        assert_eq!(parse_duration("99999999999999999d"), None);
+        assert_eq!(parse_duration("9223372036854775807s 1s"), None);
+        assert_eq!(parse_duration("99999999999999999999999"), None);
+        assert_eq!(parse_duration("inf"), None);
+        assert_eq!(parse_duration("NaN"), None);
+    }
+
+    #[test]
+    fn reads_comment_settings() {
+        let mut gcode = Gcode::default();
+        gcode.apply_comment(" generated by PrusaSlicer 2.7.0+win64 on 2024-01-01 at 10:00:00 UTC");
+        gcode.apply_comment(" estimated printing time (normal mode) = 1h 2m 3s");
+        gcode.apply_comment(" filament used [mm] = 100.5, 20");
+        gcode.apply_comment(" total filament used [g] = 12.25");
+        gcode.apply_comment(" filament_type = PETG;PLA");
+        gcode.apply_comment(" layer_height = 0.2");
+        gcode.apply_comment(" temperature = 215,210");
+        gcode.apply_comment(" bed_temperature = 60");
+        assert_eq!(gcode.slicer.as_deref(), Some("PrusaSlicer 2.7.0+win64"));
+        assert_eq!(gcode.print_time, Some(3723));
+        assert_eq!(gcode.filament_length, Some(120.5));
+        assert_eq!(gcode.filament_weight, Some(12.25));
+        assert_eq!(gcode.filament_type.as_deref(), Some("PETG"));
+        assert_eq!(gcode.layer_height, Some(0.2));
+        assert_eq!(gcode.nozzle_temperature, Some(215.0));
+        assert_eq!(gcode.bed_temperature, Some(60.0));
+    }
+
+    #[test]
+    fn reads_cura_and_bambu_comments() {
+        let mut cura = Gcode::default();
+        cura.apply_comment("Generated with Cura_SteamEngine 5.6.0");
+        cura.apply_comment("TIME:4500");
+        cura.apply_comment("Filament used: 1.5m");
+        assert_eq!(cura.slicer.as_deref(), Some("Cura 5.6.0"));
+        assert_eq!(cura.print_time, Some(4500));
+        assert_eq!(cura.filament_length, Some(1500.0));
+
+        let mut bambu = Gcode::default();
+        bambu.apply_comment(" BambuStudio 1.9.0");
+        bambu.apply_comment(" total estimated time: 10m 5s; total filament length [mm] : 250");
+        assert_eq!(bambu.slicer.as_deref(), Some("Bambu Studio 1.9.0"));
+        assert_eq!(bambu.print_time, Some(605));
+        assert_eq!(bambu.filament_length, Some(250.0));
+    }
+
+    #[test]
+    fn keeps_the_first_value_of_a_setting() {
+        let mut gcode = Gcode::default();
+        gcode.apply_comment(" layer_height = 0.2");
+        gcode.apply_comment(" layer_height = 0.3");
+        gcode.apply_comment(" estimated printing time (normal mode) = 99999999999999999d");
+        gcode.apply_comment(" estimated printing time (normal mode) = 5m");
+        assert_eq!(gcode.layer_height, Some(0.2));
+        assert_eq!(gcode.print_time, Some(300));
+    }
+
+    #[test]
+    fn parses_thumbnail_headers() {
+        let png = thumbnail_start("thumbnail begin 300x200 1234").unwrap();
+        assert_eq!((png.width, png.height, png.format), (300, 200, ThumbnailFormat::Png));
+        let jpg = thumbnail_start("thumbnail_JPG begin 16x16 99").unwrap();
+        assert_eq!(jpg.format, ThumbnailFormat::Jpg);
+        assert_eq!(thumbnail_start("thumbnail_QOI begin 48x48 10").unwrap().format, ThumbnailFormat::Qoi);
+        assert!(thumbnail_start("thumbnail_BMP begin 16x16 10").is_none());
+        assert!(thumbnail_start("thumbnail begin 16 10").is_none());
+        assert!(thumbnail_start("thumbnail begin 99999999999x16 10").is_none());
+        assert!(is_thumbnail_marker("thumbnail_JPG end", "end"));
+        assert!(!is_thumbnail_marker("thumbnails are nice", "begin"));
+    }
+
+    #[test]
+    fn reads_embedded_thumbnails() {
+        let mut gcode = Gcode::default();
+        let text = "; thumbnail begin 2x2 8\n; AQID\n; BA==\n; thumbnail end\n\
+            ; thumbnail begin 100000x100000 4\n; AQ==\n; thumbnail end\n\
+            ; thumbnail begin 4x4 4\n; not base64!\n; thumbnail end\n\
+            M104 S210\nG28\n";
+        parse_text(&mut gcode, text);
+        assert_eq!(gcode.thumbnails.len(), 2);
+        assert_eq!(gcode.thumbnails[0].data, vec![1, 2, 3, 4]);
+        assert_eq!(gcode.largest_thumbnail().unwrap().width, 100000);
+        assert_eq!(gcode.commanded_nozzle_temperature, Some(210.0));
+    }
+}
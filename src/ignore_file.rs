@@ -0,0 +1,98 @@
+/*
+ * Copyright (c) 2025-2026 Mitch Tuck
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! `.3dmignore` files hide paths from scanning.  Each line is a glob matched against paths
+//! relative to the directory holding the file, `#` starts a comment.
+//!
+//! * `*.blend1` or `backups` without a `/` match the name in any directory below.
+//! * `renders/*.png` or `/old` with a `/` match from the directory holding the file.
+//! * A matched directory hides everything inside it.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use log::warn;
+
+pub const IGNORE_FILE_NAME: &str = ".3dmignore";
+
+#[derive(Debug, Clone)]
+pub struct IgnoreFile {
+    root: PathBuf,
+    globs: GlobSet,
+}
+
+impl IgnoreFile {
+    /**
+     * The ignore file in a directory, None when there isn't one or it has no usable patterns.
+     */
+    pub fn load(dir: &Path) -> Option<IgnoreFile> {
+        let contents = fs::read_to_string(dir.join(IGNORE_FILE_NAME)).ok()?;
+        let mut builder = GlobSetBuilder::new();
+        let mut patterns = 0;
+        for line in contents.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let pattern = line.trim_end_matches('/');
+            let pattern = match pattern.strip_prefix('/') {
+                Some(anchored) => anchored.to_string(),
+                None if pattern.contains('/') => pattern.to_string(),
+                None => format!("**/{}", pattern),
+            };
+            match GlobBuilder::new(&pattern).literal_separator(true).build() {
+                Ok(glob) => {
+                    builder.add(glob);
+                    patterns += 1;
+                }
+                Err(err) => warn!("Skipping pattern {} in {}: {}", line, dir.join(IGNORE_FILE_NAME).display(), err),
+            }
+        }
+        if patterns == 0 {
+            return None;
+        }
+        let globs = builder.build().ok()?;
+        Some(IgnoreFile { root: dir.to_path_buf(), globs })
+    }
+
+    /**
+     * The ignore files in every directory above this one, outermost first.
+     */
+    pub fn load_ancestors(dir: &Path) -> Vec<IgnoreFile> {
+        let mut ignore_files: Vec<IgnoreFile> = dir.ancestors().skip(1).filter_map(IgnoreFile::load).collect();
+        ignore_files.reverse();
+        ignore_files
+    }
+
+    /**
+     * Whether the path or a directory between it and this file's directory matches a pattern.
+     */
+    pub fn is_ignored(&self, path: &Path) -> bool {
+        let Ok(relative) = path.strip_prefix(&self.root) else { return false };
+        relative.ancestors()
+            .filter(|ancestor| !ancestor.as_os_str().is_empty())
+            .any(|ancestor| self.globs.is_match(ancestor))
+    }
+}
+
+/**
+ * Whether any of the ignore files hides the path, the ignore files themselves are hidden too.
+ */
+pub fn is_ignored(ignore_files: &[IgnoreFile], path: &Path) -> bool {
+    path.file_name().is_some_and(|name| name == IGNORE_FILE_NAME)
+        || ignore_files.iter().any(|ignore_file| ignore_file.is_ignored(path))
+}
@@ -17,20 +17,33 @@
 
 pub mod config;
 pub mod models;
+mod cost;
 mod db_manager;
+mod file_hash;
+mod gcode;
+mod ignore_file;
+mod mesh;
 mod pages;
+mod renderer;
+mod scanner;
+mod search;
+mod thumbnail_cache;
+mod thumbnail_queue;
+mod watcher;
+mod widgets;
 
-use std::path::{PathBuf};
 use config::Config;
-use iced::{Element};
+use std::path::PathBuf;
+use iced::{Element, Subscription, Task};
 use iced::widget::{button, Theme};
-use which::which;
 #[allow(unused)]
 use log::{error, warn, info, debug, trace};
 
 use env_logger::Env;
 use crate::db_manager::DbManager;
-use crate::pages::{main_view, project, settings};
+use crate::models::project::Project;
+use crate::pages::{duplicates, main_view, print_queue, project, settings, spools, tags};
+use crate::scanner::Scanner;
 pub fn main() -> iced::Result {
     let mut default_log_level = "error";
     if cfg!(debug_assertions) {
@@ -42,17 +55,23 @@ pub fn main() -> iced::Result {
 
     env_logger::init_from_env(env);
 
-    iced::application(ThreeDManager::default, ThreeDManager::update, ThreeDManager::view)
+    iced::application(ThreeDManager::new, ThreeDManager::update, ThreeDManager::view)
         .title(ThreeDManager::title)
         .centered()
         .theme(ThreeDManager::theme)
+        .subscription(ThreeDManager::subscription)
         .run()
 }
 
+#[allow(clippy::large_enum_variant)]
 enum Screen {
     Main(main_view::MainView),
     Project(project::ProjectPage),
     Settings(settings::SettingsPage),
+    Tags(tags::TagsPage),
+    Duplicates(duplicates::DuplicatesPage),
+    Spools(spools::SpoolsPage),
+    PrintQueue(print_queue::PrintQueuePage),
 }
 
 #[derive(Debug, Clone)]
@@ -60,6 +79,14 @@ pub enum Message {
     MainPage(main_view::Message),
     ProjectPage(project::Message),
     SettingsPage(settings::Message),
+    TagsPage(tags::Message),
+    DuplicatesPage(duplicates::Message),
+    SpoolsPage(spools::Message),
+    PrintQueuePage(print_queue::Message),
+    /// Paths under the print paths changed on disk.
+    FilesChanged(Vec<PathBuf>),
+    /// A background sync finished, true when projects were updated.
+    ProjectsSynced(bool),
 }
 
 pub struct ThreeDManager {
@@ -76,6 +103,10 @@ impl ThreeDManager {
             Screen::Main(_)=> "Project List",
             Screen::Project(_) => "Project",
             Screen::Settings(_) => "Settings",
+            Screen::Tags(_) => "Tags",
+            Screen::Duplicates(_) => "Duplicates",
+            Screen::Spools(_) => "Filament",
+            Screen::PrintQueue(_) => "Print Queue",
         };
         format!("3D Manager - {screen}")
     }
@@ -84,40 +115,66 @@ impl ThreeDManager {
         style.border.radius = iced::border::radius(20);
         style
     }
-    fn button_tag_style(theme: &Theme, status :button::Status) -> button::Style {
-        let mut style = button::primary(theme, status);
-        let palette = theme.extended_palette();
-        style.border.radius = iced::border::right(20);
-        style.background = Some(palette.success.strong.color.into());
-        style
+    /**
+     * Style for a tag, in the tag's own colour when it has one.
+     */
+    fn button_tag_style(color: Option<iced::Color>) -> impl Fn(&Theme, button::Status) -> button::Style {
+        move |theme, status| {
+            let mut style = button::primary(theme, status);
+            let palette = theme.extended_palette();
+            style.border.radius = iced::border::right(20);
+            style.background = Some(palette.success.strong.color.into());
+            if let Some(color) = color {
+                style.background = Some(color.into());
+                //dark text on light colours so the name stays readable
+                let luminance = 0.299 * color.r + 0.587 * color.g + 0.114 * color.b;
+                style.text_color = if luminance > 0.6 { iced::Color::BLACK } else { iced::Color::WHITE };
+            }
+            style
+        }
     }
     /**
      * Process Messages
      */
-    fn update(&mut self, message: Message) {
+    fn update(&mut self, message: Message) -> Task<Message> {
         match message {
             Message::MainPage(msg) => {
                 match msg {
                     main_view::Message::SelectProject(project) => {
-                        self.screen = Screen::Project(project::ProjectPage::new(project));
+                        return self.show_project(project);
                     }
                     main_view::Message::ToSettingsPage => {
                         self.screen = Screen::Settings(settings::SettingsPage::new(self.config.clone()));
                     }
+                    main_view::Message::ToTagsPage => {
+                        self.screen = Screen::Tags(tags::TagsPage::new());
+                    }
+                    main_view::Message::ToDuplicatesPage => {
+                        self.screen = Screen::Duplicates(duplicates::DuplicatesPage::new(self.config.clone()));
+                    }
+                    main_view::Message::ToSpoolsPage => {
+                        self.screen = Screen::Spools(spools::SpoolsPage::new(self.config.clone()));
+                    }
+                    main_view::Message::ToPrintQueuePage => {
+                        self.screen = Screen::PrintQueue(print_queue::PrintQueuePage::new(self.config.clone()));
+                    }
                     _ => {
-                        let Screen::Main(page) = &mut self.screen else { return () };
-                        page.update(msg);
+                        let Screen::Main(page) = &mut self.screen else { return Task::none() };
+                        return page.update(msg).map(Message::MainPage);
                     }
                 }
             }
             Message::ProjectPage(msg) => {
                 match msg {
                     project::Message::BackToMain => {
-                        self.screen = Screen::Main(main_view::MainView::new(self.config.clone()));
+                        return self.show_main_view();
+                    }
+                    project::Message::OpenProject(project) => {
+                        return self.show_project(project);
                     }
                     _ => {
-                        let Screen::Project(page) = &mut self.screen else { return () };
-                        page.update(msg);
+                        let Screen::Project(page) = &mut self.screen else { return Task::none() };
+                        return page.update(msg).map(Message::ProjectPage);
                     }
                 }
             }
@@ -126,33 +183,119 @@ impl ThreeDManager {
                     settings::Message::SetTheme(theme) => {
                         self.config.set_theme(theme.clone());
                         //Get settings screen
-                        let Screen::Settings(page) = &mut self.screen else { return () };
+                        let Screen::Settings(page) = &mut self.screen else { return Task::none() };
                         //and give it back the message
-                        page.update(settings::Message::SetTheme(theme));
+                        return page.update(settings::Message::SetTheme(theme)).map(Message::SettingsPage);
 
                     }
                     settings::Message::BackToMain(save) => {
                         if save {
-                            let Screen::Settings(page) = &mut self.screen else { return () };
+                            let Screen::Settings(page) = &mut self.screen else { return Task::none() };
                             //and give it back the message
                             page.save_config();
                         }
+                        let scan_settings = self.config.scan_settings.clone();
                         self.config = Config::default();
-                        self.screen = Screen::Main(main_view::MainView::new(self.config.clone()));
+                        //projects found with the old scan settings may not be projects any more
+                        if self.config.scan_settings != scan_settings {
+                            return self.show_main_view().chain(Task::done(Message::MainPage(main_view::Message::FullRescan)));
+                        }
+                        return self.show_main_view();
                     }
                     _ => {
                         //Get settings screen
-                        let Screen::Settings(page) = &mut self.screen else { return () };
+                        let Screen::Settings(page) = &mut self.screen else { return Task::none() };
                         //and give it back the message
+                        return page.update(msg).map(Message::SettingsPage);
+                    }
+                }
+            }
+            Message::TagsPage(msg) => {
+                match msg {
+                    tags::Message::BackToMain => {
+                        return self.show_main_view();
+                    }
+                    _ => {
+                        let Screen::Tags(page) = &mut self.screen else { return Task::none() };
+                        page.update(msg);
+                    }
+                }
+            }
+            Message::DuplicatesPage(msg) => {
+                match msg {
+                    duplicates::Message::BackToMain => {
+                        return self.show_main_view();
+                    }
+                    _ => {
+                        let Screen::Duplicates(page) = &mut self.screen else { return Task::none() };
+                        return page.update(msg).map(Message::DuplicatesPage);
+                    }
+                }
+            }
+            Message::SpoolsPage(msg) => {
+                match msg {
+                    spools::Message::BackToMain => {
+                        return self.show_main_view();
+                    }
+                    _ => {
+                        let Screen::Spools(page) = &mut self.screen else { return Task::none() };
+                        page.update(msg);
+                    }
+                }
+            }
+            Message::PrintQueuePage(msg) => {
+                match msg {
+                    print_queue::Message::BackToMain => {
+                        return self.show_main_view();
+                    }
+                    print_queue::Message::OpenProject(project_id) => {
+                        let project = ThreeDManager::setup_db_connection().get_project(project_id);
+                        return self.show_project(project);
+                    }
+                    _ => {
+                        let Screen::PrintQueue(page) = &mut self.screen else { return Task::none() };
                         page.update(msg);
                     }
                 }
             }
+            Message::FilesChanged(paths) => {
+                return Scanner::sync_paths_in_background(self.config.clone(), paths).map(Message::ProjectsSynced);
+            }
+            Message::ProjectsSynced(changed) => {
+                if changed && let Screen::Main(page) = &mut self.screen {
+                    return page.refresh().map(Message::MainPage);
+                }
+            }
         }
+        Task::none()
+    }
 
+    /**
+     * Keep projects in sync with the print paths while the app is open.
+     */
+    fn subscription(&self) -> Subscription<Message> {
+        watcher::watch(self.config.print_paths.clone().unwrap_or_default()).map(Message::FilesChanged)
     }
 
+    /**
+     * Switch to a fresh main view and start generating its missing thumbnails.
+     */
+    fn show_main_view(&mut self) -> Task<Message> {
+        let mut main_page = main_view::MainView::new(self.config.clone());
+        let thumbnails = main_page.load_thumbnails().map(Message::MainPage);
+        self.screen = Screen::Main(main_page);
+        thumbnails
+    }
 
+    /**
+     * Switch to a project's page and start loading its model for the viewer.
+     */
+    fn show_project(&mut self, project: Project) -> Task<Message> {
+        let mut project_page = project::ProjectPage::new(project, self.config.clone());
+        let viewer_mesh = project_page.load_viewer_mesh().map(Message::ProjectPage);
+        self.screen = Screen::Project(project_page);
+        viewer_mesh
+    }
 
     /**
      * Pick and render view
@@ -166,6 +309,10 @@ impl ThreeDManager {
             Screen::Main(main_page) => main_page.view().map(Message::MainPage),
             Screen::Project(project_page)=> project_page.view().map(Message::ProjectPage),
             Screen::Settings(settings_page) => settings_page.view().map(Message::SettingsPage),
+            Screen::Tags(tags_page) => tags_page.view().map(Message::TagsPage),
+            Screen::Duplicates(duplicates_page) => duplicates_page.view().map(Message::DuplicatesPage),
+            Screen::Spools(spools_page) => spools_page.view().map(Message::SpoolsPage),
+            Screen::PrintQueue(print_queue_page) => print_queue_page.view().map(Message::PrintQueuePage),
         };
         screen.explain(color)
     }
@@ -182,19 +329,21 @@ impl ThreeDManager {
         db_file.push("3DManager.db");
         DbManager::new(db_file.to_str().unwrap().to_string())
     }
-    pub fn get_stl_thumb() -> String {
-        which("stl-thumb").unwrap_or(PathBuf::default()).to_str().unwrap_or("").to_string()
-    }
 }
-impl Default for ThreeDManager {
-    fn default() -> Self {
+impl ThreeDManager {
+    fn new() -> (Self, Task<Message>) {
         info!("ThreeDManager Started");
         let config = Config::default();
         let db_mgr = Self::setup_db_connection();
         db_mgr.run_migration();
-        Self {
-            screen: Screen::Main(main_view::MainView::new(config.clone())),
-            config,
-        }
+        let mut main_page = main_view::MainView::new(config.clone());
+        let thumbnails = main_page.load_thumbnails().map(Message::MainPage);
+        (
+            Self {
+                screen: Screen::Main(main_page),
+                config,
+            },
+            thumbnails,
+        )
     }
 }
\ No newline at end of file
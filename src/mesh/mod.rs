@@ -0,0 +1,185 @@
+/*
+ * Copyright (c) 2025-2026 Mitch Tuck
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+use std::collections::HashMap;
+use std::io;
+use std::ops::{Add, Mul, Sub};
+use std::path::Path;
+
+pub mod obj;
+pub mod stl;
+pub mod three_mf;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Vec3 {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+impl Vec3 {
+    pub fn new(x: f32, y: f32, z: f32) -> Vec3 {
+        Vec3 { x, y, z }
+    }
+    pub fn dot(&self, other: Vec3) -> f32 {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+    pub fn cross(&self, other: Vec3) -> Vec3 {
+        Vec3 {
+            x: self.y * other.z - self.z * other.y,
+            y: self.z * other.x - self.x * other.z,
+            z: self.x * other.y - self.y * other.x,
+        }
+    }
+    pub fn length(&self) -> f32 {
+        self.dot(*self).sqrt()
+    }
+    pub fn normalize(&self) -> Vec3 {
+        let length = self.length();
+        if length == 0.0 {
+            return *self;
+        }
+        *self * (1.0 / length)
+    }
+    pub fn min(&self, other: Vec3) -> Vec3 {
+        Vec3::new(self.x.min(other.x), self.y.min(other.y), self.z.min(other.z))
+    }
+    pub fn max(&self, other: Vec3) -> Vec3 {
+        Vec3::new(self.x.max(other.x), self.y.max(other.y), self.z.max(other.z))
+    }
+    pub fn is_finite(&self) -> bool {
+        self.x.is_finite() && self.y.is_finite() && self.z.is_finite()
+    }
+}
+
+impl Add for Vec3 {
+    type Output = Vec3;
+    fn add(self, other: Vec3) -> Vec3 {
+        Vec3::new(self.x + other.x, self.y + other.y, self.z + other.z)
+    }
+}
+
+impl Sub for Vec3 {
+    type Output = Vec3;
+    fn sub(self, other: Vec3) -> Vec3 {
+        Vec3::new(self.x - other.x, self.y - other.y, self.z - other.z)
+    }
+}
+
+impl Mul<f32> for Vec3 {
+    type Output = Vec3;
+    fn mul(self, scale: f32) -> Vec3 {
+        Vec3::new(self.x * scale, self.y * scale, self.z * scale)
+    }
+}
+
+/// A triangle soup loaded from a model file.  Units are whatever the file uses, which for
+/// STL and 3MF is millimeters in practice.
+#[derive(Debug, Clone, Default)]
+pub struct Mesh {
+    pub triangles: Vec<[Vec3; 3]>,
+}
+
+impl Mesh {
+    /**
+     * Load a mesh picking the parser from the file extension.
+     */
+    pub fn load(path: &str) -> io::Result<Mesh> {
+        let extension = Path::new(path).extension().and_then(|ext| ext.to_str()).unwrap_or("").to_lowercase();
+        match extension.as_str() {
+            "stl" => stl::load(path),
+            "3mf" => three_mf::load(path),
+            "obj" => obj::load(path),
+            _ => Err(io::Error::new(io::ErrorKind::Unsupported, format!("{} is not a supported model file", path))),
+        }
+    }
+
+    /**
+     * The encoded preview image a model file carries, only 3MF packages have one.
+     */
+    pub fn read_embedded_thumbnail(path: &str) -> io::Result<Option<Vec<u8>>> {
+        let extension = Path::new(path).extension().and_then(|ext| ext.to_str()).unwrap_or("").to_lowercase();
+        match extension.as_str() {
+            "3mf" => three_mf::read_thumbnail(path),
+            _ => Ok(None),
+        }
+    }
+
+    pub fn is_model_file(path: &str) -> bool {
+        let extension = Path::new(path).extension().and_then(|ext| ext.to_str()).unwrap_or("").to_lowercase();
+        matches!(extension.as_str(), "stl" | "3mf" | "obj")
+    }
+
+    /**
+     * Returns the (min, max) corners of the axis aligned bounding box.
+     */
+    pub fn bounds(&self) -> Option<(Vec3, Vec3)> {
+        let first = self.triangles.first()?[0];
+        let mut min = first;
+        let mut max = first;
+        for triangle in self.triangles.iter() {
+            for vertex in triangle.iter() {
+                min = min.min(*vertex);
+                max = max.max(*vertex);
+            }
+        }
+        Some((min, max))
+    }
+
+    /**
+     * Total area of all triangles.
+     */
+    pub fn surface_area(&self) -> f64 {
+        self.triangles.iter()
+            .map(|triangle| (triangle[1] - triangle[0]).cross(triangle[2] - triangle[0]).length() as f64 * 0.5)
+            .sum()
+    }
+
+    /**
+     * Enclosed volume from summing signed tetrahedra against the origin.  Only meaningful for
+     * watertight meshes, the absolute value is returned so inverted winding still works.
+     */
+    pub fn volume(&self) -> f64 {
+        let signed: f64 = self.triangles.iter()
+            .map(|triangle| triangle[0].dot(triangle[1].cross(triangle[2])) as f64 / 6.0)
+            .sum();
+        signed.abs()
+    }
+
+    /**
+     * A mesh is watertight when every edge is shared by exactly two triangles that traverse it
+     * in opposite directions.  Vertices are matched on exact position.
+     */
+    pub fn is_watertight(&self) -> bool {
+        if self.triangles.is_empty() {
+            return false;
+        }
+        let key = |vertex: &Vec3| [vertex.x.to_bits(), vertex.y.to_bits(), vertex.z.to_bits()];
+        let mut edges: HashMap<([u32; 3], [u32; 3]), i32> = HashMap::new();
+        for triangle in self.triangles.iter() {
+            for (start, end) in [(0, 1), (1, 2), (2, 0)] {
+                let (a, b) = (key(&triangle[start]), key(&triangle[end]));
+                if a == b {
+                    continue;
+                }
+                // +1 for one direction and -1 for the other, a closed edge sums to zero
+                let (edge, direction) = if a < b { ((a, b), 1) } else { ((b, a), -1) };
+                *edges.entry(edge).or_insert(0) += direction;
+            }
+        }
+        edges.values().all(|balance| *balance == 0)
+    }
+}
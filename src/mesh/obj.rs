@@ -0,0 +1,74 @@
+/*
+ * Copyright (c) 2025-2026 Mitch Tuck
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+use std::fs;
+use std::io;
+use super::{Mesh, Vec3};
+
+pub fn load(path: &str) -> io::Result<Mesh> {
+    let data = fs::read(path)?;
+    parse(&String::from_utf8_lossy(&data))
+}
+
+/**
+ * Parse the geometry of a Wavefront OBJ file.  Only vertices and faces matter here, polygons
+ * are split into triangle fans and texture/normal indices are ignored.
+ */
+pub fn parse(text: &str) -> io::Result<Mesh> {
+    let mut vertices: Vec<Vec3> = vec![];
+    let mut triangles = vec![];
+    for line in text.lines() {
+        let mut parts = line.split_whitespace();
+        match parts.next() {
+            Some("v") => {
+                let coordinates: Vec<f32> = parts.take(3).filter_map(|part| part.parse::<f32>().ok()).collect();
+                if coordinates.len() != 3 {
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, format!("invalid OBJ vertex line: {}", line.trim())));
+                }
+                vertices.push(Vec3::new(coordinates[0], coordinates[1], coordinates[2]));
+            }
+            Some("f") => {
+                let mut face = vec![];
+                for part in parts {
+                    face.push(resolve_index(part, vertices.len()).ok_or_else(|| {
+                        io::Error::new(io::ErrorKind::InvalidData, format!("invalid OBJ face line: {}", line.trim()))
+                    })?);
+                }
+                for index in 1..face.len().saturating_sub(1) {
+                    triangles.push([vertices[face[0]], vertices[face[index]], vertices[face[index + 1]]]);
+                }
+            }
+            _ => {}
+        }
+    }
+    if triangles.is_empty() {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "OBJ file contains no faces"));
+    }
+    Ok(Mesh { triangles })
+}
+
+/**
+ * Face entries look like `v`, `v/vt`, `v//vn` or `v/vt/vn`, are 1 based and may be negative
+ * to count back from the last vertex.
+ */
+fn resolve_index(entry: &str, vertex_count: usize) -> Option<usize> {
+    let index = entry.split('/').next()?.parse::<i64>().ok()?;
+    let resolved = if index < 0 { vertex_count as i64 + index } else { index - 1 };
+    if resolved < 0 || resolved >= vertex_count as i64 {
+        return None;
+    }
+    Some(resolved as usize)
+}
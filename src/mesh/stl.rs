@@ -0,0 +1,94 @@
+/*
+ * Copyright (c) 2025-2026 Mitch Tuck
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+use std::fs;
+use std::io;
+use super::{Mesh, Vec3};
+
+pub fn load(path: &str) -> io::Result<Mesh> {
+    let data = fs::read(path)?;
+    parse(&data)
+}
+
+/**
+ * Parse either flavour of STL.  Some exporters write binary files that start with "solid" so
+ * the size check has to win over the header text.
+ */
+pub fn parse(data: &[u8]) -> io::Result<Mesh> {
+    if is_binary(data) {
+        return parse_binary(data);
+    }
+    if data.starts_with(b"solid") {
+        return parse_ascii(data);
+    }
+    Err(io::Error::new(io::ErrorKind::InvalidData, "not a valid STL file"))
+}
+
+fn is_binary(data: &[u8]) -> bool {
+    if data.len() < 84 {
+        return false;
+    }
+    let count = u32::from_le_bytes([data[80], data[81], data[82], data[83]]) as usize;
+    data.len() == 84 + count * 50
+}
+
+fn parse_binary(data: &[u8]) -> io::Result<Mesh> {
+    let count = u32::from_le_bytes([data[80], data[81], data[82], data[83]]) as usize;
+    let mut triangles = Vec::with_capacity(count);
+    for record in data[84..].chunks_exact(50) {
+        // skip the 12 byte facet normal, we recompute it from the winding when needed
+        let mut vertices = [Vec3::default(); 3];
+        for (index, vertex) in vertices.iter_mut().enumerate() {
+            let offset = 12 + index * 12;
+            *vertex = Vec3::new(
+                read_f32(record, offset),
+                read_f32(record, offset + 4),
+                read_f32(record, offset + 8),
+            );
+        }
+        triangles.push(vertices);
+    }
+    Ok(Mesh { triangles })
+}
+
+fn read_f32(data: &[u8], offset: usize) -> f32 {
+    f32::from_le_bytes([data[offset], data[offset + 1], data[offset + 2], data[offset + 3]])
+}
+
+fn parse_ascii(data: &[u8]) -> io::Result<Mesh> {
+    let text = String::from_utf8_lossy(data);
+    let mut triangles = Vec::new();
+    let mut current: Vec<Vec3> = Vec::with_capacity(3);
+    for line in text.lines() {
+        let mut parts = line.split_whitespace();
+        if parts.next() != Some("vertex") {
+            continue;
+        }
+        let coordinates: Vec<f32> = parts.filter_map(|part| part.parse::<f32>().ok()).collect();
+        if coordinates.len() != 3 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, format!("invalid STL vertex line: {}", line.trim())));
+        }
+        current.push(Vec3::new(coordinates[0], coordinates[1], coordinates[2]));
+        if current.len() == 3 {
+            triangles.push([current[0], current[1], current[2]]);
+            current.clear();
+        }
+    }
+    if triangles.is_empty() {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "STL file contains no facets"));
+    }
+    Ok(Mesh { triangles })
+}
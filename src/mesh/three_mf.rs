@@ -0,0 +1,304 @@
+/*
+ * Copyright (c) 2025-2026 Mitch Tuck
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, Read};
+use zip::ZipArchive;
+use super::{Mesh, Vec3};
+
+const DEFAULT_MODEL_PATH: &str = "3D/3dmodel.model";
+/// Where slicers put the package thumbnail when the relationships don't say, PrusaSlicer then Bambu Studio.
+const DEFAULT_THUMBNAIL_PATHS: [&str; 2] = ["Metadata/thumbnail.png", "Metadata/plate_1.png"];
+/// Components can nest, but anything this deep is a broken or malicious file.
+const MAX_COMPONENT_DEPTH: usize = 16;
+/// Components can also repeat objects many times over, stop once a model gets this big.
+const MAX_TRIANGLES: usize = 20_000_000;
+/// Objects placed in total, so components of empty objects can't multiply without limit either.
+const MAX_OBJECT_INSTANCES: usize = 1_000_000;
+
+/// 3MF affine transform, stored the way the spec writes it: "m00 m01 m02 m10 m11 m12 m20 m21 m22 m30 m31 m32".
+#[derive(Debug, Clone, Copy)]
+struct Transform([f32; 12]);
+
+impl Transform {
+    fn identity() -> Transform {
+        Transform([1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0])
+    }
+    fn parse(value: Option<&str>) -> Transform {
+        let Some(value) = value else { return Transform::identity() };
+        let numbers: Vec<f32> = value.split_whitespace().filter_map(|part| part.parse::<f32>().ok()).collect();
+        if numbers.len() != 12 {
+            return Transform::identity();
+        }
+        let mut matrix = [0.0; 12];
+        matrix.copy_from_slice(&numbers);
+        Transform(matrix)
+    }
+    fn apply(&self, point: Vec3) -> Vec3 {
+        let m = self.0;
+        Vec3::new(
+            point.x * m[0] + point.y * m[3] + point.z * m[6] + m[9],
+            point.x * m[1] + point.y * m[4] + point.z * m[7] + m[10],
+            point.x * m[2] + point.y * m[5] + point.z * m[8] + m[11],
+        )
+    }
+    /**
+     * Returns the transform that applies `self` first and then `parent`.
+     */
+    fn then(&self, parent: &Transform) -> Transform {
+        let rows = [
+            Vec3::new(self.0[0], self.0[1], self.0[2]),
+            Vec3::new(self.0[3], self.0[4], self.0[5]),
+            Vec3::new(self.0[6], self.0[7], self.0[8]),
+        ];
+        let mut matrix = [0.0; 12];
+        for (index, row) in rows.iter().enumerate() {
+            // rows are directions so they only take the linear part of the parent
+            let rotated = parent.apply(*row) - parent.apply(Vec3::default());
+            matrix[index * 3] = rotated.x;
+            matrix[index * 3 + 1] = rotated.y;
+            matrix[index * 3 + 2] = rotated.z;
+        }
+        let translation = parent.apply(Vec3::new(self.0[9], self.0[10], self.0[11]));
+        matrix[9] = translation.x;
+        matrix[10] = translation.y;
+        matrix[11] = translation.z;
+        Transform(matrix)
+    }
+}
+
+struct Component {
+    object_id: String,
+    path: Option<String>,
+    transform: Transform,
+}
+
+enum Object {
+    Mesh(Vec<Vec3>, Vec<[usize; 3]>),
+    Components(Vec<Component>),
+}
+
+struct BuildItem {
+    object_id: String,
+    path: Option<String>,
+    transform: Transform,
+}
+
+struct ModelFile {
+    objects: HashMap<String, Object>,
+    build: Vec<BuildItem>,
+}
+
+pub fn load(path: &str) -> io::Result<Mesh> {
+    let mut archive = ZipArchive::new(File::open(path)?).map_err(io::Error::other)?;
+    let root_path = root_model_path(&mut archive);
+    let mut root = parse_model(&read_entry(&mut archive, &root_path)?)?;
+    let build = std::mem::take(&mut root.build);
+    let mut models: HashMap<String, ModelFile> = HashMap::new();
+    models.insert(root_path.clone(), root);
+    let mut mesh = Mesh::default();
+    let mut instances = 0;
+    for item in build.iter() {
+        let model_path = item.path.clone().unwrap_or(root_path.clone());
+        add_object(&mut archive, &mut models, &model_path, &item.object_id, item.transform, &mut mesh, 0, &mut instances)?;
+    }
+    if mesh.triangles.is_empty() {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "3MF file contains no printable meshes"));
+    }
+    Ok(mesh)
+}
+
+/**
+ * The encoded thumbnail image stored in the package, if there is one.
+ */
+pub fn read_thumbnail(path: &str) -> io::Result<Option<Vec<u8>>> {
+    let mut archive = ZipArchive::new(File::open(path)?).map_err(io::Error::other)?;
+    let mut candidates: Vec<String> = vec![];
+    if let Ok(rels) = read_entry(&mut archive, "_rels/.rels")
+        && let Ok(document) = roxmltree::Document::parse(&rels) {
+        candidates.extend(document.descendants()
+            .filter(|node| node.tag_name().name() == "Relationship")
+            .filter(|node| node.attribute("Type").unwrap_or("").ends_with("/thumbnail"))
+            .filter_map(|node| node.attribute("Target"))
+            .map(normalize_part_path));
+    }
+    candidates.extend(DEFAULT_THUMBNAIL_PATHS.iter().map(|path| path.to_string()));
+    for candidate in candidates {
+        let Ok(mut entry) = archive.by_name(&candidate) else { continue };
+        let mut data = vec![];
+        entry.read_to_end(&mut data)?;
+        return Ok(Some(data));
+    }
+    Ok(None)
+}
+
+/**
+ * Find the start part from the package relationships, falling back to the conventional location.
+ */
+fn root_model_path(archive: &mut ZipArchive<File>) -> String {
+    let Ok(rels) = read_entry(archive, "_rels/.rels") else { return DEFAULT_MODEL_PATH.to_string() };
+    let Ok(document) = roxmltree::Document::parse(&rels) else { return DEFAULT_MODEL_PATH.to_string() };
+    document.descendants()
+        .filter(|node| node.tag_name().name() == "Relationship")
+        .find(|node| node.attribute("Type").unwrap_or("").ends_with("/3dmodel"))
+        .and_then(|node| node.attribute("Target"))
+        .map(normalize_part_path)
+        .unwrap_or(DEFAULT_MODEL_PATH.to_string())
+}
+
+fn normalize_part_path(path: &str) -> String {
+    path.trim_start_matches('/').to_string()
+}
+
+fn read_entry(archive: &mut ZipArchive<File>, name: &str) -> io::Result<String> {
+    let mut entry = archive.by_name(name).map_err(io::Error::other)?;
+    let mut contents = String::new();
+    entry.read_to_string(&mut contents)?;
+    Ok(contents)
+}
+
+fn parse_model(contents: &str) -> io::Result<ModelFile> {
+    let document = roxmltree::Document::parse(contents)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    let mut model = ModelFile { objects: HashMap::new(), build: vec![] };
+    for node in document.descendants() {
+        match node.tag_name().name() {
+            "object" => {
+                let Some(id) = node.attribute("id") else { continue };
+                if let Some(object) = parse_object(node) {
+                    model.objects.insert(id.to_string(), object);
+                }
+            }
+            "item" => {
+                let Some(object_id) = node.attribute("objectid") else { continue };
+                model.build.push(BuildItem {
+                    object_id: object_id.to_string(),
+                    path: path_attribute(node),
+                    transform: Transform::parse(node.attribute("transform")),
+                });
+            }
+            _ => {}
+        }
+    }
+    Ok(model)
+}
+
+fn parse_object(node: roxmltree::Node) -> Option<Object> {
+    for child in node.children().filter(|child| child.is_element()) {
+        match child.tag_name().name() {
+            "mesh" => {
+                let mut vertices = vec![];
+                let mut triangles = vec![];
+                for element in child.descendants() {
+                    match element.tag_name().name() {
+                        "vertex" => {
+                            vertices.push(Vec3::new(
+                                float_attribute(element, "x"),
+                                float_attribute(element, "y"),
+                                float_attribute(element, "z"),
+                            ));
+                        }
+                        "triangle" => {
+                            let indices = ["v1", "v2", "v3"].map(|name| {
+                                element.attribute(name).and_then(|value| value.parse::<usize>().ok())
+                            });
+                            if let [Some(v1), Some(v2), Some(v3)] = indices {
+                                triangles.push([v1, v2, v3]);
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                return Some(Object::Mesh(vertices, triangles));
+            }
+            "components" => {
+                let components = child.children()
+                    .filter(|component| component.tag_name().name() == "component")
+                    .filter_map(|component| {
+                        Some(Component {
+                            object_id: component.attribute("objectid")?.to_string(),
+                            path: path_attribute(component),
+                            transform: Transform::parse(component.attribute("transform")),
+                        })
+                    })
+                    .collect();
+                return Some(Object::Components(components));
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// The production extension's `p:path` attribute, matched on local name so any prefix works.
+fn path_attribute(node: roxmltree::Node) -> Option<String> {
+    node.attributes()
+        .find(|attribute| attribute.name() == "path")
+        .map(|attribute| normalize_part_path(attribute.value()))
+}
+
+fn float_attribute(node: roxmltree::Node, name: &str) -> f32 {
+    node.attribute(name).and_then(|value| value.parse::<f32>().ok()).unwrap_or(0.0)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn add_object(
+    archive: &mut ZipArchive<File>,
+    models: &mut HashMap<String, ModelFile>,
+    model_path: &str,
+    object_id: &str,
+    transform: Transform,
+    mesh: &mut Mesh,
+    depth: usize,
+    instances: &mut usize,
+) -> io::Result<()> {
+    if depth > MAX_COMPONENT_DEPTH {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "3MF components nest too deeply"));
+    }
+    *instances += 1;
+    if *instances > MAX_OBJECT_INSTANCES {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "3MF file places too many objects"));
+    }
+    if !models.contains_key(model_path) {
+        let model = parse_model(&read_entry(archive, model_path)?)?;
+        models.insert(model_path.to_string(), model);
+    }
+    let children: Vec<(String, String, Transform)> = match models[model_path].objects.get(object_id) {
+        Some(Object::Mesh(vertices, triangles)) => {
+            if mesh.triangles.len() + triangles.len() > MAX_TRIANGLES {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "3MF file has too many triangles"));
+            }
+            for triangle in triangles.iter() {
+                if triangle.iter().any(|index| *index >= vertices.len()) {
+                    continue;
+                }
+                mesh.triangles.push(triangle.map(|index| transform.apply(vertices[index])));
+            }
+            vec![]
+        }
+        Some(Object::Components(components)) => components.iter().map(|component| (
+            component.path.clone().unwrap_or(model_path.to_string()),
+            component.object_id.clone(),
+            component.transform.then(&transform),
+        )).collect(),
+        None => vec![],
+    };
+    for (child_path, child_id, child_transform) in children {
+        add_object(archive, models, &child_path, &child_id, child_transform, mesh, depth + 1, instances)?;
+    }
+    Ok(())
+}
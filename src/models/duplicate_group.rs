@@ -0,0 +1,132 @@
+/*
+ * Copyright (c) 2025-2026 Mitch Tuck
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::collections::HashSet;
+use std::fmt::Display;
+use std::fs::{self, Metadata};
+use std::io;
+use std::path::Path;
+use crate::models::file::ProjectFile;
+
+/// Files with the same content in more than one project, each with the name of its project.
+#[derive(Debug, Clone)]
+pub struct DuplicateGroup {
+    pub hash: String,
+    pub size: i64,
+    pub files: Vec<(ProjectFile, String)>,
+}
+
+impl DuplicateGroup {
+    /**
+     * Copies taking up their own space on disk, symlinks and hardlinks to another copy don't count.
+     */
+    pub fn stored_copies(&self) -> usize {
+        let mut seen = HashSet::new();
+        self.files.iter().filter(|(file, _)| match fs::symlink_metadata(&file.path) {
+            Ok(metadata) if metadata.file_type().is_symlink() => false,
+            Ok(metadata) => match file_key(&metadata) {
+                Some(key) => seen.insert(key),
+                None => true,
+            },
+            Err(_) => false,
+        }).count()
+    }
+
+    pub fn wasted_bytes(&self) -> u64 {
+        self.size as u64 * self.stored_copies().saturating_sub(1) as u64
+    }
+
+    pub fn project_ids(&self) -> Vec<i32> {
+        let mut project_ids: Vec<i32> = self.files.iter().map(|(file, _)| file.project_id).collect();
+        project_ids.sort();
+        project_ids.dedup();
+        project_ids
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkKind {
+    Symlink,
+    Hardlink,
+}
+
+impl Display for LinkKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            LinkKind::Symlink => "symlinks",
+            LinkKind::Hardlink => "hardlinks",
+        })
+    }
+}
+
+/**
+ * Swap a duplicate for a link to the copy being kept.  The link is made beside the duplicate
+ * and renamed over it so the file is left alone when linking fails, hardlinks for example
+ * can't cross drives.
+ */
+pub fn replace_with_link(keep: &Path, duplicate: &Path, kind: LinkKind) -> io::Result<()> {
+    //renaming a hardlink over another link to the same file does nothing and leaves the temp link behind
+    if is_same_file(keep, duplicate) {
+        return Ok(());
+    }
+    let mut temp_name = duplicate.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(".3dmlink");
+    let temp_path = duplicate.with_file_name(temp_name);
+    match kind {
+        LinkKind::Symlink => symlink_file(keep, &temp_path)?,
+        LinkKind::Hardlink => fs::hard_link(keep, &temp_path)?,
+    }
+    fs::rename(&temp_path, duplicate).inspect_err(|_| {
+        let _ = fs::remove_file(&temp_path);
+    })
+}
+
+pub fn is_symlink(path: &Path) -> bool {
+    fs::symlink_metadata(path).is_ok_and(|metadata| metadata.file_type().is_symlink())
+}
+
+/**
+ * Whether both paths end up at the same file on disk, through symlinks or hardlinks.
+ */
+pub fn is_same_file(a: &Path, b: &Path) -> bool {
+    match (fs::metadata(a), fs::metadata(b)) {
+        (Ok(a), Ok(b)) => file_key(&a).is_some_and(|key| file_key(&b) == Some(key)),
+        _ => false,
+    }
+}
+
+#[cfg(unix)]
+fn symlink_file(original: &Path, link: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(original, link)
+}
+
+#[cfg(windows)]
+fn symlink_file(original: &Path, link: &Path) -> io::Result<()> {
+    std::os::windows::fs::symlink_file(original, link)
+}
+
+/// Device and inode, the same for every hardlink to a file.
+#[cfg(unix)]
+fn file_key(metadata: &Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn file_key(_metadata: &Metadata) -> Option<(u64, u64)> {
+    None
+}
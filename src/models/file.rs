@@ -14,14 +14,20 @@
  * You should have received a copy of the GNU General Public License
  * along with this program.  If not, see <http://www.gnu.org/licenses/>.
  */
-use std::{fs, path};
+use std::{fs, io, path};
 use serde::{Serialize, Deserialize};
 use regex::Regex;
 use path::{PathBuf, Path};
 use fs::create_dir_all;
-use std::process::Stdio;
 #[allow(unused)]
 use log::{error, warn, info, debug, trace};
+use crate::config::Config;
+use crate::gcode::Gcode;
+use crate::mesh::Mesh;
+use crate::models::gcode_info::GcodeInfo;
+use crate::models::mesh_info::MeshInfo;
+use crate::renderer;
+use crate::thumbnail_cache::ThumbnailCache;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ProjectFile {
@@ -30,26 +36,55 @@ pub struct ProjectFile {
     pub notes: Option<String>,
     pub project_id: i32,
     pub default: bool,
+    pub mesh: Option<MeshInfo>,
+    pub gcode: Option<GcodeInfo>,
 }
 
 impl ProjectFile {
-    pub fn get_image_path(&self, stl_thumb_path: String) -> String {
+    pub fn get_image_path(&self, config: &Config) -> String {
         if self.is_image_type() {
             return self.path.clone();
         }
         if self.can_generate_to_image() {
-            return self.get_generated_image_path(stl_thumb_path);
+            return self.get_generated_image_path(config);
         }
         "".to_string()
     }
+    /**
+     * Like get_image_path but never renders, returns None when the thumbnail still needs generating.
+     * Read only libraries keep their thumbnails in the central cache, so it is checked as well.
+     */
+    pub fn cached_image_path(&self, config: &Config, cache: &ThumbnailCache) -> Option<String> {
+        if self.is_image_type() {
+            return Some(self.path.clone());
+        }
+        if self.can_generate_to_image() {
+            if config.use_thumbnail_cache() {
+                return cache.lookup(&self.path);
+            }
+            let image_file = self.generated_image_file();
+            if self.is_thumbnail_current(&image_file) {
+                return Some(image_file.to_str().unwrap().to_string());
+            }
+            return cache.lookup(&self.path);
+        }
+        Some("".to_string())
+    }
     pub fn is_image_type(&self) -> bool {
         let regex = Regex::new(r"((?i)\.png|\.jpg|\.jpeg|\.gif)").unwrap();
         regex.is_match(&self.path)
     }
 
     pub fn can_generate_to_image(&self) -> bool {
-        let regex = Regex::new(r"((?i)\.stl|\.3mf)").unwrap();
-        regex.is_match(&self.path)
+        Mesh::is_model_file(&self.path) || Gcode::is_gcode_file(&self.path)
+    }
+
+    pub fn is_model_type(&self) -> bool {
+        Mesh::is_model_file(&self.path)
+    }
+
+    pub fn is_gcode_type(&self) -> bool {
+        Gcode::is_gcode_file(&self.path)
     }
 
     pub fn is_text_type(&self) -> bool {
@@ -64,7 +99,10 @@ impl ProjectFile {
         false
     }
 
-    pub fn get_generated_image_path(&self, stl_thumb_path: String) -> String {
+    /**
+     * Location of the generated thumbnail, `.3DManager/<file>.png` next to the source file.
+     */
+    fn generated_image_file(&self) -> PathBuf {
         //get the path to file source
         let mut path = PathBuf::from(&self.path.clone());
         let filename = path.file_name().unwrap().to_str().unwrap().to_string();
@@ -72,29 +110,85 @@ impl ProjectFile {
         path = path.parent().unwrap().to_path_buf();
         //set the generated directory
         path.push(".3DManager");
-        //ensure generated directory exists
-        create_dir_all(&path).unwrap();
-        let image_file = format!("{}/{}{}", path.to_str().unwrap(), filename, ".png");
-        if !Path::new(&image_file).exists() {
-            info!("Creating image file {}", image_file);
-            let process = std::process::Command::new(stl_thumb_path)
-                .args(&[self.path.clone(), image_file.clone()])
-                .stderr(Stdio::piped())
-                .stdout(Stdio::piped())
-                .spawn().unwrap();
+        path.push(format!("{}{}", filename, ".png"));
+        path
+    }
 
-            let output = process.wait_with_output().unwrap();
+    /**
+     * A thumbnail is current when it exists and is newer than the file it was made from.
+     */
+    fn is_thumbnail_current(&self, image_file: &Path) -> bool {
+        let Ok(image_modified) = fs::metadata(image_file).and_then(|metadata| metadata.modified()) else { return false };
+        match fs::metadata(&self.path).and_then(|metadata| metadata.modified()) {
+            Ok(source_modified) => source_modified <= image_modified,
+            Err(_) => true,
+        }
+    }
 
-            if output.status.success() {
-                return image_file
-            } else {
-                error!("Error generating image file {}. stl_thumb error  is {}", image_file, String::from_utf8(output.stderr).unwrap_or("".to_string()));
-            }
-        } else {
+    pub fn get_generated_image_path(&self, config: &Config) -> String {
+        if config.use_thumbnail_cache() {
+            return self.get_central_cache_image_path(config);
+        }
+        let image_path = self.generated_image_file();
+        let image_file = image_path.to_str().unwrap().to_string();
+        if self.is_thumbnail_current(&image_path) {
             return image_file;
         }
+        //ensure generated directory exists, read only libraries use the central cache instead
+        if let Err(err) = create_dir_all(image_path.parent().unwrap()) {
+            warn!("Can not create thumbnail directory for {} ({}), using the central cache", self.path, err);
+            return self.get_central_cache_image_path(config);
+        }
+        info!("Creating image file {}", image_file);
+        match self.generate_image(&image_file, config) {
+            Ok(()) => image_file,
+            Err(err) => {
+                error!("Error generating image file {}. Render error is {}", image_file, err);
+                "".to_string()
+            }
+        }
+    }
 
-        "".to_string()
+    fn get_central_cache_image_path(&self, config: &Config) -> String {
+        let cache = ThumbnailCache::new(config);
+        match cache.get_or_create(&self.path, |image_file| self.generate_image(image_file, config)) {
+            Ok(image_file) => image_file,
+            Err(err) => {
+                error!("Error generating cached image for {}. Render error is {}", self.path, err);
+                "".to_string()
+            }
+        }
+    }
+
+    /**
+     * Write the thumbnail for this file, using the image the slicer embedded when there is one
+     * and rendering the model otherwise.  G-code can't be rendered so it needs an embedded image.
+     */
+    fn generate_image(&self, image_file: &str, config: &Config) -> io::Result<()> {
+        if config.use_embedded_thumbnails() || self.is_gcode_type() {
+            match self.embedded_thumbnail() {
+                Ok(Some(thumbnail)) => return renderer::save_image_to_file(&thumbnail, &config.render_settings(), image_file),
+                Ok(None) => {}
+                Err(err) => warn!("Could not read embedded thumbnail of {}: {}", self.path, err),
+            }
+        }
+        if self.is_gcode_type() {
+            return Err(io::Error::new(io::ErrorKind::NotFound, "G-code file has no embedded thumbnail"));
+        }
+        let mesh = Mesh::load(&self.path)?;
+        renderer::render_to_file(&mesh, &config.render_settings(), image_file)
+    }
+
+    /**
+     * The decoded preview stored inside a 3MF package or G-code file.
+     */
+    pub fn embedded_thumbnail(&self) -> io::Result<Option<image::RgbaImage>> {
+        if self.is_gcode_type() {
+            let gcode = Gcode::load(&self.path)?;
+            return Ok(gcode.largest_thumbnail().and_then(|thumbnail| thumbnail.to_rgba()));
+        }
+        let Some(data) = Mesh::read_embedded_thumbnail(&self.path)? else { return Ok(None) };
+        Ok(image::load_from_memory(&data).ok().map(|thumbnail| thumbnail.to_rgba8()))
     }
 }
 impl Default for ProjectFile {
@@ -105,6 +199,8 @@ impl Default for ProjectFile {
             notes: Some("".to_string()),
             project_id: 0,
             default: false,
+            mesh: None,
+            gcode: None,
         }
     }
 }
\ No newline at end of file
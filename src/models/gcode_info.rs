@@ -0,0 +1,52 @@
+/*
+ * Copyright (c) 2025-2026 Mitch Tuck
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use serde::{Serialize, Deserialize};
+use crate::gcode::Gcode;
+
+/// Slicer metadata of a G-code file.  Print time is in seconds, filament length in
+/// millimeters, weight in grams and temperatures in celsius.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct GcodeInfo {
+    pub project_file_id: i32,
+    pub slicer: Option<String>,
+    pub print_time: Option<i64>,
+    pub filament_length: Option<f64>,
+    pub filament_weight: Option<f64>,
+    pub filament_type: Option<String>,
+    pub layer_height: Option<f64>,
+    pub nozzle_temperature: Option<f64>,
+    pub bed_temperature: Option<f64>,
+    pub has_thumbnail: bool,
+}
+
+impl GcodeInfo {
+    pub fn from_gcode(project_file_id: i32, gcode: &Gcode) -> GcodeInfo {
+        GcodeInfo {
+            project_file_id,
+            slicer: gcode.slicer.clone(),
+            print_time: gcode.print_time,
+            filament_length: gcode.filament_length,
+            filament_weight: gcode.filament_weight,
+            filament_type: gcode.filament_type.clone(),
+            layer_height: gcode.layer_height,
+            nozzle_temperature: gcode.nozzle_temperature,
+            bed_temperature: gcode.bed_temperature,
+            has_thumbnail: !gcode.thumbnails.is_empty(),
+        }
+    }
+}
@@ -0,0 +1,46 @@
+/*
+ * Copyright (c) 2025-2026 Mitch Tuck
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use serde::{Serialize, Deserialize};
+
+/// What a filament costs and weighs, used to estimate what a print costs.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Material {
+    pub name: String,
+    pub price_per_kg: f64,
+    /// Grams per cubic centimeter.
+    pub density: f64,
+}
+
+impl Material {
+    pub fn new(name: &str, price_per_kg: f64, density: f64) -> Material {
+        Material { name: name.to_string(), price_per_kg, density }
+    }
+
+    /**
+     * Common filaments at typical prices, used until materials are set up in the settings.
+     */
+    pub fn defaults() -> Vec<Material> {
+        vec![
+            Material::new("PLA", 20.0, 1.24),
+            Material::new("PETG", 22.0, 1.27),
+            Material::new("ABS", 22.0, 1.04),
+            Material::new("ASA", 28.0, 1.07),
+            Material::new("TPU", 35.0, 1.21),
+        ]
+    }
+}
@@ -0,0 +1,51 @@
+/*
+ * Copyright (c) 2025-2026 Mitch Tuck
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use serde::{Serialize, Deserialize};
+use crate::mesh::Mesh;
+
+/// Measurements of a model file, sizes are in millimeters.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct MeshInfo {
+    pub project_file_id: i32,
+    pub size_x: f64,
+    pub size_y: f64,
+    pub size_z: f64,
+    pub triangle_count: i64,
+    pub surface_area: f64,
+    pub volume: f64,
+    pub watertight: bool,
+}
+
+impl MeshInfo {
+    pub fn from_mesh(project_file_id: i32, mesh: &Mesh) -> MeshInfo {
+        let (size_x, size_y, size_z) = match mesh.bounds() {
+            Some((min, max)) => ((max.x - min.x) as f64, (max.y - min.y) as f64, (max.z - min.z) as f64),
+            None => (0.0, 0.0, 0.0),
+        };
+        MeshInfo {
+            project_file_id,
+            size_x,
+            size_y,
+            size_z,
+            triangle_count: mesh.triangles.len() as i64,
+            surface_area: mesh.surface_area(),
+            volume: mesh.volume(),
+            watertight: mesh.is_watertight(),
+        }
+    }
+}
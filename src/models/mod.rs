@@ -15,7 +15,22 @@
  * along with this program.  If not, see <http://www.gnu.org/licenses/>.
  */
 
+pub mod collection;
+pub mod duplicate_group;
 pub mod file;
 pub mod project;
 pub mod project_tag;
-pub mod project_source;
\ No newline at end of file
+pub mod gcode_info;
+pub mod material;
+pub mod mesh_info;
+pub mod print_job;
+pub mod printer_profile;
+pub mod project_filter;
+pub mod project_source;
+pub mod queue_item;
+pub mod saved_search;
+pub mod scan_settings;
+pub mod spool;
+pub mod status_change;
+pub mod tag_rule;
+pub mod thumbnail_cache_entry;
\ No newline at end of file
@@ -0,0 +1,127 @@
+/*
+ * Copyright (c) 2025-2026 Mitch Tuck
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::fmt::Display;
+use regex::Regex;
+use serde::{Serialize, Deserialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PrintOutcome {
+    Success,
+    Partial,
+    Failed,
+}
+
+impl PrintOutcome {
+    pub const ALL: [PrintOutcome; 3] = [PrintOutcome::Success, PrintOutcome::Partial, PrintOutcome::Failed];
+
+    /**
+     * How the outcome is stored in the database.
+     */
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            PrintOutcome::Success => "success",
+            PrintOutcome::Partial => "partial",
+            PrintOutcome::Failed => "failed",
+        }
+    }
+
+    pub fn parse(keyword: &str) -> Option<PrintOutcome> {
+        PrintOutcome::ALL.into_iter().find(|outcome| outcome.keyword() == keyword)
+    }
+}
+
+impl Display for PrintOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            PrintOutcome::Success => "Success",
+            PrintOutcome::Partial => "Partial",
+            PrintOutcome::Failed => "Failed",
+        })
+    }
+}
+
+/// One time a project file was printed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PrintJob {
+    pub id: i32,
+    pub project_file_id: i32,
+    /// Day of the print as YYYY-MM-DD.
+    pub printed: String,
+    pub printer: Option<String>,
+    pub material: Option<String>,
+    pub color: Option<String>,
+    /// Print time in seconds.
+    pub duration: Option<i64>,
+    pub outcome: PrintOutcome,
+    pub failure_reason: Option<String>,
+    pub notes: Option<String>,
+    /// Paths of photos of the print.
+    pub photos: Vec<String>,
+    /// Spool the filament came from.
+    pub spool_id: Option<i32>,
+    /// Grams of filament used, taken off the spool when the print is logged.
+    pub filament_used: Option<f64>,
+}
+
+impl PrintJob {
+    /**
+     * Whether a typed date looks like YYYY-MM-DD with a real month and day.
+     */
+    pub fn is_valid_date(date: &str) -> bool {
+        let regex = Regex::new(r"^(\d{4})-(\d{2})-(\d{2})$").unwrap();
+        let Some(captures) = regex.captures(date) else { return false };
+        let month: u32 = captures[2].parse().unwrap_or(0);
+        let day: u32 = captures[3].parse().unwrap_or(0);
+        (1..=12).contains(&month) && (1..=31).contains(&day)
+    }
+}
+
+/// What the project list can be narrowed to by print history.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrintHistory {
+    NeverPrinted,
+    Printed,
+    /// The most recent print of any of the project's files failed.
+    FailedLastTime,
+}
+
+impl PrintHistory {
+    pub const ALL: [PrintHistory; 3] = [PrintHistory::NeverPrinted, PrintHistory::Printed, PrintHistory::FailedLastTime];
+
+    /**
+     * Parse the value of a printed: filter.
+     */
+    pub fn parse(value: &str) -> Result<PrintHistory, String> {
+        match value.to_lowercase().as_str() {
+            "never" | "no" => Ok(PrintHistory::NeverPrinted),
+            "yes" => Ok(PrintHistory::Printed),
+            "failed" => Ok(PrintHistory::FailedLastTime),
+            _ => Err(format!("printed:{} should be printed:never, printed:yes or printed:failed", value)),
+        }
+    }
+}
+
+impl Display for PrintHistory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            PrintHistory::NeverPrinted => "Never printed",
+            PrintHistory::Printed => "Printed",
+            PrintHistory::FailedLastTime => "Failed last time",
+        })
+    }
+}
@@ -0,0 +1,62 @@
+/*
+ * Copyright (c) 2025-2026 Mitch Tuck
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use serde::{Serialize, Deserialize};
+use crate::models::mesh_info::MeshInfo;
+
+/// A printer and its build volume in millimeters.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PrinterProfile {
+    pub name: String,
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+impl PrinterProfile {
+    /**
+     * The build volume smallest side first.
+     */
+    pub fn sorted_volume(&self) -> [f64; 3] {
+        sorted([self.x, self.y, self.z])
+    }
+    /**
+     * True when the model fits in the build volume in any of its axis aligned orientations.
+     * Comparing both sets of sides smallest to largest covers every rotation.
+     */
+    pub fn fits(&self, mesh: &MeshInfo) -> bool {
+        let model = sorted([mesh.size_x, mesh.size_y, mesh.size_z]);
+        let volume = self.sorted_volume();
+        model.iter().zip(volume.iter()).all(|(model_side, volume_side)| model_side <= volume_side)
+    }
+}
+
+impl Default for PrinterProfile {
+    fn default() -> Self {
+        PrinterProfile {
+            name: "".to_string(),
+            x: 0.0,
+            y: 0.0,
+            z: 0.0,
+        }
+    }
+}
+
+fn sorted(mut sides: [f64; 3]) -> [f64; 3] {
+    sides.sort_by(|a, b| a.total_cmp(b));
+    sides
+}
@@ -16,11 +16,12 @@
  */
 use std::fs;
 use std::path::Path;
-use log::debug;
+use log::{debug, warn};
 use crate::models;
 use serde::{Serialize, Deserialize};
-use models::{file::ProjectFile, project_tag::ProjectTag, project_source::ProjectSource};
+use models::{file::ProjectFile, project_tag::ProjectTag, project_source::ProjectSource, printer_profile::PrinterProfile};
 use regex::Regex;
+use crate::ignore_file::{self, IgnoreFile};
 
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -32,25 +33,48 @@ pub struct Project {
     pub files: Vec<ProjectFile>,
     pub tags: Vec<ProjectTag>,
     pub sources: Vec<ProjectSource>,
+    /// The project directory no longer exists on disk.
+    pub missing: bool,
+    /// Where the project is in the workflow, one of the configured workflow states.
+    pub status: Option<String>,
 }
 
 impl Project {
+    /**
+     * Every file in the project directory, leaving out anything a `.3dmignore` in the project
+     * or a directory above it hides.
+     */
     pub fn get_file_system_files(&mut self) -> Vec<String> {
-        Project::scan_dir(self.path.clone())
+        Project::scan_dir(self.path.clone(), &IgnoreFile::load_ancestors(Path::new(&self.path)))
     }
-    fn scan_dir(dir: String) -> Vec<String> {
+    fn scan_dir(dir: String, ignore_files: &[IgnoreFile]) -> Vec<String> {
         let mut result  :Vec<String> = Vec::new();
         debug!("Scanning Directory: {}", dir);
-        for entry in fs::read_dir(Path::new(dir.as_str())).unwrap() {
-            let entry = entry.unwrap();
-            if entry.file_type().unwrap().is_dir() && !entry.path().to_str().unwrap().contains(".3DManager") {
-                let mut sub_result = Project::scan_dir(entry.path().to_str().unwrap().to_string());
+        let mut ignore_files = ignore_files.to_vec();
+        ignore_files.extend(IgnoreFile::load(Path::new(&dir)));
+        //the directory can go away mid scan, entries that can't be read or named are skipped
+        let Ok(entries) = fs::read_dir(Path::new(dir.as_str())) else {
+            warn!("Could not read directory {}", dir);
+            return result;
+        };
+        for entry in entries.flatten() {
+            if ignore_file::is_ignored(&ignore_files, &entry.path()) {
+                continue;
+            }
+            let Ok(file_type) = entry.file_type() else { continue };
+            let Some(path) = entry.path().to_str().map(str::to_string) else {
+                warn!("Skipping {}, its name is not valid UTF-8", entry.path().display());
+                continue;
+            };
+            if path.contains(".3DManager") {
+                continue;
+            }
+            if file_type.is_dir() {
+                let mut sub_result = Project::scan_dir(path, &ignore_files);
                 result.append(&mut sub_result);
                 debug!("Scanning Project directory {}. The Project Name is {}", entry.path().display(), entry.file_name().display());
             } else {
-                if !entry.path().to_str().unwrap().contains(".3DManager") {
-                    result.push(entry.path().to_str().unwrap().to_string());
-                }
+                result.push(path);
             }
         }
         result
@@ -60,15 +84,24 @@ impl Project {
 impl Project {
     pub fn get_default_or_first_image_file(&self) -> Option<ProjectFile> {
         let default_files :Vec<ProjectFile>= self.files.clone().into_iter().filter(|file| file.default).collect();
-        if default_files.len() > 0 {
+        if !default_files.is_empty() {
             return Some(default_files.first().unwrap().clone());
         }
-        let regex = Regex::new(r"((?i)\.png|\.jpg|\.jpeg|\.gif|\.stl|\.3mf)").unwrap();
+        let regex = Regex::new(r"((?i)\.png|\.jpg|\.jpeg|\.gif|\.stl|\.3mf|\.obj)").unwrap();
         let filtered_files :Vec<ProjectFile> = self.files.clone().into_iter().filter(|file| regex.is_match(file.path.as_str())).collect();
-        if filtered_files.len() > 0 {
+        if !filtered_files.is_empty() {
             return Some(filtered_files.first().unwrap().clone());
         }
-        None
+        //sliced files only have a picture if the slicer embedded one
+        self.files.iter().find(|file| file.gcode.as_ref().is_some_and(|gcode| gcode.has_thumbnail)).cloned()
+    }
+    /**
+     * Whether every measured model in the project fits the printer, None when nothing has been measured.
+     */
+    pub fn fits_printer(&self, printer: &PrinterProfile) -> Option<bool> {
+        let mut measured = self.files.iter().filter_map(|file| file.mesh.as_ref()).peekable();
+        measured.peek()?;
+        Some(measured.all(|mesh| printer.fits(mesh)))
     }
 }
 impl Default for Project {
@@ -81,6 +114,8 @@ impl Default for Project {
             files: vec![],
             tags: vec![],
             sources: vec![],
+            missing: false,
+            status: None,
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,70 @@
+/*
+ * Copyright (c) 2025-2026 Mitch Tuck
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use regex::Regex;
+use crate::models::print_job::PrintHistory;
+use crate::models::printer_profile::PrinterProfile;
+use crate::models::project_tag::ProjectTag;
+use crate::search::query::{SearchQuery, Sort};
+
+/// Bed size to check models against, without a height only the footprint is compared.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BedSize {
+    pub x: f64,
+    pub y: f64,
+    pub z: Option<f64>,
+}
+
+impl BedSize {
+    /**
+     * Parse "220x220" or "220x220x250", `×` works as well as `x`.
+     */
+    pub fn parse(value: &str) -> Option<BedSize> {
+        let regex = Regex::new(r"^\s*(\d+(?:\.\d+)?)\s*[xX×]\s*(\d+(?:\.\d+)?)(?:\s*[xX×]\s*(\d+(?:\.\d+)?))?\s*$").unwrap();
+        let captures = regex.captures(value)?;
+        Some(BedSize {
+            x: captures[1].parse().ok()?,
+            y: captures[2].parse().ok()?,
+            z: captures.get(3).and_then(|height| height.as_str().parse().ok()),
+        })
+    }
+}
+
+/// Everything the project list can be narrowed by, unset fields don't filter.
+#[derive(Debug, Clone, Default)]
+pub struct ProjectFilter {
+    /// A parsed search box query, words are found in the full text index and ranked by relevance.
+    pub query: Option<SearchQuery>,
+    /// Order used when the query doesn't have a sort: of its own, best match first when neither is set.
+    pub sort: Option<Sort>,
+    /// Exact project directory.
+    pub path: Option<String>,
+    /// Projects must have all of these tags.
+    pub tags: Option<Vec<ProjectTag>>,
+    /// Every measured model in the project fits on this bed, turning it on the bed is allowed.
+    pub fits_bed: Option<BedSize>,
+    /// Every measured model in the project fits in this printer in some orientation.
+    pub fits_printer: Option<PrinterProfile>,
+    /// Only projects whose directory is, or isn't, missing.
+    pub missing: Option<bool>,
+    /// Only projects in this collection, in the collection's order.
+    pub collection: Option<i32>,
+    /// Whether the project's files have been printed and how the last print went.
+    pub print_history: Option<PrintHistory>,
+    /// Only projects in this workflow state, an empty status matches projects without one.
+    pub status: Option<String>,
+}
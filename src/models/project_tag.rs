@@ -15,12 +15,17 @@
  * along with this program.  If not, see <http://www.gnu.org/licenses/>.
  */
 use std::fmt::Display;
+use iced::Color;
 use serde::{Serialize, Deserialize};
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct ProjectTag {
     pub id: i32,
     pub tag: String,
+    /// Hex colour like "#3a7bd5", tags without one use the theme colour.
+    pub color: Option<String>,
+    /// Broader tag this one sits under, filtering on the parent matches this tag too.
+    pub parent_id: Option<i32>,
 }
 
 impl Display for ProjectTag {
@@ -29,4 +34,133 @@ impl Display for ProjectTag {
     }
 }
 impl ProjectTag {
-}
\ No newline at end of file
+    /// Separates the levels when a tag is typed as a path, "Games > Warhammer > Terrain".
+    pub const PATH_SEPARATOR: char = '>';
+
+    pub fn color(&self) -> Option<Color> {
+        self.color.as_ref().and_then(|color| color.parse().ok())
+    }
+
+    /**
+     * Tidy a typed tag name, surrounding space is dropped and runs of spaces become one.
+     */
+    pub fn normalize(name: &str) -> String {
+        name.split_whitespace().collect::<Vec<&str>>().join(" ")
+    }
+
+    /**
+     * The levels of a typed tag path, normalized and without empty levels.
+     */
+    pub fn split_path(path: &str) -> Vec<String> {
+        path.split(Self::PATH_SEPARATOR).map(Self::normalize).filter(|name| !name.is_empty()).collect()
+    }
+
+    /**
+     * Order tags as a tree, each parent followed by its children, paired with their depth.
+     * Tags whose parent isn't in the list are treated as top level, as are tags caught in a
+     * loop of parents so they never drop out of the list.
+     */
+    pub fn tree_order(tags: &[ProjectTag]) -> Vec<(ProjectTag, usize)> {
+        let mut ordered = vec![];
+        let is_root = |tag: &ProjectTag| tag.parent_id.is_none_or(|parent_id| !tags.iter().any(|other| other.id == parent_id));
+        for root in tags.iter().filter(|tag| is_root(tag)) {
+            Self::push_subtree(tags, root, 0, &mut ordered);
+        }
+        for tag in tags.iter() {
+            Self::push_subtree(tags, tag, 0, &mut ordered);
+        }
+        ordered
+    }
+
+    fn push_subtree(tags: &[ProjectTag], tag: &ProjectTag, depth: usize, ordered: &mut Vec<(ProjectTag, usize)>) {
+        //a loop in the parents would otherwise recurse forever
+        if ordered.iter().any(|(added, _)| added.id == tag.id) {
+            return;
+        }
+        ordered.push((tag.clone(), depth));
+        for child in tags.iter().filter(|child| child.parent_id == Some(tag.id)) {
+            Self::push_subtree(tags, child, depth + 1, ordered);
+        }
+    }
+
+    /**
+     * Every tag below this one in the list, at any depth.
+     */
+    pub fn descendant_ids(&self, tags: &[ProjectTag]) -> Vec<i32> {
+        let mut ids = vec![];
+        let mut parents = vec![self.id];
+        while let Some(parent_id) = parents.pop() {
+            for child in tags.iter().filter(|child| child.parent_id == Some(parent_id)) {
+                if child.id != self.id && !ids.contains(&child.id) {
+                    ids.push(child.id);
+                    parents.push(child.id);
+                }
+            }
+        }
+        ids
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tag(id: i32, name: &str, parent_id: Option<i32>) -> ProjectTag {
+        ProjectTag { id, tag: name.to_string(), color: None, parent_id }
+    }
+
+    fn order(tags: &[ProjectTag]) -> Vec<(i32, usize)> {
+        ProjectTag::tree_order(tags).into_iter().map(|(tag, depth)| (tag.id, depth)).collect()
+    }
+
+    #[test]
+    fn normalize_collapses_spaces() {
+        assert_eq!(ProjectTag::normalize("  Warhammer   40k "), "Warhammer 40k");
+        assert_eq!(ProjectTag::normalize("   "), "");
+    }
+
+    #[test]
+    fn split_path_normalizes_levels() {
+        assert_eq!(ProjectTag::split_path("Games >  Warhammer>Terrain "), vec!["Games", "Warhammer", "Terrain"]);
+        assert_eq!(ProjectTag::split_path("> Games >> "), vec!["Games"]);
+        assert!(ProjectTag::split_path(" > ").is_empty());
+    }
+
+    #[test]
+    fn tree_order_puts_children_after_parents() {
+        let tags = [tag(3, "Terrain", Some(2)), tag(1, "Games", None), tag(2, "Warhammer", Some(1)), tag(4, "Tools", None)];
+        assert_eq!(order(&tags), vec![(1, 0), (2, 1), (3, 2), (4, 0)]);
+    }
+
+    #[test]
+    fn tree_order_treats_orphans_as_top_level() {
+        let tags = [tag(2, "Warhammer", Some(99)), tag(3, "Terrain", Some(2))];
+        assert_eq!(order(&tags), vec![(2, 0), (3, 1)]);
+    }
+
+    #[test]
+    fn tree_order_keeps_tags_in_loops() {
+        let tags = [tag(1, "Games", None), tag(2, "Self", Some(2)), tag(3, "A", Some(4)), tag(4, "B", Some(3))];
+        let ordered = order(&tags);
+        assert_eq!(ordered.len(), 4);
+        assert_eq!(ordered[0], (1, 0));
+        assert!(ordered.contains(&(2, 0)));
+        assert!(ordered.contains(&(3, 0)) || ordered.contains(&(4, 0)));
+    }
+
+    #[test]
+    fn descendant_ids_finds_every_level() {
+        let tags = [tag(1, "Games", None), tag(2, "Warhammer", Some(1)), tag(3, "Terrain", Some(2)), tag(4, "Tools", None)];
+        let mut descendants = tags[0].descendant_ids(&tags);
+        descendants.sort();
+        assert_eq!(descendants, vec![2, 3]);
+        assert!(tags[3].descendant_ids(&tags).is_empty());
+    }
+
+    #[test]
+    fn descendant_ids_stops_at_loops() {
+        let tags = [tag(1, "A", Some(2)), tag(2, "B", Some(1)), tag(3, "Self", Some(3))];
+        assert_eq!(tags[0].descendant_ids(&tags), vec![2]);
+        assert!(tags[2].descendant_ids(&tags).is_empty());
+    }
+}
@@ -0,0 +1,101 @@
+/*
+ * Copyright (c) 2025-2026 Mitch Tuck
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::fmt::Display;
+use serde::{Serialize, Deserialize};
+use crate::models::file::ProjectFile;
+use crate::models::printer_profile::PrinterProfile;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum QueuePriority {
+    High,
+    Normal,
+    Low,
+}
+
+impl QueuePriority {
+    pub const ALL: [QueuePriority; 3] = [QueuePriority::High, QueuePriority::Normal, QueuePriority::Low];
+
+    /**
+     * How the priority is stored in the database, higher comes first.
+     */
+    pub fn value(&self) -> i32 {
+        match self {
+            QueuePriority::High => 1,
+            QueuePriority::Normal => 0,
+            QueuePriority::Low => -1,
+        }
+    }
+
+    pub fn from_value(value: i32) -> QueuePriority {
+        match value {
+            value if value > 0 => QueuePriority::High,
+            0 => QueuePriority::Normal,
+            _ => QueuePriority::Low,
+        }
+    }
+}
+
+impl Display for QueuePriority {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", match self {
+            QueuePriority::High => "High",
+            QueuePriority::Normal => "Normal",
+            QueuePriority::Low => "Low",
+        })
+    }
+}
+
+/// A file waiting to be printed some number of times.
+#[derive(Debug, Clone)]
+pub struct QueueItem {
+    pub id: i32,
+    pub file: ProjectFile,
+    pub project_name: String,
+    /// How many copies are wanted.
+    pub quantity: i32,
+    /// How many copies have been printed so far.
+    pub printed: i32,
+    pub priority: QueuePriority,
+    /// Printer the item is meant for, any printer it fits when not set.
+    pub printer: Option<String>,
+}
+
+impl QueueItem {
+    pub fn remaining(&self) -> i32 {
+        (self.quantity - self.printed).max(0)
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.printed >= self.quantity
+    }
+
+    pub fn progress(&self) -> f32 {
+        if self.quantity <= 0 { 1.0 } else { (self.printed as f32 / self.quantity as f32).min(1.0) }
+    }
+
+    /**
+     * Whether the item belongs on the printer's queue, it's either meant for it or for no
+     * printer in particular and fits on it.  Files that haven't been measured are assumed to fit.
+     */
+    pub fn is_for_printer(&self, printer: &PrinterProfile) -> bool {
+        match &self.printer {
+            Some(name) => *name == printer.name,
+            None => self.file.mesh.as_ref().is_none_or(|mesh| printer.fits(mesh)),
+        }
+    }
+}
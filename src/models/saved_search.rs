@@ -0,0 +1,32 @@
+/*
+ * Copyright (c) 2025-2026 Mitch Tuck
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use crate::models::project_tag::ProjectTag;
+use crate::search::query::Sort;
+
+/// A named search shown in the side panel, applying it restores the search text, tags and sort.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SavedSearch {
+    pub id: i32,
+    pub name: String,
+    /// Search box text in the query syntax.
+    pub query: String,
+    pub tags: Vec<ProjectTag>,
+    pub sort: Sort,
+    /// Pinned searches are listed first.
+    pub pinned: bool,
+}
@@ -0,0 +1,171 @@
+/*
+ * Copyright (c) 2025-2026 Mitch Tuck
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use log::{debug, error};
+use serde::{Serialize, Deserialize};
+use crate::ignore_file::{self, IgnoreFile};
+use crate::mesh::Mesh;
+
+/// How far below a print path marker files and model directories are looked for.
+const MAX_SEARCH_DEPTH: usize = 10;
+
+/// How projects are found in a print path.  A directory is a project when it's `depth` levels
+/// below the print path, holds one of the marker files or, with `model_dirs`, holds model files.
+/// Projects aren't searched for projects nested inside them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScanSettings {
+    pub print_path: String,
+    /// 1 for `print path/project`, 3 for `print path/category/designer/project`,
+    /// 0 to only use the marker files and model directories.
+    pub depth: usize,
+    /// A directory with model files directly inside it is a project.
+    pub model_dirs: bool,
+    /// File names marking their directory as a project, e.g. `.3dproject`.
+    pub marker_files: Vec<String>,
+}
+
+impl Default for ScanSettings {
+    fn default() -> Self {
+        ScanSettings {
+            print_path: "".to_string(),
+            depth: 1,
+            model_dirs: false,
+            marker_files: vec![],
+        }
+    }
+}
+
+impl ScanSettings {
+    pub fn new(print_path: &str) -> ScanSettings {
+        ScanSettings { print_path: print_path.to_string(), ..Default::default() }
+    }
+
+    /**
+     * What is wrong with these settings, None when they look fine.  Projects aren't searched for
+     * projects inside them, so with a depth the marker files and model directories can only find
+     * projects above that depth.
+     */
+    pub fn problem(&self) -> Option<String> {
+        if self.finds_nothing() {
+            return Some("Depth 0 finds nothing without marker files or model directories".to_string());
+        }
+        if self.uses_contents() && self.depth > 0 {
+            return Some(format!(
+                "Marker files and model directories only find projects less than {} levels down, use depth 0 to find them at any depth",
+                self.depth
+            ));
+        }
+        None
+    }
+
+    /**
+     * Whether these settings can't find any project.
+     */
+    pub fn finds_nothing(&self) -> bool {
+        self.search_depth() == 0
+    }
+
+    /**
+     * Whether projects are also found by the marker files or model directories.
+     */
+    pub fn uses_contents(&self) -> bool {
+        self.model_dirs || !self.marker_files.is_empty()
+    }
+
+    /**
+     * Every project directory in the print path, skipping anything a `.3dmignore` hides.
+     */
+    pub fn project_dirs(&self) -> Vec<PathBuf> {
+        let print_path = Path::new(&self.print_path);
+        if let Err(e) = fs::read_dir(print_path) {
+            error!("Could not read print path {}: {}", self.print_path, e);
+            return vec![];
+        }
+        let mut project_dirs = vec![];
+        self.find_project_dirs(print_path, 0, &IgnoreFile::load_ancestors(print_path), &mut project_dirs);
+        project_dirs
+    }
+
+    fn find_project_dirs(&self, dir: &Path, level: usize, ignore_files: &[IgnoreFile], project_dirs: &mut Vec<PathBuf>) {
+        if level > 0 && self.is_project_dir(dir, level) {
+            project_dirs.push(dir.to_path_buf());
+            return;
+        }
+        if level >= self.search_depth() {
+            return;
+        }
+        let Ok(entries) = fs::read_dir(dir) else {
+            debug!("Could not read directory {}", dir.display());
+            return;
+        };
+        let mut ignore_files = ignore_files.to_vec();
+        ignore_files.extend(IgnoreFile::load(dir));
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if entry.file_type().is_ok_and(|file_type| file_type.is_dir())
+                && entry.file_name() != ".3DManager"
+                && !ignore_file::is_ignored(&ignore_files, &path) {
+                self.find_project_dirs(&path, level + 1, &ignore_files, project_dirs);
+            }
+        }
+    }
+
+    /**
+     * The project directory holding a path in this print path, the first directory on the way
+     * down that is a project.  None when the path isn't in a project or is ignored.
+     */
+    pub fn project_dir_for(&self, path: &Path) -> Option<PathBuf> {
+        let relative = path.strip_prefix(&self.print_path).ok()?;
+        let mut dir = PathBuf::from(&self.print_path);
+        let mut ignore_files = IgnoreFile::load_ancestors(&dir);
+        ignore_files.extend(IgnoreFile::load(&dir));
+        for (index, component) in relative.components().enumerate().take(self.search_depth()) {
+            dir.push(component);
+            if ignore_file::is_ignored(&ignore_files, &dir) || !dir.is_dir() {
+                return None;
+            }
+            if self.is_project_dir(&dir, index + 1) {
+                return Some(dir);
+            }
+            ignore_files.extend(IgnoreFile::load(&dir));
+        }
+        None
+    }
+
+    fn is_project_dir(&self, dir: &Path, level: usize) -> bool {
+        if level == self.depth || self.marker_files.iter().any(|marker| dir.join(marker).is_file()) {
+            return true;
+        }
+        self.model_dirs && fs::read_dir(dir).is_ok_and(|entries| entries.flatten().any(|entry| {
+            entry.file_type().is_ok_and(|file_type| file_type.is_file()) && Mesh::is_model_file(entry.path().to_str().unwrap_or(""))
+        }))
+    }
+
+    /**
+     * Only the fixed depth is searched unless projects can also be found by their contents.
+     */
+    fn search_depth(&self) -> usize {
+        if self.uses_contents() {
+            MAX_SEARCH_DEPTH.max(self.depth)
+        } else {
+            self.depth
+        }
+    }
+}
@@ -0,0 +1,52 @@
+/*
+ * Copyright (c) 2025-2026 Mitch Tuck
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::fmt::Display;
+use serde::{Serialize, Deserialize};
+
+/// A reel of filament, weights are grams of filament without the spool itself.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Spool {
+    pub id: i32,
+    pub brand: String,
+    pub material: String,
+    pub color: String,
+    /// Filament diameter in mm.
+    pub diameter: f64,
+    pub initial_weight: f64,
+    pub remaining_weight: f64,
+    /// What the whole spool cost.
+    pub price: Option<f64>,
+}
+
+impl Spool {
+    /**
+     * Fraction of the spool left, 0 to 1.
+     */
+    pub fn remaining_fraction(&self) -> f32 {
+        if self.initial_weight <= 0.0 {
+            return 0.0;
+        }
+        (self.remaining_weight / self.initial_weight).clamp(0.0, 1.0) as f32
+    }
+}
+
+impl Display for Spool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} {} ({:.0} g left)", self.brand, self.material, self.color, self.remaining_weight)
+    }
+}
@@ -0,0 +1,91 @@
+/*
+ * Copyright (c) 2025-2026 Mitch Tuck
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::fmt::Display;
+use std::path::Path;
+use regex::{Regex, RegexBuilder};
+use serde::{Serialize, Deserialize};
+use crate::models::project_tag::ProjectTag;
+
+/// What part of a project a tag rule's pattern is matched against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RuleTarget {
+    /// The full path of the project directory.
+    Directory,
+    /// The name of any file in the project.
+    FileName,
+    /// The extension of any file in the project, without the dot.
+    Extension,
+}
+
+impl RuleTarget {
+    pub const ALL: [RuleTarget; 3] = [RuleTarget::Directory, RuleTarget::FileName, RuleTarget::Extension];
+}
+
+impl Display for RuleTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            RuleTarget::Directory => "Directory",
+            RuleTarget::FileName => "File name",
+            RuleTarget::Extension => "Extension",
+        })
+    }
+}
+
+/// Tags given to projects whose directory or files match a pattern when they're scanned.
+/// Patterns are case insensitive regular expressions and tags can use the pattern's groups,
+/// `/Minis/(\w+)/` with the tag `Minis > $1` files dwarves under Minis.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TagRule {
+    pub target: RuleTarget,
+    pub pattern: String,
+    pub tags: Vec<String>,
+}
+
+impl TagRule {
+    pub fn regex(&self) -> Result<Regex, regex::Error> {
+        RegexBuilder::new(&self.pattern).case_insensitive(true).build()
+    }
+
+    /**
+     * The tags this rule gives a project directory holding these files, empty when nothing matches.
+     * Takes the rule's compiled pattern so it is compiled once rather than for every project.
+     */
+    pub fn tags_for(&self, regex: &Regex, project_path: &str, files: &[String]) -> Vec<String> {
+        let subjects: Vec<&str> = match self.target {
+            RuleTarget::Directory => vec![project_path],
+            RuleTarget::FileName => files.iter()
+                .filter_map(|file| Path::new(file).file_name().and_then(|name| name.to_str()))
+                .collect(),
+            RuleTarget::Extension => files.iter()
+                .filter_map(|file| Path::new(file).extension().and_then(|extension| extension.to_str()))
+                .collect(),
+        };
+        let mut tags = vec![];
+        for captures in subjects.iter().filter_map(|subject| regex.captures(subject)) {
+            for template in self.tags.iter() {
+                let mut tag = String::new();
+                captures.expand(template, &mut tag);
+                let tag = ProjectTag::split_path(&tag).join(" > ");
+                if !tag.is_empty() && !tags.contains(&tag) {
+                    tags.push(tag);
+                }
+            }
+        }
+        tags
+    }
+}
@@ -0,0 +1,269 @@
+/*
+ * Copyright (c) 2025-2026 Mitch Tuck
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::fs;
+use iced::{Element, Length, Task};
+use iced::alignment::Vertical;
+use iced::widget::{button, column, container, radio, row, scrollable, text, Column, Container};
+use iced_dialog::dialog;
+use log::{error, info};
+use crate::ThreeDManager;
+use crate::config::Config;
+use crate::db_manager::DbManager;
+use crate::file_hash;
+use crate::models::duplicate_group::{self, DuplicateGroup, LinkKind};
+use crate::models::file::ProjectFile;
+use crate::scanner::Scanner;
+
+/// Something done to the other copies in a group, waiting for the user to confirm it.
+#[derive(Debug, Clone)]
+enum DuplicateAction {
+    DeleteOthers(String),
+    Link(String, LinkKind),
+}
+
+pub struct DuplicatesPage {
+    config: Config,
+    db_manager: DbManager,
+    /// Each group with the space its extra copies take up.
+    groups: Vec<(DuplicateGroup, u64)>,
+    /// The file kept for each group by hash, the first file until another is picked.
+    keep: HashMap<String, i32>,
+    confirm: Option<DuplicateAction>,
+    status: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    BackToMain,
+    Keep(String, i32),
+    DeleteOthers(String),
+    Link(String, LinkKind),
+    RelateProjects(String),
+    Confirm,
+    Cancel,
+    /// The projects of the replaced files were synced.
+    Synced(bool),
+}
+
+impl DuplicatesPage {
+    pub fn new(config: Config) -> DuplicatesPage {
+        let mut duplicates_page = DuplicatesPage {
+            config,
+            db_manager: ThreeDManager::setup_db_connection(),
+            groups: vec![],
+            keep: HashMap::new(),
+            confirm: None,
+            status: None,
+        };
+        duplicates_page.load_groups();
+        duplicates_page
+    }
+
+    pub fn update(&mut self, message: Message) -> Task<Message> {
+        match message {
+            Message::BackToMain => {} //handled in main update
+            Message::Keep(hash, file_id) => {
+                self.keep.insert(hash, file_id);
+            }
+            Message::DeleteOthers(hash) => {
+                self.confirm = Some(DuplicateAction::DeleteOthers(hash));
+            }
+            Message::Link(hash, kind) => {
+                self.confirm = Some(DuplicateAction::Link(hash, kind));
+            }
+            Message::RelateProjects(hash) => {
+                if let Some(group) = self.group(&hash) {
+                    let project_ids = group.project_ids();
+                    self.db_manager.relate_projects(&project_ids);
+                    self.status = Some(format!("Marked {} projects as related.", project_ids.len()));
+                }
+            }
+            Message::Confirm => {
+                let (status, changed) = match self.confirm.take() {
+                    Some(DuplicateAction::DeleteOthers(hash)) => self.replace_others(&hash, None),
+                    Some(DuplicateAction::Link(hash, kind)) => self.replace_others(&hash, Some(kind)),
+                    None => return Task::none(),
+                };
+                self.status = Some(status);
+                if !changed.is_empty() {
+                    return Scanner::sync_paths_in_background(self.config.clone(), changed).map(Message::Synced);
+                }
+            }
+            Message::Cancel => {
+                self.confirm = None;
+            }
+            Message::Synced(_) => {
+                self.load_groups();
+            }
+        }
+        Task::none()
+    }
+
+    /**
+     * Groups that still waste space, copies already linked to each other are left out.
+     */
+    fn load_groups(&mut self) {
+        self.groups = self.db_manager.get_duplicate_files().into_iter()
+            .map(|group| {
+                let wasted = group.wasted_bytes();
+                (group, wasted)
+            })
+            .filter(|(_, wasted)| *wasted > 0)
+            .collect();
+    }
+
+    fn group(&self, hash: &str) -> Option<&DuplicateGroup> {
+        self.groups.iter().find(|(group, _)| group.hash == hash).map(|(group, _)| group)
+    }
+
+    /**
+     * The file picked to keep, by default the first one that isn't a symlink.
+     */
+    fn kept_file(&self, group: &DuplicateGroup) -> Option<ProjectFile> {
+        match self.keep.get(&group.hash) {
+            Some(keep) => group.files.iter().find(|(file, _)| file.id == *keep),
+            None => group.files.iter().find(|(file, _)| !duplicate_group::is_symlink(Path::new(&file.path))),
+        }.map(|(file, _)| file.clone())
+    }
+
+    /**
+     * Delete the other copies or swap them for links to the kept one, returns what happened and
+     * the paths changed.  Every file is hashed again first, the hashes from the last scan may
+     * be out of date and a copy that has been edited since is left alone.
+     */
+    fn replace_others(&mut self, hash: &str, link: Option<LinkKind>) -> (String, Vec<PathBuf>) {
+        let Some(group) = self.group(hash).cloned() else { return ("".to_string(), vec![]) };
+        let Some(keep) = self.kept_file(&group) else { return ("".to_string(), vec![]) };
+        let keep_path = Path::new(&keep.path);
+        //deleting the target of a symlink or linking it back to itself would lose the model
+        if duplicate_group::is_symlink(keep_path) {
+            return ("The copy to keep is a symlink, pick the file it points to instead.".to_string(), vec![]);
+        }
+        if !file_hash::content_hash(&keep.path).is_ok_and(|current| current == group.hash) {
+            return (format!("{} has changed since the last scan, rescan before removing duplicates.", keep.path), vec![]);
+        }
+        let mut changed: Vec<PathBuf> = vec![];
+        let mut failed = vec![];
+        for (file, _) in group.files.iter().filter(|(file, _)| file.id != keep.id) {
+            let path = Path::new(&file.path);
+            if duplicate_group::is_same_file(keep_path, path) {
+                continue;
+            }
+            if !file_hash::content_hash(&file.path).is_ok_and(|current| current == group.hash) {
+                failed.push(format!("{}: changed since the last scan", file.path));
+                continue;
+            }
+            let result = match link {
+                Some(kind) => duplicate_group::replace_with_link(Path::new(&keep.path), path, kind),
+                None => fs::remove_file(path),
+            };
+            match result {
+                Ok(()) => changed.push(path.to_path_buf()),
+                Err(e) => {
+                    error!("Could not replace {}: {}", file.path, e);
+                    failed.push(format!("{}: {}", file.path, e));
+                }
+            }
+        }
+        info!("Replaced {} copies of {} with {:?}", changed.len(), keep.path, link);
+        let done = match link {
+            Some(kind) => format!("Replaced {} copies with {}.", changed.len(), kind),
+            None => format!("Deleted {} copies.", changed.len()),
+        };
+        let status = if failed.is_empty() { done } else { format!("{} Skipped: {}", done, failed.join(", ")) };
+        (status, changed)
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let total: u64 = self.groups.iter().map(|(_, wasted)| wasted).sum();
+        let header = row![
+            text("Duplicates").size(50).width(Length::Fill),
+            text!("{} wasted", format_size(total)),
+            button(text("Back")).style(ThreeDManager::rounded_button).on_press(Message::BackToMain),
+        ].spacing(10).align_y(Vertical::Center);
+
+        let mut group_list = column![].width(Length::Fill).spacing(20);
+        for (group, wasted) in self.groups.iter() {
+            group_list = group_list.push(self.group_view(group, *wasted));
+        }
+        if self.groups.is_empty() {
+            group_list = group_list.push(text("No model is stored in more than one project."));
+        }
+
+        let main_content = column![header]
+            .push(self.status.as_ref().map(|status| text(status.clone())))
+            .push(scrollable(group_list).height(Length::Fill))
+            .spacing(10);
+
+        let confirm_content = match &self.confirm {
+            Some(DuplicateAction::DeleteOthers(hash)) => format!("Delete {} from disk and keep only the selected copy?", self.other_copies(hash)),
+            Some(DuplicateAction::Link(hash, kind)) => format!("Replace {} with {} to the selected copy? Editing any of them will change them all.", self.other_copies(hash), kind),
+            None => "".to_string(),
+        };
+        dialog(self.confirm.is_some(), Container::new(main_content).width(Length::Fill).height(Length::Fill), text(confirm_content))
+            .title("Remove Duplicates")
+            .push_button(iced_dialog::button("Cancel", Message::Cancel))
+            .push_button(iced_dialog::button("OK", Message::Confirm))
+            .width(400)
+            .height(234)
+            .into()
+    }
+
+    fn group_view(&self, group: &DuplicateGroup, wasted: u64) -> Column<'_, Message> {
+        let name = group.files.first()
+            .and_then(|(file, _)| Path::new(&file.path).file_name())
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let keep = self.kept_file(group).map(|file| file.id);
+        let mut files = column![].spacing(5);
+        for (file, project_name) in group.files.iter() {
+            let hash = group.hash.clone();
+            files = files.push(
+                radio(format!("{}  ({})", project_name, file.path), file.id, keep, move |file_id| Message::Keep(hash.clone(), file_id))
+            );
+        }
+        column![
+            row![
+                text(name).size(24).width(Length::Fill),
+                text!("{} copies of {}, {} wasted", group.files.len(), format_size(group.size as u64), format_size(wasted)),
+            ].spacing(10).align_y(Vertical::Center),
+            container(files).padding([0, 20]),
+            row![
+                button(text("Delete Other Copies")).style(button::danger).on_press(Message::DeleteOthers(group.hash.clone())),
+                button(text("Replace With Symlinks")).on_press(Message::Link(group.hash.clone(), LinkKind::Symlink)),
+                button(text("Replace With Hardlinks")).on_press(Message::Link(group.hash.clone(), LinkKind::Hardlink)),
+                button(text("Mark Projects Related")).style(ThreeDManager::rounded_button).on_press(Message::RelateProjects(group.hash.clone())),
+            ].spacing(10),
+        ].spacing(5)
+    }
+
+    fn other_copies(&self, hash: &str) -> String {
+        let count = self.group(hash).map(|group| group.files.len().saturating_sub(1)).unwrap_or(0);
+        if count == 1 { "the other copy".to_string() } else { format!("the other {} copies", count) }
+    }
+}
+
+fn format_size(bytes: u64) -> String {
+    let mb = bytes as f64 / (1024.0 * 1024.0);
+    if mb >= 1024.0 {
+        format!("{:.1} GB", mb / 1024.0)
+    } else {
+        format!("{:.1} MB", mb)
+    }
+}
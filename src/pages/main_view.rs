@@ -14,71 +14,228 @@
  * You should have received a copy of the GNU General Public License
  * along with this program.  If not, see <http://www.gnu.org/licenses/>.
  */
-use std::fs;
-use std::path::Path;
-use iced::{Background, Fill, Length, Element};
-use iced::widget::{button, text, container, Container, row, column, text_input, Text, scrollable, image};
-use iced::alignment::{Horizontal};
+use std::collections::HashMap;
+use iced::{mouse, Background, Border, Fill, Font, Length, Element, Task, Theme};
+use iced::font::Weight;
+use iced::widget::{button, text, container, Container, Column, row, column, text_input, Text, scrollable, image, pick_list, rich_text, span, mouse_area, Space};
+use iced::alignment::{Horizontal, Vertical};
 use iced::widget::text::Alignment;
 use iced_dialog::dialog;
-use log::{debug, error, info};
+use rfd::FileDialog;
+use log::{error, info};
 use crate::{ThreeDManager};
 use crate::config::Config;
 use crate::db_manager::DbManager;
+use crate::models::collection::Collection;
+use crate::models::print_job::PrintHistory;
 use crate::models::project::Project;
+use crate::models::project_filter::{BedSize, ProjectFilter};
 use crate::models::project_tag::ProjectTag;
+use crate::models::saved_search::SavedSearch;
+use crate::models::spool::Spool;
+use crate::scanner::{RescanSummary, Scanner};
+use crate::search::{highlight_parts, SearchSnippet};
+use crate::search::query::{SearchQuery, Sort, SortKey};
+use crate::thumbnail_cache::ThumbnailCache;
+use crate::thumbnail_queue::{ThumbnailQueue, ThumbnailResult};
 
 pub struct MainView {
     config: Config,
     db_manager: DbManager,
     project_list: Vec<Project>,
-    name_filter: String,
+    search_text: String,
+    /// Where each listed project matched the search text.
+    search_snippets: HashMap<i32, SearchSnippet>,
+    /// Why the search text couldn't be used, the previous results stay listed meanwhile.
+    search_error: Option<String>,
+    /// Order of the results when the search text has no sort: filter.
+    sort: Sort,
+    saved_searches: Vec<SavedSearch>,
+    /// Number of projects each saved search matches, absent when its query no longer parses.
+    saved_search_counts: HashMap<i32, usize>,
+    /// Name for saving the current search.
+    saved_search_name: String,
+    /// Saved search being renamed and the new name typed so far.
+    renaming_search: Option<(i32, String)>,
+    collections: Vec<Collection>,
+    /// Name for a new collection.
+    collection_name: String,
+    /// Collection shown instead of the project grid.
+    open_collection: Option<Collection>,
+    /// Name of the open collection as edited in its header.
+    open_collection_name: String,
+    collection_projects: Vec<Project>,
+    /// Index of the collection project being dragged and the index it would be dropped at.
+    dragging: Option<(usize, usize)>,
+    /// Collection waiting for the user to confirm it should be deleted.
+    confirm_delete_collection: Option<Collection>,
+    /// Bed size as typed, e.g. "220x220" or "220x220x250".
+    bed_filter: String,
+    /// Name of the printer every model must fit on.
+    printer_filter: Option<String>,
+    print_history_filter: Option<PrintHistory>,
     tag_list: Vec<ProjectTag>,
     filter_tags: Vec<ProjectTag>,
-    stl_thumb: String,
+    /// Model file path to thumbnail path, empty when no thumbnail could be made.
+    thumbnails: HashMap<String, String>,
+    thumbnail_queue: ThumbnailQueue,
+    /// Looked up before queueing a thumbnail, shared so listing projects opens one connection.
+    thumbnail_cache: ThumbnailCache,
+    /// Projects whose directory has disappeared.
+    missing_projects: Vec<Project>,
+    show_missing: bool,
+    scanning: bool,
+    rescanning: bool,
+    rescan_summary: Option<RescanSummary>,
+    /// Missing project waiting for the user to confirm it should be purged.
+    confirm_purge: Option<Project>,
+    /// Spools running out of filament.
+    low_spools: Vec<Spool>,
+    /// Show the projects as a board with a column for each workflow state instead of a grid.
+    show_board: bool,
 }
 
 #[derive(Debug, Clone)]
 pub enum Message {
-    CloseApplication,
     ToSettingsPage,
+    ToTagsPage,
+    ToDuplicatesPage,
+    ToSpoolsPage,
+    ToPrintQueuePage,
     ScanProjectDirs,
+    ScanFinished,
     FilterChanged(String),
+    BedFilterChanged(String),
+    PrinterFilterChanged(Option<String>),
+    PrintHistoryFilterChanged(Option<PrintHistory>),
     FilterTagToggle(ProjectTag),
+    SortChanged(SortKey),
+    SavedSearchNameChanged(String),
+    SaveSearch,
+    ApplySavedSearch(i32),
+    TogglePinSavedSearch(i32),
+    StartRenameSavedSearch(i32),
+    RenameSavedSearchChanged(String),
+    FinishRenameSavedSearch,
+    CancelRenameSavedSearch,
+    DeleteSavedSearch(i32),
+    CollectionNameChanged(String),
+    CreateCollection,
+    OpenCollection(i32),
+    CloseCollection,
+    OpenCollectionNameChanged(String),
+    RenameCollection,
+    DeleteCollection,
+    ConfirmDeleteCollection,
+    CancelDeleteCollection,
+    RemoveFromCollection(i32),
+    StartDrag(usize),
+    DragOver(usize),
+    Drop,
+    CancelDrag,
     SelectProject(Project),
+    ThumbnailGenerated(ThumbnailResult),
+    FullRescan,
+    RescanFinished(RescanSummary),
+    CloseRescanSummary,
+    ToggleMissingProjects,
+    RelinkProject(i32),
+    PurgeProject(i32),
+    ConfirmPurge,
+    CancelPurge,
+    ToggleBoard,
+    /// Project id and the status to move it to, None clears its status.
+    MoveProjectStatus(i32, Option<String>),
 }
 impl MainView {
     pub fn new(config: Config) -> Self {
         let db_manager = ThreeDManager::setup_db_connection();
         let mut main_view = MainView {
+            thumbnail_queue: ThumbnailQueue::new(config.clone()),
+            thumbnail_cache: ThumbnailCache::new(&config),
             config,
             db_manager,
             project_list: vec![],
-            name_filter: "".to_string(),
+            search_text: "".to_string(),
+            search_snippets: HashMap::new(),
+            search_error: None,
+            sort: Sort::new(SortKey::Relevance),
+            saved_searches: vec![],
+            saved_search_counts: HashMap::new(),
+            saved_search_name: "".to_string(),
+            renaming_search: None,
+            collections: vec![],
+            collection_name: "".to_string(),
+            open_collection: None,
+            open_collection_name: "".to_string(),
+            collection_projects: vec![],
+            dragging: None,
+            confirm_delete_collection: None,
+            bed_filter: "".to_string(),
+            printer_filter: None,
+            print_history_filter: None,
             tag_list: vec![],
             filter_tags: vec![],
-            stl_thumb: ThreeDManager::get_stl_thumb(),
+            thumbnails: HashMap::new(),
+            missing_projects: vec![],
+            show_missing: false,
+            scanning: false,
+            rescanning: false,
+            rescan_summary: None,
+            confirm_purge: None,
+            low_spools: vec![],
+            show_board: false,
         };
         main_view.get_projects();
+        main_view.get_saved_searches();
 
         main_view
     }
 
-    pub fn update(&mut self, message: Message) {
+    pub fn update(&mut self, message: Message) -> Task<Message> {
         match message {
-            Message::CloseApplication => {
-                error!("You need to have stl-thumb installed first.");
-                std::process::exit(1);
-            }
             Message::ToSettingsPage => {}  //should never get here this is handled in main update
+            Message::ToTagsPage => {}  //should never get here this is handled in main update
+            Message::ToDuplicatesPage => {}  //should never get here this is handled in main update
+            Message::ToSpoolsPage => {}  //should never get here this is handled in main update
+            Message::ToPrintQueuePage => {}  //should never get here this is handled in main update
             Message::SelectProject(_) => {} //should never get here this is handled in main update
             Message::ScanProjectDirs => {
-                self.scan_project_dirs();
-                self.get_projects();
+                self.scanning = true;
+                return Scanner::scan_project_dirs_in_background(self.config.clone()).map(|_| Message::ScanFinished);
+            }
+            Message::ScanFinished => {
+                self.scanning = false;
+                return self.refresh();
             }
             Message::FilterChanged(filter) => {
-                self.name_filter = filter;
+                self.search_text = filter;
                 self.get_projects();
+                return self.load_thumbnails();
+            }
+            Message::BedFilterChanged(filter) => {
+                let previous_bed = BedSize::parse(&self.bed_filter);
+                self.bed_filter = filter;
+                let bed = BedSize::parse(&self.bed_filter);
+                if self.bed_filter.trim().is_empty() || bed.is_some() {
+                    self.get_projects();
+                    if bed != previous_bed {
+                        self.count_saved_searches();
+                    }
+                    return self.load_thumbnails();
+                }
+            }
+            Message::PrinterFilterChanged(printer) => {
+                self.printer_filter = printer;
+                self.get_projects();
+                self.count_saved_searches();
+                return self.load_thumbnails();
+            }
+            Message::PrintHistoryFilterChanged(print_history) => {
+                self.print_history_filter = print_history;
+                self.get_projects();
+                self.count_saved_searches();
+                return self.load_thumbnails();
             }
             Message::FilterTagToggle(tag) => {
                 if let Some(pos) = self.filter_tags.iter().position(|x| *x == tag) {
@@ -87,30 +244,278 @@ impl MainView {
                     self.filter_tags.push(tag);
                 }
                 self.get_projects();
+                return self.load_thumbnails();
+            }
+            Message::SortChanged(key) => {
+                self.sort = Sort::new(key);
+                self.get_projects();
+                return self.load_thumbnails();
+            }
+            Message::SavedSearchNameChanged(name) => {
+                self.saved_search_name = name;
+            }
+            Message::SaveSearch => {
+                let name = self.saved_search_name.trim().to_string();
+                if name.is_empty() {
+                    return Task::none();
+                }
+                if let Err(e) = self.db_manager.create_saved_search(&SavedSearch {
+                    id: 0,
+                    name,
+                    query: self.search_text.trim().to_string(),
+                    tags: self.filter_tags.clone(),
+                    sort: self.sort,
+                    pinned: false,
+                }) {
+                    self.search_error = Some(format!("Could not save the search: {}", e));
+                    return Task::none();
+                }
+                self.saved_search_name = "".to_string();
+                self.get_saved_searches();
+            }
+            Message::ApplySavedSearch(saved_search_id) => {
+                let Some(saved_search) = self.saved_searches.iter().find(|saved_search| saved_search.id == saved_search_id) else { return Task::none() };
+                self.search_text = saved_search.query.clone();
+                self.filter_tags = saved_search.tags.clone();
+                self.sort = saved_search.sort;
+                self.show_missing = false;
+                self.get_projects();
+                return self.load_thumbnails();
+            }
+            Message::TogglePinSavedSearch(saved_search_id) => {
+                if let Some(saved_search) = self.saved_searches.iter().find(|saved_search| saved_search.id == saved_search_id) {
+                    self.db_manager.set_saved_search_pinned(saved_search_id, !saved_search.pinned);
+                    self.get_saved_searches();
+                }
+            }
+            Message::StartRenameSavedSearch(saved_search_id) => {
+                self.renaming_search = self.saved_searches.iter()
+                    .find(|saved_search| saved_search.id == saved_search_id)
+                    .map(|saved_search| (saved_search.id, saved_search.name.clone()));
+            }
+            Message::RenameSavedSearchChanged(name) => {
+                if let Some((_, new_name)) = self.renaming_search.as_mut() {
+                    *new_name = name;
+                }
+            }
+            Message::FinishRenameSavedSearch => {
+                if let Some((saved_search_id, name)) = self.renaming_search.take() && !name.trim().is_empty() {
+                    self.db_manager.rename_saved_search(saved_search_id, name.trim());
+                    self.get_saved_searches();
+                }
+            }
+            Message::CancelRenameSavedSearch => {
+                self.renaming_search = None;
+            }
+            Message::DeleteSavedSearch(saved_search_id) => {
+                self.db_manager.delete_saved_search(saved_search_id);
+                self.get_saved_searches();
+            }
+            Message::CollectionNameChanged(name) => {
+                self.collection_name = name;
+            }
+            Message::CreateCollection => {
+                let name = self.collection_name.trim().to_string();
+                if name.is_empty() {
+                    return Task::none();
+                }
+                let collection_id = self.db_manager.create_collection(&name);
+                self.collection_name = "".to_string();
+                return self.update(Message::OpenCollection(collection_id));
+            }
+            Message::OpenCollection(collection_id) => {
+                self.collections = self.db_manager.get_collections();
+                self.open_collection = self.collections.iter().find(|collection| collection.id == collection_id).cloned();
+                self.open_collection_name = self.open_collection.as_ref().map(|collection| collection.name.clone()).unwrap_or_default();
+                self.show_missing = false;
+                self.dragging = None;
+                self.get_collection_projects();
+                return self.load_thumbnails();
+            }
+            Message::CloseCollection => {
+                self.open_collection = None;
+                self.collection_projects = vec![];
+            }
+            Message::OpenCollectionNameChanged(name) => {
+                self.open_collection_name = name;
+            }
+            Message::RenameCollection => {
+                let name = self.open_collection_name.trim().to_string();
+                if let Some(collection) = self.open_collection.as_mut() && !name.is_empty() {
+                    self.db_manager.rename_collection(collection.id, &name);
+                    collection.name = name;
+                    self.collections = self.db_manager.get_collections();
+                }
+            }
+            Message::DeleteCollection => {
+                self.confirm_delete_collection = self.open_collection.clone();
+            }
+            Message::ConfirmDeleteCollection => {
+                if let Some(collection) = self.confirm_delete_collection.take() {
+                    if let Err(e) = self.db_manager.delete_collection(collection.id) {
+                        error!("Could not delete collection {}: {}", collection.name, e);
+                        return Task::none();
+                    }
+                    self.open_collection = None;
+                    self.collection_projects = vec![];
+                    self.collections = self.db_manager.get_collections();
+                }
+            }
+            Message::CancelDeleteCollection => {
+                self.confirm_delete_collection = None;
+            }
+            Message::RemoveFromCollection(project_id) => {
+                if let Some(collection) = &self.open_collection {
+                    self.db_manager.collection_remove_project(collection.id, project_id);
+                    self.get_collection_projects();
+                }
+            }
+            Message::StartDrag(index) => {
+                self.dragging = Some((index, index));
+            }
+            Message::DragOver(index) => {
+                if let Some((from, _)) = self.dragging {
+                    self.dragging = Some((from, index));
+                }
+            }
+            Message::Drop => {
+                if let Some((from, to)) = self.dragging.take()
+                    && from != to
+                    && let Some(collection) = &self.open_collection
+                    && let Some(project) = self.collection_projects.get(from) {
+                    self.db_manager.collection_move_project(collection.id, project.id, to);
+                    self.get_collection_projects();
+                }
+            }
+            Message::CancelDrag => {
+                self.dragging = None;
+            }
+            Message::ThumbnailGenerated(result) => {
+                let next_jobs = self.thumbnail_queue.finished(&result);
+                self.thumbnails.insert(result.source, result.image);
+                return next_jobs.map(Message::ThumbnailGenerated);
+            }
+            Message::FullRescan => {
+                self.rescanning = true;
+                return Scanner::rescan_in_background(self.config.clone()).map(Message::RescanFinished);
+            }
+            Message::RescanFinished(summary) => {
+                self.rescanning = false;
+                self.rescan_summary = Some(summary);
+                return self.refresh();
+            }
+            Message::CloseRescanSummary => {
+                self.rescan_summary = None;
+            }
+            Message::ToggleMissingProjects => {
+                self.show_missing = !self.show_missing;
+            }
+            Message::RelinkProject(project_id) => {
+                let Some(project) = self.missing_projects.iter().find(|project| project.id == project_id) else { return Task::none() };
+                if let Some(new_path) = FileDialog::new().set_title(format!("Where is {} now?", project.name)).pick_folder() {
+                    Scanner::new(self.config.clone()).relink_project(project, &new_path);
+                    return self.refresh();
+                }
+            }
+            Message::PurgeProject(project_id) => {
+                self.confirm_purge = self.missing_projects.iter().find(|project| project.id == project_id).cloned();
+            }
+            Message::ConfirmPurge => {
+                if let Some(project) = self.confirm_purge.take() {
+                    info!("Purging missing project {} ({})", project.name, project.path);
+                    self.db_manager.delete_project(project.id);
+                    return self.refresh();
+                }
+            }
+            Message::CancelPurge => {
+                self.confirm_purge = None;
+            }
+            Message::ToggleBoard => {
+                self.show_board = !self.show_board;
+            }
+            Message::MoveProjectStatus(project_id, status) => {
+                self.db_manager.set_project_status(project_id, status.as_deref());
+                self.get_projects();
+                self.count_saved_searches();
             }
         }
+        Task::none()
+    }
 
+    /**
+     * Reload the project list, after a scan or when the watcher saw changes.
+     */
+    pub fn refresh(&mut self) -> Task<Message> {
+        self.get_projects();
+        self.count_saved_searches();
+        self.load_thumbnails()
+    }
+
+    /**
+     * Pick up existing thumbnails for the listed projects and queue the missing ones for rendering.
+     */
+    pub fn load_thumbnails(&mut self) -> Task<Message> {
+        let mut missing = vec![];
+        for project in self.project_list.iter().chain(self.collection_projects.iter()) {
+            let Some(project_file) = project.get_default_or_first_image_file() else { continue };
+            if self.thumbnails.contains_key(&project_file.path) || self.thumbnail_queue.is_queued(&project_file.path) {
+                continue;
+            }
+            match project_file.cached_image_path(&self.config, &self.thumbnail_cache) {
+                Some(image_path) => {
+                    self.thumbnails.insert(project_file.path.clone(), image_path);
+                }
+                None => missing.push(project_file),
+            }
+        }
+        self.thumbnail_queue.enqueue(missing).map(Message::ThumbnailGenerated)
     }
     pub fn view(&self) -> Element<'_, Message> {
         let main_content = row![self.main_side_panel(), self.main_project_panel()];
 
-        if ThreeDManager::get_stl_thumb().eq("") {
-            let dialog_content = "Please install stl-thumb first";
-            dialog(true, Element::new(main_content), dialog_content)
-                .title("Save")
-                .push_button(iced_dialog::button("OK", Message::CloseApplication))
-                .width(350)
-                .height(234)
-                .into()
-        } else {
-            let dialog_content = "Please add print project directories in the settings page.";
-            dialog(self.config.clone().print_path_empty_or_none(), Element::new(main_content), dialog_content)
-                .title("Save")
-                .push_button(iced_dialog::button("OK", Message::ToSettingsPage))
-                .width(350)
-                .height(234)
-                .into()
-        }
+        let summary_content = match &self.rescan_summary {
+            Some(summary) if summary.has_changes() => format!(
+                "{} new projects, {} missing, {} found again.\n{} files added, {} files removed.",
+                summary.projects_added, summary.projects_missing, summary.projects_restored,
+                summary.files_added, summary.files_removed,
+            ),
+            _ => "Everything was already up to date.".to_string(),
+        };
+        let main_content = dialog(self.rescan_summary.is_some(), main_content, text(summary_content))
+            .title("Rescan Finished")
+            .push_button(iced_dialog::button("OK", Message::CloseRescanSummary))
+            .width(400)
+            .height(234);
+
+        let purge_content = match &self.confirm_purge {
+            Some(project) => format!("Remove {} and its notes, tags and sources from the library? Nothing on disk is deleted.", project.name),
+            None => "".to_string(),
+        };
+        let main_content = dialog(self.confirm_purge.is_some(), main_content, text(purge_content))
+            .title("Purge Project")
+            .push_button(iced_dialog::button("Cancel", Message::CancelPurge))
+            .push_button(iced_dialog::button("Purge", Message::ConfirmPurge))
+            .width(400)
+            .height(234);
+
+        let delete_collection_content = match &self.confirm_delete_collection {
+            Some(collection) => format!("Delete the collection {}? The projects in it are kept.", collection.name),
+            None => "".to_string(),
+        };
+        let main_content = dialog(self.confirm_delete_collection.is_some(), main_content, text(delete_collection_content))
+            .title("Delete Collection")
+            .push_button(iced_dialog::button("Cancel", Message::CancelDeleteCollection))
+            .push_button(iced_dialog::button("Delete", Message::ConfirmDeleteCollection))
+            .width(400)
+            .height(234);
+
+        let dialog_content = "Please add print project directories in the settings page.";
+        dialog(self.config.clone().print_path_empty_or_none(), main_content, dialog_content)
+            .title("Save")
+            .push_button(iced_dialog::button("OK", Message::ToSettingsPage))
+            .width(350)
+            .height(234)
+            .into()
     }
     fn main_side_panel(&self) -> Container<'_, Message> {
         let prog_options = column![]
@@ -122,35 +527,110 @@ impl MainView {
 
             )
             .push(
-                button(Container::new(Text::new("Scan Project Dirs")).center_x(Fill))
+                button(Container::new(Text::new("Manage Tags")).center_x(Fill))
                     .style(ThreeDManager::rounded_button)
-                    .on_press(Message::ScanProjectDirs)
+                    .on_press(Message::ToTagsPage)
                     .width(Length::FillPortion(4))
             )
+            .push(
+                button(Container::new(Text::new("Find Duplicates")).center_x(Fill))
+                    .style(ThreeDManager::rounded_button)
+                    .on_press(Message::ToDuplicatesPage)
+                    .width(Length::FillPortion(4))
+            )
+            .push(
+                button(Container::new(Text::new("Filament Spools")).center_x(Fill))
+                    .style(ThreeDManager::rounded_button)
+                    .on_press(Message::ToSpoolsPage)
+                    .width(Length::FillPortion(4))
+            )
+            .push(
+                button(Container::new(Text::new("Print Queue")).center_x(Fill))
+                    .style(ThreeDManager::rounded_button)
+                    .on_press(Message::ToPrintQueuePage)
+                    .width(Length::FillPortion(4))
+            )
+            .push(
+                button(Container::new(Text::new(if self.scanning { "Scanning…" } else { "Scan Project Dirs" })).center_x(Fill))
+                    .style(ThreeDManager::rounded_button)
+                    .on_press_maybe((!self.scanning).then_some(Message::ScanProjectDirs))
+                    .width(Length::FillPortion(4))
+            )
+            .push(
+                button(Container::new(Text::new(if self.rescanning { "Rescanning…" } else { "Full Rescan" })).center_x(Fill))
+                    .style(ThreeDManager::rounded_button)
+                    .on_press_maybe((!self.rescanning).then_some(Message::FullRescan))
+                    .width(Length::FillPortion(4))
+            )
+            .push((!self.missing_projects.is_empty()).then(|| {
+                button(Container::new(text!("Missing Projects ({})", self.missing_projects.len())).center_x(Fill))
+                    .style(ThreeDManager::rounded_button)
+                    .on_press(Message::ToggleMissingProjects)
+                    .width(Length::FillPortion(4))
+            }))
             .width(Fill);
         let mut filter_column = column![].width(Fill).height(Fill);
         filter_column = filter_column
             .push(
-                text_input("Search", &self.name_filter)
+                text_input("Search, e.g. dragon tag:articulated -tag:supports sort:added", &self.search_text)
                     .style(|theme, status| {
                         let mut style = text_input::default(theme, status);
                         style.background = Background::Color(iced::Color::BLACK);
                         style
                     })
                     .on_input(Message::FilterChanged)
+            )
+            .push(self.search_error.as_ref().map(|error| text(error.clone()).style(text::danger)))
+            .push(pick_list(SortKey::ALL, Some(self.sort.key), Message::SortChanged).width(Fill))
+            .push(
+                text_input("Fits bed, e.g. 220x220x250", &self.bed_filter)
+                    .style(|theme, status| {
+                        let mut style = text_input::default(theme, status);
+                        style.background = Background::Color(iced::Color::BLACK);
+                        style
+                    })
+                    .on_input(Message::BedFilterChanged)
             );
+        if !self.bed_filter.trim().is_empty() && BedSize::parse(&self.bed_filter).is_none() {
+            filter_column = filter_column.push(text("Bed size should look like 220x220 or 220x220x250").style(text::danger));
+        }
+        let printer_names: Vec<String> = self.config.get_printers().into_iter().map(|printer| printer.name).collect();
+        if !printer_names.is_empty() {
+            let mut printer_row = row![
+                pick_list(printer_names, self.printer_filter.clone(), |name| Message::PrinterFilterChanged(Some(name)))
+                    .placeholder("Fits printer")
+                    .width(Fill)
+            ];
+            if self.printer_filter.is_some() {
+                printer_row = printer_row.push(button(text("✕")).style(button::text).on_press(Message::PrinterFilterChanged(None)));
+            }
+            filter_column = filter_column.push(printer_row);
+        }
+        let mut print_history_row = row![
+            pick_list(PrintHistory::ALL, self.print_history_filter, |print_history| Message::PrintHistoryFilterChanged(Some(print_history)))
+                .placeholder("Print history")
+                .width(Fill)
+        ];
+        if self.print_history_filter.is_some() {
+            print_history_row = print_history_row.push(button(text("✕")).style(button::text).on_press(Message::PrintHistoryFilterChanged(None)));
+        }
+        filter_column = filter_column.push(print_history_row);
         let mut tag_boxes = column![].width(Fill).height(Fill);
-        for tag in self.tag_list.iter() {
+        //children are indented under their parent, ticking a parent matches its children too
+        for (tag, depth) in ProjectTag::tree_order(&self.tag_list) {
+            let indent = "    ".repeat(depth);
             if self.filter_tags.contains(&tag) {
                 tag_boxes = tag_boxes.push(
-                    button(text!("☑ {}", tag.tag)).style(button::text).on_press(Message::FilterTagToggle(tag.clone()))
+                    button(text!("{}☑ {}", indent, tag.tag)).style(button::text).on_press(Message::FilterTagToggle(tag.clone()))
                 );
             } else {
                 tag_boxes = tag_boxes.push(
-                    button(text!("☐ {}", tag.tag)).style(button::text).on_press(Message::FilterTagToggle(tag.clone()))
+                    button(text!("{}☐ {}", indent, tag.tag)).style(button::text).on_press(Message::FilterTagToggle(tag.clone()))
                 );
             }
         }
+        filter_column = filter_column.push(self.saved_search_list());
+        filter_column = filter_column.push(self.collection_list());
         filter_column = filter_column.push(scrollable(tag_boxes));
         let side_panel = column![text("Filter").size(50)]
             .push(
@@ -161,15 +641,210 @@ impl MainView {
             ).width(Fill);
         Container::new(side_panel).width(Length::Fixed(20.0)).height(Fill).center_x(Length::FillPortion(1)).center_y(Fill)
     }
+    /**
+     * Saved searches with their current counts, plus a box to save the current search.
+     */
+    fn saved_search_list(&self) -> Column<'_, Message> {
+        let mut saved_list = column![text("Saved Searches").size(20)].width(Fill);
+        for saved_search in self.saved_searches.iter() {
+            if let Some((saved_search_id, name)) = &self.renaming_search && *saved_search_id == saved_search.id {
+                saved_list = saved_list.push(
+                    row![
+                        text_input("Name", name)
+                            .on_input(Message::RenameSavedSearchChanged)
+                            .on_submit(Message::FinishRenameSavedSearch)
+                            .width(Fill),
+                        button(text("✓")).style(button::text).on_press(Message::FinishRenameSavedSearch),
+                        button(text("✕")).style(button::text).on_press(Message::CancelRenameSavedSearch),
+                    ].align_y(Vertical::Center)
+                );
+                continue;
+            }
+            let count = match self.saved_search_counts.get(&saved_search.id) {
+                Some(count) => count.to_string(),
+                None => "?".to_string(),
+            };
+            saved_list = saved_list.push(
+                row![
+                    button(text!("{} ({})", saved_search.name, count))
+                        .style(button::text)
+                        .on_press(Message::ApplySavedSearch(saved_search.id))
+                        .width(Fill),
+                    button(text(if saved_search.pinned { "★" } else { "☆" })).style(button::text).on_press(Message::TogglePinSavedSearch(saved_search.id)),
+                    button(text("✎")).style(button::text).on_press(Message::StartRenameSavedSearch(saved_search.id)),
+                    button(text("✕")).style(button::text).on_press(Message::DeleteSavedSearch(saved_search.id)),
+                ].align_y(Vertical::Center)
+            );
+        }
+        let has_search = !self.search_text.trim().is_empty() || !self.filter_tags.is_empty();
+        if has_search {
+            saved_list = saved_list.push(
+                row![
+                    text_input("Save this search as…", &self.saved_search_name)
+                        .on_input(Message::SavedSearchNameChanged)
+                        .on_submit(Message::SaveSearch)
+                        .width(Fill),
+                    button(text("Save"))
+                        .style(ThreeDManager::rounded_button)
+                        .on_press_maybe((!self.saved_search_name.trim().is_empty()).then_some(Message::SaveSearch)),
+                ].align_y(Vertical::Center)
+            );
+        }
+        saved_list
+    }
+    /**
+     * Collections with how many projects they hold, plus a box to create one.
+     */
+    fn collection_list(&self) -> Column<'_, Message> {
+        let mut collection_list = column![text("Collections").size(20)].width(Fill);
+        for collection in self.collections.iter() {
+            collection_list = collection_list.push(
+                button(text!("{} ({})", collection.name, collection.project_count))
+                    .style(button::text)
+                    .on_press(Message::OpenCollection(collection.id))
+                    .width(Fill)
+            );
+        }
+        collection_list.push(
+            row![
+                text_input("New collection", &self.collection_name)
+                    .on_input(Message::CollectionNameChanged)
+                    .on_submit(Message::CreateCollection)
+                    .width(Fill),
+                button(text("Create"))
+                    .style(ThreeDManager::rounded_button)
+                    .on_press_maybe((!self.collection_name.trim().is_empty()).then_some(Message::CreateCollection)),
+            ].align_y(Vertical::Center)
+        )
+    }
+    /**
+     * The open collection in its own order.  Dragging a row by its handle and letting go over
+     * another row moves it there.
+     */
+    fn collection_panel(&self, collection: &Collection) -> Container<'_, Message> {
+        let mut collection_list = column![
+            row![
+                text_input("Collection name", &self.open_collection_name)
+                    .on_input(Message::OpenCollectionNameChanged)
+                    .on_submit(Message::RenameCollection)
+                    .size(40)
+                    .width(Fill),
+                button(text("Rename"))
+                    .style(ThreeDManager::rounded_button)
+                    .on_press_maybe((self.open_collection_name.trim() != collection.name && !self.open_collection_name.trim().is_empty()).then_some(Message::RenameCollection)),
+                button(text("Delete")).style(button::danger).on_press(Message::DeleteCollection),
+                button(text("Back to Projects")).style(ThreeDManager::rounded_button).on_press(Message::CloseCollection),
+            ].spacing(10).align_y(Vertical::Center)
+        ].width(Fill).spacing(10);
+        if self.collection_projects.is_empty() {
+            collection_list = collection_list.push(text("Add projects to this collection from their project page."));
+        }
+        let mut rows = column![].width(Fill).spacing(5);
+        for (index, project) in self.collection_projects.iter().enumerate() {
+            let preview: Element<'_, Message> = match project.get_default_or_first_image_file().and_then(|file| self.thumbnails.get(&file.path)) {
+                Some(image_path) if !image_path.is_empty() => image(image_path.clone()).width(64).height(64).into(),
+                _ => Space::new().width(64).height(64).into(),
+            };
+            let project_row = row![
+                mouse_area(text("⠿").size(30)).on_press(Message::StartDrag(index)).interaction(mouse::Interaction::Grab),
+                preview,
+                column![text(project.name.clone()).size(20), text(project.path.clone()).size(12)].width(Fill),
+                button(text("Open")).style(ThreeDManager::rounded_button).on_press(Message::SelectProject(project.clone())),
+                button(text("Remove")).style(button::danger).on_press(Message::RemoveFromCollection(project.id)),
+            ].spacing(10).align_y(Vertical::Center);
+            let drop_target = matches!(self.dragging, Some((from, to)) if to == index && from != index);
+            let dragged = matches!(self.dragging, Some((from, _)) if from == index);
+            rows = rows.push(
+                mouse_area(
+                    container(project_row).padding(5).width(Fill).style(move |theme: &Theme| {
+                        let palette = theme.extended_palette();
+                        let mut style = container::Style::default();
+                        if drop_target {
+                            style.border = Border { color: palette.primary.strong.color, width: 2.0, radius: 5.0.into() };
+                        } else if dragged {
+                            style.background = Some(palette.background.strong.color.into());
+                        }
+                        style
+                    })
+                ).on_enter(Message::DragOver(index))
+            );
+        }
+        let mut drag_area = mouse_area(rows).on_release(Message::Drop).on_exit(Message::CancelDrag);
+        if self.dragging.is_some() {
+            drag_area = drag_area.interaction(mouse::Interaction::Grabbing);
+        }
+        collection_list = collection_list.push(drag_area);
+        Container::new(scrollable(collection_list)).width(Fill).height(Fill).center_x(Length::FillPortion(4))
+    }
+    /**
+     * A search snippet with the matched words in bold.
+     */
+    fn snippet_text(snippet: &SearchSnippet) -> Element<'_, Message> {
+        let mut spans: Vec<text::Span<'_>> = vec![span(snippet.label().to_string())];
+        for (part, highlighted) in highlight_parts(&snippet.text) {
+            let part_span = span(part);
+            spans.push(if highlighted { part_span.font(Font { weight: Weight::Bold, ..Font::default() }) } else { part_span });
+        }
+        rich_text(spans).size(12).width(Fill).into()
+    }
+    /**
+     * Projects whose directory has gone, with buttons to point them at a new one or remove them.
+     */
+    fn missing_project_panel(&self) -> Container<'_, Message> {
+        let mut missing_list = column![
+            row![
+                text("Missing Projects").size(50).width(Fill),
+                button(text("Back to Projects")).style(ThreeDManager::rounded_button).on_press(Message::ToggleMissingProjects),
+            ].align_y(Vertical::Center)
+        ].width(Fill).spacing(10);
+        for project in &self.missing_projects {
+            missing_list = missing_list.push(
+                row![
+                    column![text(project.name.clone()).size(20), text(project.path.clone())].width(Fill),
+                    button(text("Relink…")).style(ThreeDManager::rounded_button).on_press(Message::RelinkProject(project.id)),
+                    button(text("Purge")).style(button::danger).on_press(Message::PurgeProject(project.id)),
+                ].spacing(10).align_y(Vertical::Center)
+            );
+        }
+        Container::new(scrollable(missing_list)).width(Fill).height(Fill).center_x(Length::FillPortion(4))
+    }
     fn main_project_panel(&self) -> Container<'_, Message> {
+        if self.show_missing && !self.missing_projects.is_empty() {
+            return self.missing_project_panel();
+        }
+        if let Some(collection) = &self.open_collection {
+            return self.collection_panel(collection);
+        }
         let mut project_grid = row![].height(Fill).width(Fill);
-        let mut project_panel = column![text("Project List").size(50)].height(Fill).width(Fill);
+        let mut header = row![text("Project List").size(50).width(Fill)].spacing(10).align_y(Vertical::Center);
+        if let Some((completed, total)) = self.thumbnail_queue.progress() {
+            header = header.push(text!("Generating thumbnails {}/{}", completed, total));
+        }
+        header = header.push(
+            button(text(if self.show_board { "Grid" } else { "Board" })).style(ThreeDManager::rounded_button).on_press(Message::ToggleBoard)
+        );
+        let mut project_panel = column![header].height(Fill).width(Fill);
+        if !self.low_spools.is_empty() {
+            let spools: Vec<String> = self.low_spools.iter().map(|spool| spool.to_string()).collect();
+            project_panel = project_panel.push(
+                row![
+                    text!("Running low on filament: {}", spools.join(", ")).style(text::danger).width(Fill),
+                    button(text("Spools")).style(ThreeDManager::rounded_button).on_press(Message::ToSpoolsPage),
+                ].spacing(10).align_y(Vertical::Center)
+            );
+        }
 
+        if self.show_board {
+            project_panel = project_panel.push(self.status_board());
+            return Container::new(project_panel).width(Fill).height(Fill).center_x(Length::FillPortion(4)).center_y(Fill);
+        }
         for project in &self.project_list {
             let project_file = project.get_default_or_first_image_file();
-            let image_path = match project_file {
-                Some(project_file) => project_file.get_image_path(self.stl_thumb.clone()),
-                None => "".to_string()
+            let preview: Element<'_, Message> = match project_file.as_ref().and_then(|file| self.thumbnails.get(&file.path)) {
+                Some(image_path) if !image_path.is_empty() => image(image_path.clone()).into(),
+                Some(_) => text("No preview").into(),
+                None if project_file.is_some() => text("Generating preview…").into(),
+                None => text("No preview").into(),
             };
 
             project_grid = project_grid.push(
@@ -177,8 +852,12 @@ impl MainView {
                        container(
                            column![
                                text(project.name.to_string()).align_x(Alignment::Center).width(Fill),
-                               image(image_path)
-                           ],
+                               preview
+                           ]
+                               .push(self.search_snippets.get(&project.id).map(Self::snippet_text))
+                               .push(project.status.as_deref().map(Self::status_badge))
+                               .push(project.missing.then(|| text("Missing").style(text::danger)))
+                               .align_x(Horizontal::Center),
                        )
                            .align_x(Horizontal::Center)
                 )
@@ -193,60 +872,163 @@ impl MainView {
         Container::new(project_panel).width(Fill).height(Fill).center_x(Length::FillPortion(4)).center_y(Fill)
     }
 
-    fn get_projects(&mut self) {
-        let mut option_filter = None;
-        if !self.name_filter.eq(&"".to_string()) {
-            option_filter = Some(self.name_filter.clone());
+    fn status_badge(status: &str) -> Element<'_, Message> {
+        container(text(status.to_string()).size(12))
+            .padding([2, 8])
+            .style(|theme: &Theme| {
+                let palette = theme.extended_palette();
+                container::Style {
+                    background: Some(Background::Color(palette.primary.weak.color)),
+                    text_color: Some(palette.primary.weak.text),
+                    border: Border { radius: 10.0.into(), ..Border::default() },
+                    ..container::Style::default()
+                }
+            })
+            .into()
+    }
+    /**
+     * The listed projects in a column per workflow state, projects without a status first and any
+     * whose status is no longer a workflow state last.  Arrows move a project to the next state.
+     */
+    fn status_board(&self) -> Element<'_, Message> {
+        let mut states: Vec<Option<String>> = vec![None];
+        states.extend(self.config.get_workflow_states().into_iter().map(Some));
+        let other: Vec<&Project> = self.project_list.iter()
+            .filter(|project| !states.contains(&project.status))
+            .collect();
+        let mut board = row![].spacing(10).height(Fill).width(Fill);
+        for (index, state) in states.iter().enumerate() {
+            let projects: Vec<&Project> = self.project_list.iter().filter(|project| project.status == *state).collect();
+            let previous = index.checked_sub(1).map(|previous| states[previous].clone());
+            let next = states.get(index + 1).cloned();
+            board = board.push(self.status_board_column(state.as_deref().unwrap_or("No status"), projects, previous, next));
         }
-        let mut filter_tags :Option<Vec<ProjectTag>> = None;
-        if self.filter_tags.len() > 0 {
-            filter_tags = Some(self.filter_tags.clone());
+        if !other.is_empty() {
+            board = board.push(self.status_board_column("Other", other, states.last().cloned(), None));
         }
-        self.project_list = self.db_manager.get_filtered_projects(option_filter, None, filter_tags);
-        info!("There are {} projects", self.project_list.len());
+        board.into()
     }
-
-    fn scan_project_dirs(&mut self) {
-        if self.config.print_paths.is_none() { return ()}
-        let print_paths = self.config.print_paths.clone().unwrap();
-        for project_dir in print_paths.iter() {
-            self.scan_project_dir(project_dir.clone());
+    fn status_board_column<'a>(&'a self, title: &str, projects: Vec<&'a Project>, previous: Option<Option<String>>, next: Option<Option<String>>) -> Element<'a, Message> {
+        let mut cards = column![].spacing(5).width(Fill);
+        for project in projects.iter() {
+            let preview: Element<'_, Message> = match project.get_default_or_first_image_file().and_then(|file| self.thumbnails.get(&file.path)) {
+                Some(image_path) if !image_path.is_empty() => image(image_path.clone()).width(48).height(48).into(),
+                _ => Space::new().width(48).height(48).into(),
+            };
+            let mut card = row![
+                button(row![preview, text(project.name.clone()).width(Fill)].spacing(5).align_y(Vertical::Center))
+                    .style(button::text)
+                    .width(Fill)
+                    .on_press(Message::SelectProject((*project).clone())),
+            ].spacing(2).align_y(Vertical::Center);
+            if let Some(previous) = &previous {
+                card = card.push(button(text("◀")).style(button::text).padding(3).on_press(Message::MoveProjectStatus(project.id, previous.clone())));
+            }
+            if let Some(next) = &next {
+                card = card.push(button(text("▶")).style(button::text).padding(3).on_press(Message::MoveProjectStatus(project.id, next.clone())));
+            }
+            cards = cards.push(
+                container(card).padding(5).width(Fill).style(|theme: &Theme| {
+                    let palette = theme.extended_palette();
+                    container::Style {
+                        background: Some(Background::Color(palette.background.weak.color)),
+                        border: Border { radius: 5.0.into(), ..Border::default() },
+                        ..container::Style::default()
+                    }
+                })
+            );
         }
+        column![
+            text!("{} ({})", title, projects.len()).size(20),
+            scrollable(cards).height(Fill),
+        ].spacing(5).width(Fill).height(Fill).into()
     }
-    fn scan_project_dir(&mut self, project_dir: String) {
-        for entry in fs::read_dir(Path::new(project_dir.as_str())).unwrap() {
-            let entry = entry.unwrap();
-            if entry.file_type().unwrap().is_dir() {
-                if !self.does_project_with_path_exist(entry.path().to_str().unwrap().to_string()) {
-                    let mut project  = self.create_project(
-                        entry.file_name().to_str().unwrap().to_string(),
-                        entry.path().to_str().unwrap().to_string(),
-                        "".to_string()
-                    );
-                    self.db_manager.update_project_files(project.clone(),  project.get_file_system_files());
-                }
-                debug!("Scanning Project directory {}. The Project Name is {}", entry.path().display(), entry.file_name().display());
+
+    fn get_projects(&mut self) {
+        let mut filter = match self.query_filter(&self.search_text, &self.filter_tags, self.sort) {
+            Ok(filter) => filter,
+            Err(e) => {
+                self.search_error = Some(e);
+                return;
             }
-        }
+        };
+        self.search_error = None;
+        self.apply_filter_controls(&mut filter);
+        self.project_list = self.db_manager.get_filtered_projects(&filter);
+        self.search_snippets = match &filter.query {
+            Some(query) => self.db_manager.get_search_snippets(query),
+            None => HashMap::new(),
+        };
+        self.missing_projects = self.db_manager.get_filtered_projects(&ProjectFilter { missing: Some(true), ..Default::default() });
+        self.tag_list = self.db_manager.get_tag_list();
+        self.collections = self.db_manager.get_collections();
+        self.low_spools = self.db_manager.get_low_spools(self.config.get_low_spool_grams());
+        self.get_collection_projects();
+        info!("There are {} projects", self.project_list.len());
     }
 
-    fn create_project (&mut self, project_name: String, project_path: String, project_notes: String) -> Project {
-        let new_project = Project {
-            id: 0,
-            path: project_path,
-            name: project_name,
-            notes: project_notes,
-            tags: vec![],
-            files: vec![],
-            sources: vec![],
+    fn get_collection_projects(&mut self) {
+        self.collection_projects = match &self.open_collection {
+            Some(collection) => self.db_manager.get_filtered_projects(&ProjectFilter { collection: Some(collection.id), ..Default::default() }),
+            None => vec![],
         };
-        self.db_manager.create_project(new_project).unwrap()
     }
-    fn does_project_with_path_exist(&mut self, project_path: String) -> bool {
-        let project_list = self.db_manager.get_filtered_projects(None,Some(project_path),None);
-        if project_list.len() > 0 {
-            return true
+
+    /**
+     * Reload the saved searches and count what each of them matches now.
+     */
+    fn get_saved_searches(&mut self) {
+        self.saved_searches = self.db_manager.get_saved_searches();
+        self.count_saved_searches();
+    }
+
+    /**
+     * Count what each saved search matches with the bed, printer and print history controls
+     * applied the way the search would.  Only needed when the saved searches, the projects or
+     * those controls change, not while typing a search.
+     */
+    fn count_saved_searches(&mut self) {
+        self.saved_search_counts = self.saved_searches.iter()
+            .filter_map(|saved_search| {
+                let mut filter = self.query_filter(&saved_search.query, &saved_search.tags, saved_search.sort).ok()?;
+                self.apply_filter_controls(&mut filter);
+                Some((saved_search.id, self.db_manager.count_filtered_projects(&filter)))
+            })
+            .collect();
+    }
+
+    /**
+     * Narrow a filter by the bed, printer and print history controls.  fits:, printer: and
+     * printed: typed in the search take precedence over the controls.
+     */
+    fn apply_filter_controls(&self, filter: &mut ProjectFilter) {
+        if filter.fits_bed.is_none() {
+            filter.fits_bed = BedSize::parse(&self.bed_filter);
+        }
+        if filter.fits_printer.is_none() {
+            filter.fits_printer = self.printer_filter.as_ref().and_then(|name| self.config.get_printer(name));
+        }
+        if filter.print_history.is_none() {
+            filter.print_history = self.print_history_filter;
+        }
+    }
+
+    /**
+     * Build the filter for search text in the query syntax plus a set of tags.
+     */
+    fn query_filter(&self, search_text: &str, tags: &[ProjectTag], sort: Sort) -> Result<ProjectFilter, String> {
+        let query = SearchQuery::parse(search_text)?;
+        let mut filter = ProjectFilter { fits_bed: query.fits, print_history: query.printed, status: query.status.clone(), sort: Some(sort), ..Default::default() };
+        if let Some(name) = &query.printer {
+            let printer = self.config.get_printers().into_iter().find(|printer| printer.name.eq_ignore_ascii_case(name));
+            filter.fits_printer = Some(printer.ok_or(format!("There is no printer called {}", name))?);
+        }
+        if !query.is_empty() {
+            filter.query = Some(query);
+        }
+        if !tags.is_empty() {
+            filter.tags = Some(tags.to_vec());
         }
-        false
+        Ok(filter)
     }
 }
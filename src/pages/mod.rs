@@ -17,4 +17,8 @@
 
 pub mod settings;
 pub mod project;
-pub mod main_view;
\ No newline at end of file
+pub mod main_view;
+pub mod tags;
+pub mod duplicates;
+pub mod spools;
+pub mod print_queue;
\ No newline at end of file
@@ -0,0 +1,208 @@
+/*
+ * Copyright (c) 2025-2026 Mitch Tuck
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+use std::collections::HashMap;
+use iced::{Element, Length};
+use iced::alignment::Vertical;
+use iced::widget::{button, column, pick_list, progress_bar, row, scrollable, text, text_input, Container};
+use log::{error, info};
+use crate::ThreeDManager;
+use crate::config::Config;
+use crate::db_manager::DbManager;
+use crate::models::queue_item::{QueueItem, QueuePriority};
+
+pub struct PrintQueuePage {
+    config: Config,
+    db_manager: DbManager,
+    queue: Vec<QueueItem>,
+    /// Only show what can be printed on this printer.
+    printer_filter: Option<String>,
+    /// Quantity typed for each queue item, by item id.
+    quantities: HashMap<i32, String>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    BackToMain,
+    /// Open the project the file is in, handled in main update.
+    OpenProject(i32),
+    PrinterFilterChanged(Option<String>),
+    QuantityChanged(i32, String),
+    /// Item id and how many prints to add, negative takes prints off.
+    CountPrinted(i32, i32),
+    PriorityChanged(i32, QueuePriority),
+    AssignPrinter(i32, Option<String>),
+    /// Item id and the index in queue order to move it to.
+    MoveItem(i32, usize),
+    RemoveItem(i32),
+    ClearFinished,
+}
+
+impl PrintQueuePage {
+    pub fn new(config: Config) -> PrintQueuePage {
+        let mut print_queue_page = PrintQueuePage {
+            config,
+            db_manager: ThreeDManager::setup_db_connection(),
+            queue: vec![],
+            printer_filter: None,
+            quantities: HashMap::new(),
+        };
+        print_queue_page.load_queue();
+        print_queue_page
+    }
+
+    pub fn update(&mut self, message: Message) {
+        match message {
+            Message::BackToMain => {} //handled in main update
+            Message::OpenProject(_) => {} //handled in main update
+            Message::PrinterFilterChanged(printer) => {
+                self.printer_filter = printer;
+            }
+            Message::QuantityChanged(item_id, quantity) => {
+                if let Ok(value) = quantity.trim().parse::<i32>() && value > 0 {
+                    self.update_item(item_id, |item| item.quantity = value);
+                }
+                self.quantities.insert(item_id, quantity);
+            }
+            Message::CountPrinted(item_id, count) => {
+                self.update_item(item_id, |item| item.printed = (item.printed + count).max(0));
+            }
+            Message::PriorityChanged(item_id, priority) => {
+                self.update_item(item_id, |item| item.priority = priority);
+            }
+            Message::AssignPrinter(item_id, printer) => {
+                self.update_item(item_id, |item| item.printer = printer);
+            }
+            Message::MoveItem(item_id, index) => {
+                if let Err(e) = self.db_manager.move_queue_item(item_id, index) {
+                    error!("Could not move queue item {}: {}", item_id, e);
+                }
+                self.load_queue();
+            }
+            Message::RemoveItem(item_id) => {
+                if let Err(e) = self.db_manager.delete_queue_item(item_id) {
+                    error!("Could not remove queue item {}: {}", item_id, e);
+                }
+                self.load_queue();
+            }
+            Message::ClearFinished => {
+                let cleared = self.db_manager.clear_finished_queue_items();
+                info!("Cleared {} finished items from the print queue", cleared);
+                self.load_queue();
+            }
+        }
+    }
+
+    fn update_item(&mut self, item_id: i32, change: impl FnOnce(&mut QueueItem)) {
+        let Some(item) = self.queue.iter_mut().find(|item| item.id == item_id) else { return };
+        change(item);
+        self.db_manager.update_queue_item(item);
+        self.load_queue();
+    }
+
+    fn load_queue(&mut self) {
+        self.queue = self.db_manager.get_print_queue();
+        self.quantities.retain(|item_id, _| self.queue.iter().any(|item| item.id == *item_id));
+    }
+
+    /**
+     * Index in queue order of the items that should be listed with the printer filter applied.
+     */
+    fn visible_items(&self) -> Vec<usize> {
+        let printer = self.printer_filter.as_ref().and_then(|name| self.config.get_printer(name));
+        self.queue.iter().enumerate()
+            .filter(|(_, item)| printer.as_ref().is_none_or(|printer| item.is_for_printer(printer)))
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let printer_names: Vec<String> = self.config.get_printers().into_iter().map(|printer| printer.name).collect();
+        let visible = self.visible_items();
+        let remaining: i32 = visible.iter().map(|index| self.queue[*index].remaining()).sum();
+        let mut header = row![
+            text("Print Queue").size(50).width(Length::Fill),
+            text!("{} left to print", remaining),
+        ].spacing(10).align_y(Vertical::Center);
+        if !printer_names.is_empty() {
+            header = header.push(
+                pick_list(printer_names.clone(), self.printer_filter.clone(), |name| Message::PrinterFilterChanged(Some(name))).placeholder("All printers")
+            );
+            if self.printer_filter.is_some() {
+                header = header.push(button(text("✕")).style(button::text).padding(3).on_press(Message::PrinterFilterChanged(None)));
+            }
+        }
+        header = header
+            .push(button(text("Clear Finished")).style(ThreeDManager::rounded_button)
+                .on_press_maybe(self.queue.iter().any(|item| item.is_done()).then_some(Message::ClearFinished)))
+            .push(button(text("Back")).style(ThreeDManager::rounded_button).on_press(Message::BackToMain));
+
+        let mut queue_list = column![].spacing(8).width(Length::Fill);
+        for (position, index) in visible.iter().enumerate() {
+            let item = &self.queue[*index];
+            //items only move past neighbours of the same priority, priority decides the rest of the order
+            let move_to = |neighbour: Option<&usize>| neighbour
+                .filter(|neighbour| self.queue[**neighbour].priority == item.priority)
+                .map(|neighbour| Message::MoveItem(item.id, *neighbour));
+            let previous = position.checked_sub(1).and_then(|previous| visible.get(previous));
+            let file_name = std::path::Path::new(&item.file.path).file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or(item.file.path.clone());
+            let count = text!("{} / {}", item.printed, item.quantity);
+            let mut item_row = row![
+                column![
+                    button(text("▲")).style(button::text).padding(2).on_press_maybe(move_to(previous)),
+                    button(text("▼")).style(button::text).padding(2).on_press_maybe(move_to(visible.get(position + 1))),
+                ],
+                column![
+                    row![
+                        text!("{}× {}", item.quantity, file_name).size(20),
+                        button(text(item.project_name.clone()).size(12)).style(button::text).padding(3)
+                            .on_press(Message::OpenProject(item.file.project_id)),
+                    ].spacing(5).align_y(Vertical::Center),
+                    progress_bar(0.0..=1.0, item.progress()).girth(6),
+                ].spacing(3).width(Length::Fill),
+                if item.is_done() { count.style(text::success) } else { count },
+                button(text("−")).style(ThreeDManager::rounded_button).on_press_maybe((item.printed > 0).then_some(Message::CountPrinted(item.id, -1))),
+                button(text("+")).style(ThreeDManager::rounded_button).on_press(Message::CountPrinted(item.id, 1)),
+                text_input("Qty", self.quantities.get(&item.id).map(|quantity| quantity.as_str()).unwrap_or(&item.quantity.to_string()))
+                    .on_input(move |quantity| Message::QuantityChanged(item.id, quantity))
+                    .width(50),
+                pick_list(QueuePriority::ALL, Some(item.priority), move |priority| Message::PriorityChanged(item.id, priority)),
+            ].spacing(10).align_y(Vertical::Center);
+            if !printer_names.is_empty() {
+                item_row = item_row.push(
+                    pick_list(printer_names.clone(), item.printer.clone(), move |name| Message::AssignPrinter(item.id, Some(name))).placeholder("Any printer")
+                );
+                if item.printer.is_some() {
+                    item_row = item_row.push(button(text("✕")).style(button::text).padding(3).on_press(Message::AssignPrinter(item.id, None)));
+                }
+            }
+            item_row = item_row.push(button(text("Remove")).style(button::danger).on_press(Message::RemoveItem(item.id)));
+            queue_list = queue_list.push(item_row);
+        }
+        if self.queue.is_empty() {
+            queue_list = queue_list.push(text("Add files to the queue from their project page. Logging a successful print counts it here."));
+        } else if visible.is_empty() {
+            queue_list = queue_list.push(text("Nothing queued fits this printer."));
+        }
+
+        Container::new(column![header, scrollable(queue_list).height(Length::Fill)].spacing(10))
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .into()
+    }
+}
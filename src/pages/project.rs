@@ -16,18 +16,52 @@
  */
 use std::fs::{self, OpenOptions};
 use std::io::Write;
-use iced::{Element, Length, Theme};
-use iced::alignment::Horizontal;
-use iced::widget::{button, text, Container, row, Row, column, scrollable, text_editor, text_input, Space, image};
+use std::sync::Arc;
+use iced::{Element, Length, Task, Theme};
+use iced::alignment::{Horizontal, Vertical};
+use iced::widget::{button, text, Container, row, Row, column, Column, scrollable, text_editor, text_input, Space, image, canvas, pick_list};
+use iced_dialog::dialog;
+use rfd::FileDialog;
 use log::{error, info};
 use crate::{ThreeDManager};
+use crate::config::Config;
+use crate::cost;
 use crate::db_manager::DbManager;
+use crate::gcode::{format_duration, parse_duration};
+use crate::models::collection::Collection;
+use crate::models::gcode_info::GcodeInfo;
 use crate::models::file::ProjectFile;
+use crate::models::print_job::{PrintJob, PrintOutcome};
+use crate::models::printer_profile::PrinterProfile;
 use crate::models::project::Project;
 use crate::models::project_tag::ProjectTag;
+use crate::models::queue_item::QueuePriority;
+use crate::models::spool::Spool;
+use crate::models::status_change::StatusChange;
+use crate::renderer::Camera;
+use crate::widgets::model_viewer::{ModelViewer, ViewerMesh};
+
+/// A print being logged for the selected file, fields as typed.
+#[derive(Debug, Clone)]
+struct PrintLogForm {
+    file: ProjectFile,
+    printed: String,
+    printer: String,
+    material: String,
+    color: String,
+    duration: String,
+    outcome: PrintOutcome,
+    failure_reason: String,
+    notes: String,
+    photos: Vec<String>,
+    spool: Option<Spool>,
+    /// Grams of filament used.
+    filament_used: String,
+    error: Option<String>,
+}
 
 pub struct ProjectPage {
-    stl_thumb: String,
+    config: Config,
     db_manager: DbManager,
     selected_project: Project,
     project_note_editor: text_editor::Content,
@@ -35,8 +69,37 @@ pub struct ProjectPage {
     tag_to_add: String,
     selected_project_file: Option<ProjectFile>,
     selected_image_project_file: Option<ProjectFile>,
+    /// Thumbnail shown when the selected file can't be opened in the viewer, found on selection
+    /// as looking it up reads the thumbnail cache and may render.
+    preview_image: Option<String>,
     source_name: String,
     source_url: String,
+    viewer_mesh: Option<Arc<ViewerMesh>>,
+    viewer_camera: Camera,
+    viewer_wireframe: bool,
+    viewer_cache: canvas::Cache,
+    /// Every collection, for the add to collection list.
+    collections: Vec<Collection>,
+    /// Collections this project is in.
+    project_collections: Vec<Collection>,
+    /// Projects marked as related, usually because they share files.
+    related_projects: Vec<Project>,
+    /// Prints of the project's files with the path printed, newest first.
+    print_jobs: Vec<(PrintJob, String)>,
+    print_log_form: Option<PrintLogForm>,
+    /// Spools the filament for a logged print can come from.
+    spools: Vec<Spool>,
+    /// Print waiting for the user to confirm it should be deleted.
+    confirm_delete_print: Option<i32>,
+    /// When the project moved between workflow states, newest first.
+    status_history: Vec<StatusChange>,
+    /// Copies of the selected file to add to the print queue, as typed.
+    queue_quantity: String,
+    queue_priority: QueuePriority,
+    /// What was last added to the print queue.
+    queue_status: Option<String>,
+    /// Where the quote was last exported to, or why it couldn't be.
+    quote_status: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -49,7 +112,7 @@ pub enum Message {
     TagToAddChanged(String),
     ProjectAddTag,
     ProjectNameUpdate(String),
-    SelectFile(ProjectFile),
+    SelectFile(Box<ProjectFile>),
     ProjectFileSave,
     SetFileDefault,
     ProjectSave,
@@ -57,13 +120,48 @@ pub enum Message {
     SourceURLUpdate(String),
     AddSource,
     OpenSource(String),
+    /// Path of the model and the loaded mesh, None when it couldn't be read.
+    ViewerMeshLoaded(String, Option<Arc<ViewerMesh>>),
+    ViewerCameraChanged(Camera),
+    ToggleWireframe,
+    ResetView,
+    AddToCollection(Collection),
+    RemoveFromCollection(i32),
+    OpenProject(Project),
+    RemoveRelated(i32),
+    LogPrint,
+    LogDateChanged(String),
+    LogPrinterChanged(String),
+    LogMaterialChanged(String),
+    LogColorChanged(String),
+    LogDurationChanged(String),
+    LogOutcomeChanged(PrintOutcome),
+    LogFailureReasonChanged(String),
+    LogNotesChanged(String),
+    LogSpoolChanged(Spool),
+    LogFilamentUsedChanged(String),
+    /// Take the print time, material and filament used from a G-code file in the project.
+    LogImportGcode(String),
+    AddLogPhotos,
+    RemoveLogPhoto(usize),
+    SaveLogPrint,
+    CancelLogPrint,
+    DeletePrintJob(i32),
+    ConfirmDeletePrintJob,
+    CancelDeletePrintJob,
+    ExportQuote,
+    StatusChanged(String),
+    ClearStatus,
+    QueueQuantityChanged(String),
+    QueuePriorityChanged(QueuePriority),
+    AddToQueue,
 }
 
 impl ProjectPage {
-    pub fn new(project: Project) -> Self {
+    pub fn new(project: Project, config: Config) -> Self {
         let db_manager = ThreeDManager::setup_db_connection();
         let mut project_page = ProjectPage {
-            stl_thumb: ThreeDManager::get_stl_thumb(),
+            config,
             db_manager,
             selected_project: project,
             project_note_editor: text_editor::Content::with_text(""),
@@ -71,17 +169,38 @@ impl ProjectPage {
             tag_to_add: "".to_string(),
             selected_project_file: None,
             selected_image_project_file: None,
+            preview_image: None,
             source_name: "".to_string(),
             source_url: "".to_string(),
+            viewer_mesh: None,
+            viewer_camera: Camera::default(),
+            viewer_wireframe: false,
+            viewer_cache: canvas::Cache::new(),
+            collections: vec![],
+            project_collections: vec![],
+            related_projects: vec![],
+            print_jobs: vec![],
+            print_log_form: None,
+            spools: vec![],
+            confirm_delete_print: None,
+            status_history: vec![],
+            queue_quantity: "1".to_string(),
+            queue_priority: QueuePriority::Normal,
+            queue_status: None,
+            quote_status: None,
         };
         project_page.project_note_editor = text_editor::Content::with_text(project_page.selected_project.notes.as_str());
         project_page.selected_project_file = project_page.selected_project.get_default_or_first_image_file();
         project_page.selected_image_project_file = project_page.selected_project.get_default_or_first_image_file();
         project_page.update_project_file_note_editor_on_selection();
+        project_page.load_collections();
+        project_page.load_related_projects();
+        project_page.load_print_jobs();
+        project_page.load_status_history();
         project_page
     }
 
-    pub fn update(&mut self, message: Message) {
+    pub fn update(&mut self, message: Message) -> Task<Message> {
         match message {
             Message::BackToMain => {}  //This should not occur as should be handled in main update function
 
@@ -135,10 +254,12 @@ impl ProjectPage {
                 self.selected_project.name = project_name;
             }
             Message::SelectFile(file) => {
-                self.selected_project_file = Some(file.clone());
+                self.selected_project_file = Some(*file.clone());
                 self.update_project_file_note_editor_on_selection();
+                self.queue_status = None;
                 if file.is_image_or_can_generate_to_image() {
-                    self.selected_image_project_file = Some(file.clone());
+                    self.selected_image_project_file = Some(*file.clone());
+                    return self.load_viewer_mesh();
                 }
             }
             Message::ProjectSave => {
@@ -161,8 +282,221 @@ impl ProjectPage {
                     Err(err) => error!("An error occurred when opening '{}': {}", source_url, err),
                 }
             }
+            Message::ViewerMeshLoaded(path, viewer_mesh) => {
+                //a slow load can finish after another file was picked
+                let Some(project_file) = self.selected_image_project_file.as_ref().filter(|file| file.path == path) else { return Task::none() };
+                match viewer_mesh {
+                    Some(viewer_mesh) => self.viewer_mesh = Some(viewer_mesh),
+                    None => self.preview_image = Some(project_file.get_image_path(&self.config)),
+                }
+                self.viewer_cache.clear();
+            }
+            Message::ViewerCameraChanged(camera) => {
+                self.viewer_camera = camera;
+                self.viewer_cache.clear();
+            }
+            Message::ToggleWireframe => {
+                self.viewer_wireframe = !self.viewer_wireframe;
+                self.viewer_cache.clear();
+            }
+            Message::ResetView => {
+                self.viewer_camera = Camera::default();
+                self.viewer_cache.clear();
+            }
+            Message::AddToCollection(collection) => {
+                self.db_manager.collection_add_project(collection.id, self.selected_project.id);
+                self.load_collections();
+            }
+            Message::RemoveFromCollection(collection_id) => {
+                self.db_manager.collection_remove_project(collection_id, self.selected_project.id);
+                self.load_collections();
+            }
+            Message::OpenProject(_) => {}  //handled in main update
+            Message::QueueQuantityChanged(quantity) => {
+                self.queue_quantity = quantity;
+            }
+            Message::QueuePriorityChanged(priority) => {
+                self.queue_priority = priority;
+            }
+            Message::AddToQueue => {
+                let Some(file) = self.selected_project_file.as_ref() else { return Task::none() };
+                let Some(quantity) = self.queue_quantity.trim().parse::<i32>().ok().filter(|quantity| *quantity > 0) else { return Task::none() };
+                self.db_manager.add_to_queue(file.id, quantity, self.queue_priority);
+                self.queue_status = Some(format!("Queued {}× {}", quantity, file_name(&file.path)));
+                self.queue_quantity = "1".to_string();
+            }
+            Message::StatusChanged(status) => {
+                self.db_manager.set_project_status(self.selected_project.id, Some(&status));
+                self.selected_project.status = Some(status);
+                self.load_status_history();
+            }
+            Message::ClearStatus => {
+                self.db_manager.set_project_status(self.selected_project.id, None);
+                self.selected_project.status = None;
+                self.load_status_history();
+            }
+            Message::RemoveRelated(project_id) => {
+                self.db_manager.unrelate_projects(self.selected_project.id, project_id);
+                self.load_related_projects();
+            }
+            Message::LogPrint => {
+                let Some(file) = self.selected_project_file.clone() else { return Task::none() };
+                self.spools = self.db_manager.get_spools();
+                let mut form = PrintLogForm {
+                    printed: "".to_string(),
+                    printer: "".to_string(),
+                    material: "".to_string(),
+                    color: "".to_string(),
+                    duration: "".to_string(),
+                    outcome: PrintOutcome::Success,
+                    failure_reason: "".to_string(),
+                    notes: "".to_string(),
+                    photos: vec![],
+                    spool: None,
+                    filament_used: "".to_string(),
+                    error: None,
+                    file: file.clone(),
+                };
+                //start from what the slicer says about sliced files
+                form.import_gcode(&file);
+                self.print_log_form = Some(form);
+            }
+            Message::LogSpoolChanged(spool) => {
+                if let Some(form) = self.print_log_form.as_mut() {
+                    form.material = spool.material.clone();
+                    form.color = spool.color.clone();
+                    form.spool = Some(spool);
+                }
+            }
+            Message::LogFilamentUsedChanged(filament_used) => {
+                if let Some(form) = self.print_log_form.as_mut() { form.filament_used = filament_used; }
+            }
+            Message::LogImportGcode(path) => {
+                let file = self.selected_project.files.iter().find(|file| file.path == path).cloned();
+                if let (Some(form), Some(file)) = (self.print_log_form.as_mut(), file) {
+                    form.import_gcode(&file);
+                }
+            }
+            Message::LogDateChanged(printed) => {
+                if let Some(form) = self.print_log_form.as_mut() { form.printed = printed; }
+            }
+            Message::LogPrinterChanged(printer) => {
+                if let Some(form) = self.print_log_form.as_mut() { form.printer = printer; }
+            }
+            Message::LogMaterialChanged(material) => {
+                if let Some(form) = self.print_log_form.as_mut() { form.material = material; }
+            }
+            Message::LogColorChanged(color) => {
+                if let Some(form) = self.print_log_form.as_mut() { form.color = color; }
+            }
+            Message::LogDurationChanged(duration) => {
+                if let Some(form) = self.print_log_form.as_mut() { form.duration = duration; }
+            }
+            Message::LogOutcomeChanged(outcome) => {
+                if let Some(form) = self.print_log_form.as_mut() { form.outcome = outcome; }
+            }
+            Message::LogFailureReasonChanged(failure_reason) => {
+                if let Some(form) = self.print_log_form.as_mut() { form.failure_reason = failure_reason; }
+            }
+            Message::LogNotesChanged(notes) => {
+                if let Some(form) = self.print_log_form.as_mut() { form.notes = notes; }
+            }
+            Message::ExportQuote => {
+                let Some(quote_file) = FileDialog::new()
+                    .add_filter("Text", &["txt"])
+                    .set_directory(&self.selected_project.path)
+                    .set_file_name(format!("{} quote.txt", self.selected_project.name))
+                    .save_file() else { return Task::none() };
+                match fs::write(&quote_file, cost::quote(&self.selected_project, &self.config)) {
+                    Ok(()) => {
+                        info!("Saved quote to {}", quote_file.display());
+                        self.quote_status = Some(format!("Saved quote to {}", quote_file.display()));
+                    }
+                    Err(err) => {
+                        error!("Unable to save quote to {}: {}", quote_file.display(), err);
+                        self.quote_status = Some(format!("Unable to save quote: {}", err));
+                    }
+                }
+            }
+            Message::AddLogPhotos => {
+                let Some(form) = self.print_log_form.as_mut() else { return Task::none() };
+                let photos = FileDialog::new()
+                    .add_filter("Images", &["png", "jpg", "jpeg", "gif"])
+                    .set_directory(&self.selected_project.path)
+                    .pick_files()
+                    .unwrap_or_default();
+                form.photos.extend(photos.iter().filter_map(|photo| photo.to_str().map(|photo| photo.to_string())));
+            }
+            Message::RemoveLogPhoto(index) => {
+                if let Some(form) = self.print_log_form.as_mut() && index < form.photos.len() {
+                    form.photos.remove(index);
+                }
+            }
+            Message::SaveLogPrint => {
+                let Some(form) = self.print_log_form.as_mut() else { return Task::none() };
+                match form.to_print_job() {
+                    Ok(print_job) => {
+                        if let Err(e) = self.db_manager.add_print_job(&print_job) {
+                            form.error = Some(format!("Could not save the print: {}", e));
+                            return Task::none();
+                        }
+                        self.print_log_form = None;
+                        self.load_print_jobs();
+                    }
+                    Err(e) => form.error = Some(e),
+                }
+            }
+            Message::CancelLogPrint => {
+                self.print_log_form = None;
+            }
+            Message::DeletePrintJob(print_job_id) => {
+                self.confirm_delete_print = Some(print_job_id);
+            }
+            Message::ConfirmDeletePrintJob => {
+                if let Some(print_job_id) = self.confirm_delete_print.take() {
+                    self.db_manager.delete_print_job(print_job_id);
+                    self.load_print_jobs();
+                }
+            }
+            Message::CancelDeletePrintJob => {
+                self.confirm_delete_print = None;
+            }
         }
-
+        Task::none()
+    }
+    /**
+     * Start loading the selected model for the 3D viewer, images and failed loads fall back to the thumbnail.
+     */
+    pub fn load_viewer_mesh(&mut self) -> Task<Message> {
+        self.viewer_mesh = None;
+        self.preview_image = None;
+        self.viewer_camera = Camera::default();
+        self.viewer_cache.clear();
+        match self.selected_image_project_file.clone() {
+            Some(project_file) if project_file.is_model_type() => {
+                let path = project_file.path.clone();
+                ViewerMesh::load_in_background(project_file.path)
+                    .map(move |viewer_mesh| Message::ViewerMeshLoaded(path.clone(), viewer_mesh))
+            }
+            Some(project_file) => {
+                self.preview_image = Some(project_file.get_image_path(&self.config));
+                Task::none()
+            }
+            None => Task::none(),
+        }
+    }
+    fn load_collections(&mut self) {
+        self.collections = self.db_manager.get_collections();
+        self.project_collections = self.db_manager.project_get_collections(self.selected_project.id);
+    }
+    fn load_print_jobs(&mut self) {
+        self.print_jobs = self.db_manager.get_project_print_jobs(self.selected_project.id);
+    }
+    fn load_status_history(&mut self) {
+        self.status_history = self.db_manager.get_project_status_history(self.selected_project.id);
+    }
+    fn load_related_projects(&mut self) {
+        self.related_projects = self.db_manager.get_related_projects(self.selected_project.id);
     }
     pub fn update_project_file_note_editor_on_selection(&mut self) {
         self.project_file_note_editor = match self.selected_project_file.clone() {
@@ -195,22 +529,65 @@ impl ProjectPage {
             )
             .push(
                 row![
-                    column![image(self.selected_image_project_file.clone().unwrap().get_image_path(self.stl_thumb.clone()))].height(Length::Fill).width(Length::Fill).height(Length::Fill),
+                    self.project_view_preview(),
                     column![
                         row![text_editor(&self.project_note_editor)
                                 .placeholder("Type something here...")
                                 .on_action(Message::ProjectNotesEdit)].height(Length::Fill).width(Length::Fill),
-                        row![self.project_view_tags()].width(Length::Fill)
+                        row![self.project_view_status()].width(Length::Fill),
+                        row![self.project_view_tags()].width(Length::Fill),
+                        row![self.project_view_collections()].width(Length::Fill),
+                        row![self.project_view_related()].width(Length::Fill)
                     ].height(Length::Fill),
                 ].width(Length::Fill).height(Length::Fill)
             )
             .push(
                 self.project_view_files()
             )
+            .push(
+                self.project_view_print_history()
+            )
             .push(
                 self.project_view_sources()
             );
-        Element::new(Container::new(main_content).width(Length::Fill).height(Length::Fill))
+        let base = Container::new(main_content).width(Length::Fill).height(Length::Fill);
+        if self.confirm_delete_print.is_some() {
+            return dialog(true, base, text("Delete this print from the history?"))
+                .title("Delete Print")
+                .push_button(iced_dialog::button("Cancel", Message::CancelDeletePrintJob))
+                .push_button(iced_dialog::button("Delete", Message::ConfirmDeletePrintJob))
+                .width(400)
+                .height(200)
+                .into();
+        }
+        dialog(self.print_log_form.is_some(), base, self.print_log_form_view())
+            .title("Log a Print")
+            .push_button(iced_dialog::button("Cancel", Message::CancelLogPrint))
+            .push_button(iced_dialog::button("Log Print", Message::SaveLogPrint))
+            .width(520)
+            .height(600)
+            .into()
+    }
+
+    fn project_view_preview(&self) -> Container<'_, Message> {
+        let content = match (&self.viewer_mesh, &self.preview_image) {
+            (Some(mesh), _) => column![
+                canvas(ModelViewer::new(mesh, self.viewer_camera, self.viewer_wireframe, &self.viewer_cache, Message::ViewerCameraChanged))
+                    .width(Length::Fill)
+                    .height(Length::Fill),
+                row![
+                    button(text(if self.viewer_wireframe { "Solid" } else { "Wireframe" })).style(ThreeDManager::rounded_button).on_press(Message::ToggleWireframe),
+                    Space::new().width(5),
+                    button(text("Reset View")).style(ThreeDManager::rounded_button).on_press(Message::ResetView),
+                ],
+            ].align_x(Horizontal::Center),
+            (None, Some(preview_image)) => column![image(preview_image.clone())],
+            (None, None) if self.selected_image_project_file.as_ref().is_some_and(|file| file.is_model_type()) => {
+                column![text("Loading model...")].align_x(Horizontal::Center)
+            }
+            (None, None) => column![],
+        };
+        Container::new(content.height(Length::Fill).width(Length::Fill)).width(Length::Fill).height(Length::Fill)
     }
 
     fn project_view_tags(&self) -> Container<'_, Message> {
@@ -221,7 +598,7 @@ impl ProjectPage {
         for tag in self.selected_project.tags.iter() {
             tag_list = tag_list.push(
                 button(text(tag.tag.to_string()))
-                    .style(ThreeDManager::button_tag_style)
+                    .style(ThreeDManager::button_tag_style(tag.color()))
                     .padding(3).on_press(Message::RemoveTag(tag.clone()))
                 );
             tag_list = tag_list.push(Space::new().width(5));
@@ -229,7 +606,7 @@ impl ProjectPage {
         content = content.push(Row::wrap(tag_list));
         let mut add_tag = row![].width(Length::Fill);
         add_tag = add_tag.push(
-            text_input("Tag to add", &self.tag_to_add.as_str()).on_input(Message::TagToAddChanged),
+            text_input("Tag to add, e.g. Games > Warhammer > Terrain", &self.tag_to_add.as_str()).on_input(Message::TagToAddChanged),
         )
             .push(
                 button(text("Add Tag")).style(ThreeDManager::rounded_button).on_press(Message::ProjectAddTag)
@@ -239,7 +616,69 @@ impl ProjectPage {
         Container::new(content).width(Length::Fill)
     }
 
+    /**
+     * The workflow state picker followed by when the project reached each state, oldest first.
+     */
+    fn project_view_status(&self) -> Container<'_, Message> {
+        let mut states = self.config.get_workflow_states();
+        if let Some(status) = &self.selected_project.status && !states.contains(status) {
+            states.push(status.clone());
+        }
+        let mut status_row = row![
+            text("Status:"),
+            pick_list(states, self.selected_project.status.clone(), Message::StatusChanged).placeholder("No status"),
+        ].spacing(5).align_y(Vertical::Center);
+        if self.selected_project.status.is_some() {
+            status_row = status_row.push(button(text("✕")).style(button::text).padding(3).on_press(Message::ClearStatus));
+        }
+        for change in self.status_history.iter().rev() {
+            let date = change.changed.split(' ').next().unwrap_or(&change.changed);
+            status_row = status_row.push(text!("→ {} {}", change.status.as_deref().unwrap_or("No status"), date).size(12));
+        }
+        Container::new(Row::wrap(status_row)).width(Length::Fill)
+    }
+    fn project_view_collections(&self) -> Container<'_, Message> {
+        let mut collection_list = row![text("Collections:")].spacing(5).align_y(Vertical::Center);
+        for collection in self.project_collections.iter() {
+            collection_list = collection_list.push(
+                button(text!("{} ✕", collection.name))
+                    .style(ThreeDManager::button_tag_style(None))
+                    .padding(3)
+                    .on_press(Message::RemoveFromCollection(collection.id))
+            );
+        }
+        let available: Vec<Collection> = self.collections.iter()
+            .filter(|collection| !self.project_collections.iter().any(|added| added.id == collection.id))
+            .cloned()
+            .collect();
+        if !available.is_empty() {
+            collection_list = collection_list.push(
+                pick_list(available, None::<Collection>, Message::AddToCollection).placeholder("Add to collection")
+            );
+        } else if self.collections.is_empty() {
+            collection_list = collection_list.push(text("Create collections on the project list to add this project to them."));
+        }
+        Container::new(Row::wrap(collection_list)).width(Length::Fill)
+    }
+
+    fn project_view_related(&self) -> Container<'_, Message> {
+        let mut related_list = row![text("Related:")].spacing(5).align_y(Vertical::Center);
+        for project in self.related_projects.iter() {
+            related_list = related_list.push(
+                row![
+                    button(text(project.name.clone())).style(ThreeDManager::rounded_button).padding(3).on_press(Message::OpenProject(project.clone())),
+                    button(text("✕")).style(button::text).padding(3).on_press(Message::RemoveRelated(project.id)),
+                ]
+            );
+        }
+        if self.related_projects.is_empty() {
+            related_list = related_list.push(text("Projects sharing files can be marked related on the duplicates page."));
+        }
+        Container::new(Row::wrap(related_list)).width(Length::Fill)
+    }
+
     fn project_view_files(&self) -> Container<'_, Message> {
+        let printers = self.config.get_printers();
         let mut file_list = column![].width(Length::Fill).height(Length::Fill);
         for file in self.selected_project.files.iter() {
             let mut strip_path= self.selected_project.clone().path;
@@ -263,8 +702,13 @@ impl ProjectPage {
 
                         style
                     })
-                    .on_press(Message::SelectFile(file.clone()))
+                    .on_press(Message::SelectFile(Box::new(file.clone())))
                     .width(Length::Fill));
+            if let Some(mesh) = &file.mesh && !printers.is_empty() {
+                let fit_count = printers.iter().filter(|printer| printer.fits(mesh)).count();
+                let fit_text = text!("fits {}/{} printers", fit_count, printers.len());
+                this_row = this_row.push(if fit_count == 0 { fit_text.style(text::danger) } else { fit_text });
+            }
             file_list = file_list.push(this_row)
         }
 
@@ -275,6 +719,21 @@ impl ProjectPage {
                 .on_press(Message::OpenDirectory(self.selected_project_file.clone().unwrap().path))
                 .style(ThreeDManager::rounded_button)
         );
+        file_actions_buttons = file_actions_buttons.push(
+            button(text("Log a Print").align_x(Horizontal::Center))
+                .on_press(Message::LogPrint)
+                .style(ThreeDManager::rounded_button)
+        );
+        let queue_quantity_valid = self.queue_quantity.trim().parse::<i32>().is_ok_and(|quantity| quantity > 0);
+        file_actions_buttons = file_actions_buttons.push(
+            row![
+                text_input("Qty", &self.queue_quantity).on_input(Message::QueueQuantityChanged).width(50),
+                pick_list(QueuePriority::ALL, Some(self.queue_priority), Message::QueuePriorityChanged),
+                button(text("Add to Queue").align_x(Horizontal::Center))
+                    .on_press_maybe(queue_quantity_valid.then_some(Message::AddToQueue))
+                    .style(ThreeDManager::rounded_button),
+            ].align_y(Vertical::Center)
+        );
         if self.selected_project_file.clone().unwrap().is_image_or_can_generate_to_image() {
             file_actions_buttons = file_actions_buttons.push(
                 button(text("Set Default").align_x(Horizontal::Center))
@@ -282,9 +741,23 @@ impl ProjectPage {
                     .style(ThreeDManager::rounded_button)
             );
         }
+        let mut project_fit = row![];
+        let fitting_printers: Vec<&PrinterProfile> = printers.iter()
+            .filter(|printer| self.selected_project.fits_printer(printer) == Some(true)).collect();
+        if !printers.is_empty() && self.selected_project.files.iter().any(|file| file.mesh.is_some()) {
+            if fitting_printers.is_empty() {
+                project_fit = project_fit.push(text("Project doesn't fit on any printer").style(text::danger));
+            } else {
+                project_fit = project_fit.push(text!("Whole project fits: {}", printer_names(&fitting_printers)));
+            }
+        }
         let file_list_container = column![
+            project_fit,
             row![scrollable(file_list)],
-            file_actions_buttons.wrap()
+            self.project_view_file_info(),
+            self.project_view_cost().wrap(),
+            file_actions_buttons.wrap(),
+            self.queue_status.as_ref().map(|queue_status| text(queue_status.clone()))
         ].width(Length::Fill).height(Length::Fill).align_x(Horizontal::Center);
         let file_note_editor  = column![
                 text("File Notes:").size(30).width(Length::Fill),
@@ -295,6 +768,181 @@ impl ProjectPage {
         ].height(Length::Fill).width(Length::Fill).align_x(Horizontal::Center);
         Container::new(row![file_list_container,file_note_editor]).width(Length::Fill).height(Length::Fill)
     }
+    /**
+     * Measurements of the selected model or slicer details of the selected G-code.
+     */
+    fn project_view_file_info(&self) -> Column<'_, Message> {
+        let Some(file) = self.selected_project_file.as_ref() else { return column![] };
+        if let Some(gcode) = file.gcode.as_ref() {
+            return self.project_view_gcode_info(gcode);
+        }
+        let mut info = column![].width(Length::Fill);
+        let Some(mesh) = file.mesh.as_ref() else { return info };
+        info = info
+            .push(text!("Size: {:.1} x {:.1} x {:.1} mm", mesh.size_x, mesh.size_y, mesh.size_z))
+            .push(text!("Triangles: {}", mesh.triangle_count))
+            .push(text!("Surface area: {:.1} cm²", mesh.surface_area / 100.0))
+            .push(text!("Volume: {:.1} cm³", mesh.volume / 1000.0))
+            .push(text(if mesh.watertight { "Watertight" } else { "Not watertight" }));
+        let printers = self.config.get_printers();
+        if !printers.is_empty() {
+            let (fits, too_small): (Vec<_>, Vec<_>) = printers.iter().partition(|printer| printer.fits(mesh));
+            if !fits.is_empty() {
+                info = info.push(text!("Fits: {}", printer_names(&fits)).style(text::success));
+            }
+            if !too_small.is_empty() {
+                info = info.push(text!("Too big for: {}", printer_names(&too_small)).style(text::danger));
+            }
+        }
+        info
+    }
+    fn project_view_gcode_info(&self, gcode: &GcodeInfo) -> Column<'_, Message> {
+        let mut details = column![].width(Length::Fill);
+        if let Some(slicer) = &gcode.slicer {
+            details = details.push(text!("Sliced with {}", slicer));
+        }
+        if let Some(print_time) = gcode.print_time {
+            details = details.push(text!("Print time: {}", format_duration(print_time)));
+        }
+        let mut filament = vec![];
+        if let Some(length) = gcode.filament_length {
+            filament.push(format!("{:.2} m", length / 1000.0));
+        }
+        if let Some(weight) = gcode.filament_weight {
+            filament.push(format!("{:.1} g", weight));
+        }
+        if let Some(filament_type) = &gcode.filament_type {
+            filament.push(filament_type.clone());
+        }
+        if !filament.is_empty() {
+            details = details.push(text!("Filament: {}", filament.join(", ")));
+        }
+        if let Some(layer_height) = gcode.layer_height {
+            details = details.push(text!("Layer height: {} mm", layer_height));
+        }
+        if let Some(temperature) = gcode.nozzle_temperature {
+            details = details.push(text!("Nozzle: {}°C", temperature));
+        }
+        if let Some(temperature) = gcode.bed_temperature {
+            details = details.push(text!("Bed: {}°C", temperature));
+        }
+        details
+    }
+    /**
+     * Estimated cost of printing the selected file and the whole project.
+     */
+    fn project_view_cost(&self) -> Row<'_, Message> {
+        let mut cost_row = row![].spacing(10).align_y(Vertical::Center);
+        if let Some(estimate) = self.selected_project_file.as_ref().and_then(|file| cost::estimate_file(file, &self.config)) {
+            cost_row = cost_row.push(text!(
+                "File: {} ({:.1} g {}, {} power)",
+                cost::format_money(estimate.total(), &self.config),
+                estimate.grams,
+                estimate.material,
+                cost::format_money(estimate.energy_cost, &self.config),
+            ));
+        }
+        let Some(total) = cost::total(&cost::project_estimates(&self.selected_project, &self.config)) else { return cost_row };
+        cost_row
+            .push(text!("Project: {} ({:.1} g)", cost::format_money(total.total(), &self.config), total.grams))
+            .push(button(text("Export Quote")).style(ThreeDManager::rounded_button).on_press(Message::ExportQuote))
+            .push(self.quote_status.as_ref().map(|quote_status| text(quote_status.clone())))
+    }
+    fn print_log_form_view(&self) -> Column<'_, Message> {
+        let Some(form) = &self.print_log_form else { return column![] };
+        let mut content = column![
+            text!("Printing {}", file_name(&form.file.path)),
+            row![
+                text_input("Date, today when empty (YYYY-MM-DD)", &form.printed).on_input(Message::LogDateChanged),
+                pick_list(PrintOutcome::ALL, Some(form.outcome), Message::LogOutcomeChanged),
+            ].spacing(5),
+            text_input("Printer", &form.printer).on_input(Message::LogPrinterChanged),
+            row![
+                text_input("Material, e.g. PLA", &form.material).on_input(Message::LogMaterialChanged),
+                text_input("Colour", &form.color).on_input(Message::LogColorChanged),
+            ].spacing(5),
+            text_input("Print time, e.g. 2h 30m", &form.duration).on_input(Message::LogDurationChanged),
+        ].spacing(5);
+        let gcode_files: Vec<String> = self.selected_project.files.iter()
+            .filter(|file| file.gcode.is_some())
+            .map(|file| file.path.clone())
+            .collect();
+        if !gcode_files.is_empty() {
+            content = content.push(
+                pick_list(gcode_files, None::<String>, Message::LogImportGcode).placeholder("Use the estimate from a G-code file")
+            );
+        }
+        content = content.push(
+            row![
+                pick_list(self.spools.clone(), form.spool.clone(), Message::LogSpoolChanged).placeholder("Spool"),
+                text_input("Filament used (g)", &form.filament_used).on_input(Message::LogFilamentUsedChanged),
+            ].spacing(5)
+        );
+        if self.spools.is_empty() {
+            content = content.push(text("Add spools on the filament page to keep track of what's left."));
+        }
+        if form.outcome != PrintOutcome::Success {
+            content = content.push(text_input("What went wrong?", &form.failure_reason).on_input(Message::LogFailureReasonChanged));
+        }
+        content = content.push(text_input("Notes", &form.notes).on_input(Message::LogNotesChanged));
+        let mut photos = row![button(text("Add Photos")).style(ThreeDManager::rounded_button).on_press(Message::AddLogPhotos)].spacing(5);
+        for (index, photo) in form.photos.iter().enumerate() {
+            photos = photos.push(button(text!("{} ✕", file_name(photo))).style(button::text).padding(3).on_press(Message::RemoveLogPhoto(index)));
+        }
+        content = content.push(photos.wrap());
+        content.push(form.error.as_ref().map(|error| text(error.clone()).style(text::danger)))
+    }
+
+    /**
+     * Every print of the project's files, newest first.
+     */
+    fn project_view_print_history(&self) -> Container<'_, Message> {
+        let mut history = column![].spacing(5).width(Length::Fill);
+        for (print_job, path) in self.print_jobs.iter() {
+            let outcome = text(print_job.outcome.to_string());
+            let details: Vec<String> = [
+                print_job.printer.clone(),
+                Some([print_job.material.clone(), print_job.color.clone()].into_iter().flatten().collect::<Vec<_>>().join(" ")).filter(|material| !material.is_empty()),
+                print_job.duration.map(format_duration),
+                print_job.filament_used.map(|grams| format!("{:.1} g", grams)),
+            ].into_iter().flatten().collect();
+            let mut entry = column![
+                row![
+                    text(print_job.printed.clone()),
+                    text(file_name(path)).width(Length::Fill),
+                    match print_job.outcome {
+                        PrintOutcome::Success => outcome.style(text::success),
+                        PrintOutcome::Partial => outcome,
+                        PrintOutcome::Failed => outcome.style(text::danger),
+                    },
+                    text(details.join(", ")),
+                    button(text("✕")).style(button::text).padding(3).on_press(Message::DeletePrintJob(print_job.id)),
+                ].spacing(10).align_y(Vertical::Center),
+            ];
+            if let Some(failure_reason) = print_job.failure_reason.as_ref().filter(|reason| !reason.is_empty()) {
+                entry = entry.push(text(failure_reason.clone()).style(text::danger));
+            }
+            if let Some(notes) = print_job.notes.as_ref().filter(|notes| !notes.is_empty()) {
+                entry = entry.push(text(notes.clone()));
+            }
+            if !print_job.photos.is_empty() {
+                let mut photos = row![].spacing(5);
+                for photo in print_job.photos.iter() {
+                    photos = photos.push(button(image(photo.clone()).height(80)).style(button::text).padding(0).on_press(Message::OpenDirectory(photo.clone())));
+                }
+                entry = entry.push(photos.wrap());
+            }
+            history = history.push(entry);
+        }
+        if self.print_jobs.is_empty() {
+            history = history.push(text("Nothing printed yet, select a file and log a print."));
+        }
+        Container::new(column![
+            text("Print History:").size(30),
+            scrollable(history).height(160),
+        ]).width(Length::Fill)
+    }
+
     fn project_view_sources(&self) -> Container<'_, Message> {
         let mut content = column![].width(Length::Fill);
         let mut main_content = row![].width(Length::Fill);
@@ -314,4 +962,64 @@ impl ProjectPage {
         content = content.push(add_content);
         Container::new(content).width(Length::Fill)
     }
-}
\ No newline at end of file
+}
+
+impl PrintLogForm {
+    /**
+     * The print as typed, or what needs fixing first.
+     */
+    fn to_print_job(&self) -> Result<PrintJob, String> {
+        let printed = self.printed.trim().to_string();
+        if !printed.is_empty() && !PrintJob::is_valid_date(&printed) {
+            return Err("The date should look like 2026-01-31".to_string());
+        }
+        let duration = match self.duration.trim() {
+            "" => None,
+            duration => Some(parse_duration(duration).ok_or("The print time should look like 2h 30m")?),
+        };
+        let filament_used = match self.filament_used.trim() {
+            "" => None,
+            grams => Some(grams.parse::<f64>().ok().filter(|grams| grams.is_finite() && *grams >= 0.0).ok_or("Filament used should be a number of grams")?),
+        };
+        let optional = |value: &str| Some(value.trim().to_string()).filter(|value| !value.is_empty());
+        Ok(PrintJob {
+            id: 0,
+            project_file_id: self.file.id,
+            printed,
+            printer: optional(&self.printer),
+            material: optional(&self.material),
+            color: optional(&self.color),
+            duration,
+            outcome: self.outcome,
+            failure_reason: if self.outcome == PrintOutcome::Success { None } else { optional(&self.failure_reason) },
+            notes: optional(&self.notes),
+            photos: self.photos.clone(),
+            spool_id: self.spool.as_ref().map(|spool| spool.id),
+            filament_used,
+        })
+    }
+
+    /**
+     * Fill in the print time, material and filament used from a sliced file, other files are ignored.
+     */
+    fn import_gcode(&mut self, file: &ProjectFile) {
+        let Some(gcode) = file.gcode.as_ref() else { return };
+        if let Some(print_time) = gcode.print_time {
+            self.duration = format_duration(print_time);
+        }
+        if let Some(filament_type) = gcode.filament_type.clone() {
+            self.material = filament_type;
+        }
+        if let Some(filament_weight) = gcode.filament_weight {
+            self.filament_used = format!("{:.1}", filament_weight);
+        }
+    }
+}
+
+fn file_name(path: &str) -> String {
+    std::path::Path::new(path).file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or(path.to_string())
+}
+
+fn printer_names(printers: &[&PrinterProfile]) -> String {
+    printers.iter().map(|printer| printer.name.as_str()).collect::<Vec<_>>().join(", ")
+}
@@ -15,15 +15,59 @@
  * along with this program.  If not, see <http://www.gnu.org/licenses/>.
  */
 
+use std::collections::HashMap;
 use iced::alignment::Horizontal;
-use iced::{Element, Length};
-use iced::widget::{row, Column, Container, button, Space, column, text, pick_list};
+use iced::{Element, Length, Task};
+use iced::widget::{row, Column, Row, Container, button, Space, column, text, pick_list, text_input, checkbox, scrollable};
 use iced::Theme;
 use rfd::FileDialog;
 use super::super::config::Config;
+use log::error;
+use crate::models::material::Material;
+use crate::models::printer_profile::PrinterProfile;
+use crate::models::project::Project;
+use crate::models::project_tag::ProjectTag;
+use crate::models::scan_settings::ScanSettings;
+use crate::models::tag_rule::{RuleTarget, TagRule};
+use crate::renderer::{Shading, MAX_THUMBNAIL_SIZE};
+use crate::scanner::Scanner;
+use crate::thumbnail_cache::ThumbnailCache;
 
 pub struct SettingsPage {
     config :Config,
+    thumbnail_size: String,
+    thumbnail_yaw: String,
+    thumbnail_pitch: String,
+    thumbnail_workers: String,
+    thumbnail_cache_size: String,
+    thumbnail_cache_usage: u64,
+    printer_name: String,
+    printer_x: String,
+    printer_y: String,
+    printer_z: String,
+    low_spool_grams: String,
+    electricity_rate: String,
+    printer_wattage: String,
+    /// Model fill typed as a percentage.
+    model_fill: String,
+    material_name: String,
+    material_price: String,
+    material_density: String,
+    /// Comma separated workflow states.
+    workflow_states: String,
+    rule_target: RuleTarget,
+    rule_pattern: String,
+    /// Comma separated tags for the rule being added.
+    rule_tags: String,
+    /// Tags the rules would add to each project, shown before they're applied.
+    rule_preview: Option<Vec<(Project, Vec<String>)>>,
+    rule_status: Option<String>,
+    /// A preview or apply of the tag rules is running.
+    rules_busy: bool,
+    /// Project depth typed for each print path.
+    scan_depths: HashMap<String, String>,
+    /// Comma separated marker files typed for each print path.
+    scan_markers: HashMap<String, String>,
 }
 
 #[derive(Debug, Clone)]
@@ -33,16 +77,89 @@ pub enum Message {
     BackToMain(bool),
     SettingsAddProjectDirectory,
     SettingsRemoveProjectDirectory(String),
+    /// Print path and the typed depth.
+    ScanDepthChanged(String, String),
+    ScanModelDirsToggled(String, bool),
+    /// Print path and the typed marker files.
+    ScanMarkersChanged(String, String),
+    ThumbnailSizeChanged(String),
+    ThumbnailYawChanged(String),
+    ThumbnailPitchChanged(String),
+    ThumbnailShadingChanged(Shading),
+    ThumbnailWorkersChanged(String),
+    ThumbnailCacheToggled(bool),
+    ThumbnailCacheSizeChanged(String),
+    EmbeddedThumbnailsToggled(bool),
+    ClearThumbnailCache,
+    PrinterNameChanged(String),
+    PrinterXChanged(String),
+    PrinterYChanged(String),
+    PrinterZChanged(String),
+    AddPrinter,
+    EditPrinter(PrinterProfile),
+    RemovePrinter(String),
+    LowSpoolGramsChanged(String),
+    ElectricityRateChanged(String),
+    PrinterWattageChanged(String),
+    ModelFillChanged(String),
+    CurrencyChanged(String),
+    DefaultMaterialChanged(String),
+    MaterialNameChanged(String),
+    MaterialPriceChanged(String),
+    MaterialDensityChanged(String),
+    AddMaterial,
+    EditMaterial(Material),
+    RemoveMaterial(String),
+    WorkflowStatesChanged(String),
+    RuleTargetChanged(RuleTarget),
+    RulePatternChanged(String),
+    RuleTagsChanged(String),
+    AddTagRule,
+    EditTagRule(usize),
+    RemoveTagRule(usize),
+    PreviewTagRules,
+    TagRulesPreviewed(Vec<(Project, Vec<String>)>),
+    ApplyTagRules,
+    /// How many tags were added to how many projects.
+    TagRulesApplied(usize, usize),
+    ClosePreview,
 }
 
 impl SettingsPage{
     pub fn new(config :Config) -> SettingsPage {
+        let render_settings = config.render_settings();
         SettingsPage {
-            config
+            config: config.clone(),
+            thumbnail_size: render_settings.size.to_string(),
+            thumbnail_yaw: render_settings.camera.yaw.to_string(),
+            thumbnail_pitch: render_settings.camera.pitch.to_string(),
+            thumbnail_workers: config.get_thumbnail_workers().to_string(),
+            thumbnail_cache_size: config.get_thumbnail_cache_size_mb().to_string(),
+            thumbnail_cache_usage: ThumbnailCache::usage_bytes(),
+            printer_name: "".to_string(),
+            printer_x: "".to_string(),
+            printer_y: "".to_string(),
+            printer_z: "".to_string(),
+            low_spool_grams: config.get_low_spool_grams().to_string(),
+            electricity_rate: config.get_electricity_rate().to_string(),
+            printer_wattage: config.get_printer_wattage().to_string(),
+            model_fill: (config.get_model_fill() * 100.0).to_string(),
+            material_name: "".to_string(),
+            material_price: "".to_string(),
+            material_density: "".to_string(),
+            workflow_states: config.get_workflow_states().join(", "),
+            rule_target: RuleTarget::Directory,
+            rule_pattern: "".to_string(),
+            rule_tags: "".to_string(),
+            rule_preview: None,
+            rule_status: None,
+            rules_busy: false,
+            scan_depths: HashMap::new(),
+            scan_markers: HashMap::new(),
         }
     }
 
-    pub fn update(&mut self, message : Message) {
+    pub fn update(&mut self, message : Message) -> Task<Message> {
         match message {
             Message::SetTheme(theme) => {
                 self.config.set_theme(theme);
@@ -57,7 +174,216 @@ impl SettingsPage{
             Message::SettingsRemoveProjectDirectory(path) => {
                 self.config.remove_print_path(path.as_str());
             }
+            Message::ScanDepthChanged(path, depth) => {
+                if let Ok(value) = depth.parse::<usize>() {
+                    let mut settings = self.config.get_scan_settings(&path);
+                    settings.depth = value;
+                    self.config.set_scan_settings(settings);
+                }
+                self.scan_depths.insert(path, depth);
+            }
+            Message::ScanModelDirsToggled(path, enabled) => {
+                let mut settings = self.config.get_scan_settings(&path);
+                settings.model_dirs = enabled;
+                self.config.set_scan_settings(settings);
+            }
+            Message::ScanMarkersChanged(path, markers) => {
+                let mut settings = self.config.get_scan_settings(&path);
+                settings.marker_files = markers.split(',').map(|marker| marker.trim().to_string()).filter(|marker| !marker.is_empty()).collect();
+                self.config.set_scan_settings(settings);
+                self.scan_markers.insert(path, markers);
+            }
+            //only store values that parse, the text box keeps whatever is typed
+            Message::ThumbnailSizeChanged(size) => {
+                if let Ok(value) = size.parse::<u32>() && value > 0 {
+                    self.config.thumbnail_size = Some(value.min(MAX_THUMBNAIL_SIZE));
+                }
+                self.thumbnail_size = size;
+            }
+            Message::ThumbnailYawChanged(yaw) => {
+                if let Ok(value) = yaw.parse::<f32>() {
+                    self.config.thumbnail_yaw = Some(value);
+                }
+                self.thumbnail_yaw = yaw;
+            }
+            Message::ThumbnailPitchChanged(pitch) => {
+                if let Ok(value) = pitch.parse::<f32>() {
+                    self.config.thumbnail_pitch = Some(value);
+                }
+                self.thumbnail_pitch = pitch;
+            }
+            Message::ThumbnailShadingChanged(shading) => {
+                self.config.thumbnail_shading = Some(shading);
+            }
+            Message::ThumbnailWorkersChanged(workers) => {
+                if let Ok(value) = workers.parse::<usize>() && value > 0 {
+                    self.config.thumbnail_workers = Some(value);
+                }
+                self.thumbnail_workers = workers;
+            }
+            Message::ThumbnailCacheToggled(enabled) => {
+                self.config.thumbnail_cache = Some(enabled);
+            }
+            Message::EmbeddedThumbnailsToggled(enabled) => {
+                self.config.embedded_thumbnails = Some(enabled);
+            }
+            Message::ThumbnailCacheSizeChanged(size) => {
+                if let Ok(value) = size.parse::<u64>() {
+                    self.config.thumbnail_cache_size_mb = Some(value);
+                }
+                self.thumbnail_cache_size = size;
+            }
+            Message::ClearThumbnailCache => {
+                if let Err(err) = ThumbnailCache::new(&self.config).clear() {
+                    error!("Could not clear the thumbnail cache: {}", err);
+                }
+                self.thumbnail_cache_usage = ThumbnailCache::usage_bytes();
+            }
+            Message::PrinterNameChanged(name) => {
+                self.printer_name = name;
+            }
+            Message::PrinterXChanged(x) => {
+                self.printer_x = x;
+            }
+            Message::PrinterYChanged(y) => {
+                self.printer_y = y;
+            }
+            Message::PrinterZChanged(z) => {
+                self.printer_z = z;
+            }
+            Message::AddPrinter => {
+                if let Some(printer) = self.printer_to_add() {
+                    self.config.add_printer(printer);
+                    self.printer_name = "".to_string();
+                    self.printer_x = "".to_string();
+                    self.printer_y = "".to_string();
+                    self.printer_z = "".to_string();
+                }
+            }
+            Message::EditPrinter(printer) => {
+                self.printer_name = printer.name;
+                self.printer_x = printer.x.to_string();
+                self.printer_y = printer.y.to_string();
+                self.printer_z = printer.z.to_string();
+            }
+            Message::RemovePrinter(name) => {
+                self.config.remove_printer(name.as_str());
+            }
+            Message::LowSpoolGramsChanged(grams) => {
+                if let Ok(value) = grams.parse::<f64>() && value >= 0.0 {
+                    self.config.low_spool_grams = Some(value);
+                }
+                self.low_spool_grams = grams;
+            }
+            Message::ElectricityRateChanged(rate) => {
+                if let Ok(value) = rate.parse::<f64>() && value >= 0.0 {
+                    self.config.electricity_rate = Some(value);
+                }
+                self.electricity_rate = rate;
+            }
+            Message::PrinterWattageChanged(wattage) => {
+                if let Ok(value) = wattage.parse::<f64>() && value >= 0.0 {
+                    self.config.printer_wattage = Some(value);
+                }
+                self.printer_wattage = wattage;
+            }
+            Message::ModelFillChanged(fill) => {
+                if let Ok(value) = fill.parse::<f64>() && (0.0..=100.0).contains(&value) {
+                    self.config.model_fill = Some(value / 100.0);
+                }
+                self.model_fill = fill;
+            }
+            Message::CurrencyChanged(currency) => {
+                self.config.currency = Some(currency);
+            }
+            Message::DefaultMaterialChanged(name) => {
+                self.config.default_material = Some(name);
+            }
+            Message::MaterialNameChanged(name) => {
+                self.material_name = name;
+            }
+            Message::MaterialPriceChanged(price) => {
+                self.material_price = price;
+            }
+            Message::MaterialDensityChanged(density) => {
+                self.material_density = density;
+            }
+            Message::AddMaterial => {
+                if let Some(material) = self.material_to_add() {
+                    self.config.add_material(material);
+                    self.material_name = "".to_string();
+                    self.material_price = "".to_string();
+                    self.material_density = "".to_string();
+                }
+            }
+            Message::EditMaterial(material) => {
+                self.material_name = material.name;
+                self.material_price = material.price_per_kg.to_string();
+                self.material_density = material.density.to_string();
+            }
+            Message::RemoveMaterial(name) => {
+                self.config.remove_material(name.as_str());
+            }
+            Message::WorkflowStatesChanged(states) => {
+                let workflow_states: Vec<String> = states.split(',').map(|state| state.trim().to_string()).filter(|state| !state.is_empty()).collect();
+                self.config.workflow_states = (!workflow_states.is_empty()).then_some(workflow_states);
+                self.workflow_states = states;
+            }
+            Message::RuleTargetChanged(target) => {
+                self.rule_target = target;
+            }
+            Message::RulePatternChanged(pattern) => {
+                self.rule_pattern = pattern;
+            }
+            Message::RuleTagsChanged(tags) => {
+                self.rule_tags = tags;
+            }
+            Message::AddTagRule => {
+                if let Ok(rule) = self.rule_to_add() {
+                    self.config.add_tag_rule(rule);
+                    self.rule_pattern = "".to_string();
+                    self.rule_tags = "".to_string();
+                }
+            }
+            Message::EditTagRule(index) => {
+                if let Some(rule) = self.config.get_tag_rules().get(index) {
+                    self.rule_target = rule.target;
+                    self.rule_pattern = rule.pattern.clone();
+                    self.rule_tags = rule.tags.join(", ");
+                    self.config.remove_tag_rule(index);
+                }
+            }
+            Message::RemoveTagRule(index) => {
+                self.config.remove_tag_rule(index);
+            }
+            Message::PreviewTagRules => {
+                self.rule_status = Some("Previewing rules...".to_string());
+                self.rule_preview = None;
+                self.rules_busy = true;
+                return Scanner::preview_tag_rules_in_background(self.config.clone()).map(Message::TagRulesPreviewed);
+            }
+            Message::TagRulesPreviewed(preview) => {
+                self.rules_busy = false;
+                self.rule_status = None;
+                self.rule_preview = Some(preview);
+            }
+            Message::ApplyTagRules => {
+                if let Some(preview) = self.rule_preview.take() {
+                    self.rule_status = Some("Applying rules...".to_string());
+                    self.rules_busy = true;
+                    return Scanner::apply_tag_rules_in_background(self.config.clone(), preview)
+                        .map(|(added, projects)| Message::TagRulesApplied(added, projects));
+                }
+            }
+            Message::TagRulesApplied(added, projects) => {
+                self.rules_busy = false;
+                self.rule_status = Some(format!("Added {} tags to {} projects.", added, projects));
+            }
+            Message::ClosePreview => {
+                self.rule_preview = None;
+            }
         }
+        Task::none()
     }
 
     pub fn view(&self) -> Element<'_, Message> {
@@ -68,7 +394,10 @@ impl SettingsPage{
                     row![
                         button("Delete").on_press(Message::SettingsRemoveProjectDirectory(directory.clone())),
                         Space::new().width(20),
-                        column![text(directory.to_string())].width(Length::Fill),
+                        column![
+                            text(directory.to_string()),
+                            self.scan_settings_row(&self.config.get_scan_settings(&directory)),
+                        ].width(Length::Fill),
                     ]
                 );
             }
@@ -85,9 +414,17 @@ impl SettingsPage{
                 iced::widget::column![
                     text("Project Directories:").size(40),
                     project_dirs_widget,
+                    text("Projects are also found in directories holding a marker file or, when ticked, model files. Put globs in a .3dmignore file to skip paths."),
                     row![column![button("Add Directory").on_press(Message::SettingsAddProjectDirectory)].width(Length::Fill).align_x(Horizontal::Right)].width(Length::Fill)
                 ].width(Length::Fill)
-            ).width(Length::Fill).height(Length::Fill);
+            )
+            .push(self.thumbnail_settings())
+            .push(self.printer_settings())
+            .push(self.filament_settings())
+            .push(self.cost_settings())
+            .push(self.workflow_settings())
+            .push(self.tag_rule_settings())
+            .width(Length::Fill);
         let action_content = iced::widget::column![
                 row![
                     button("Cancel").on_press(Message::BackToMain(false)),
@@ -95,7 +432,286 @@ impl SettingsPage{
                     button("Save").on_press(Message::BackToMain(true)),
                 ]
             ].width(Length::Fill).align_x(Horizontal::Right);
-        Element::new(Container::new(iced::widget::column![main_content,action_content]).width(Length::Fill).height(Length::Fill))
+        Element::new(Container::new(iced::widget::column![scrollable(main_content).height(Length::Fill),action_content]).width(Length::Fill).height(Length::Fill))
+    }
+
+    fn scan_settings_row(&self, settings: &ScanSettings) -> Row<'_, Message> {
+        let path = settings.print_path.clone();
+        let depth = self.scan_depths.get(&path).cloned().unwrap_or(settings.depth.to_string());
+        let markers = self.scan_markers.get(&path).cloned().unwrap_or(settings.marker_files.join(", "));
+        let depth_path = path.clone();
+        let model_dirs_path = path.clone();
+        row![
+            text("Project depth:"),
+            Space::new().width(10),
+            text_input("1", &depth).on_input(move |depth| Message::ScanDepthChanged(depth_path.clone(), depth)).width(60),
+            Space::new().width(30),
+            checkbox(settings.model_dirs)
+                .label("Directories with model files are projects")
+                .on_toggle(move |enabled| Message::ScanModelDirsToggled(model_dirs_path.clone(), enabled)),
+            Space::new().width(30),
+            text("Marker files:"),
+            Space::new().width(10),
+            text_input(".3dproject", &markers).on_input(move |markers| Message::ScanMarkersChanged(path.clone(), markers)).width(200),
+            Space::new().width(30),
+            settings.problem().map(|problem| text(problem).style(text::danger)),
+            (settings.uses_contents() && settings.depth > 0).then(|| {
+                button("Use depth 0").on_press(Message::ScanDepthChanged(settings.print_path.clone(), "0".to_string()))
+            }),
+        ].align_y(iced::Alignment::Center)
+    }
+
+    fn thumbnail_settings(&self) -> Column<'_, Message> {
+        column![
+            text("Thumbnails:").size(40),
+            row![
+                text("Size (px):"),
+                Space::new().width(10),
+                text_input("256", &self.thumbnail_size).on_input(Message::ThumbnailSizeChanged).width(80),
+                self.thumbnail_size.parse::<u32>().is_ok_and(|size| size > MAX_THUMBNAIL_SIZE)
+                    .then(|| text!(" Using the largest size, {}", MAX_THUMBNAIL_SIZE).style(text::danger)),
+                Space::new().width(30),
+                text("Camera yaw:"),
+                Space::new().width(10),
+                text_input("30", &self.thumbnail_yaw).on_input(Message::ThumbnailYawChanged).width(80),
+                Space::new().width(30),
+                text("Camera pitch:"),
+                Space::new().width(10),
+                text_input("30", &self.thumbnail_pitch).on_input(Message::ThumbnailPitchChanged).width(80),
+                Space::new().width(30),
+                text("Shading:"),
+                Space::new().width(10),
+                pick_list(Shading::ALL, Some(self.config.render_settings().shading), Message::ThumbnailShadingChanged),
+                Space::new().width(30),
+                text("Parallel renders:"),
+                Space::new().width(10),
+                text_input("4", &self.thumbnail_workers).on_input(Message::ThumbnailWorkersChanged).width(60),
+            ],
+            row![
+                checkbox(self.config.use_thumbnail_cache())
+                    .label("Keep thumbnails in a central cache instead of each project folder")
+                    .on_toggle(Message::ThumbnailCacheToggled),
+                Space::new().width(30),
+                text("Cache limit (MB):"),
+                Space::new().width(10),
+                text_input("500", &self.thumbnail_cache_size).on_input(Message::ThumbnailCacheSizeChanged).width(80),
+                Space::new().width(30),
+                text!("Using {:.1} MB", self.thumbnail_cache_usage as f64 / (1024.0 * 1024.0)),
+                Space::new().width(10),
+                button("Clear Cache").on_press(Message::ClearThumbnailCache),
+            ],
+            checkbox(self.config.use_embedded_thumbnails())
+                .label("Use the thumbnail saved in 3MF files by the slicer instead of rendering")
+                .on_toggle(Message::EmbeddedThumbnailsToggled),
+            text("Changes apply to thumbnails generated after saving."),
+        ].width(Length::Fill)
+    }
+
+    fn printer_settings(&self) -> Column<'_, Message> {
+        let mut printers = column![text("Printers:").size(40)].width(Length::Fill);
+        for printer in self.config.get_printers() {
+            printers = printers.push(
+                row![
+                    button("Delete").on_press(Message::RemovePrinter(printer.name.clone())),
+                    Space::new().width(10),
+                    button("Edit").on_press(Message::EditPrinter(printer.clone())),
+                    Space::new().width(20),
+                    text!("{} ({} x {} x {} mm)", printer.name, printer.x, printer.y, printer.z),
+                ]
+            );
+        }
+        let mut add_button = button("Add Printer");
+        if self.printer_to_add().is_some() {
+            add_button = add_button.on_press(Message::AddPrinter);
+        }
+        printers.push(
+            row![
+                text_input("Printer name", &self.printer_name).on_input(Message::PrinterNameChanged).width(200),
+                Space::new().width(10),
+                text_input("X", &self.printer_x).on_input(Message::PrinterXChanged).width(70),
+                Space::new().width(10),
+                text_input("Y", &self.printer_y).on_input(Message::PrinterYChanged).width(70),
+                Space::new().width(10),
+                text_input("Z", &self.printer_z).on_input(Message::PrinterZChanged).width(70),
+                Space::new().width(10),
+                text("mm"),
+                Space::new().width(30),
+                add_button,
+            ]
+        )
+    }
+    fn filament_settings(&self) -> Column<'_, Message> {
+        column![
+            text("Filament:").size(40),
+            row![
+                text("Warn when a spool has less than (g):"),
+                Space::new().width(10),
+                text_input("100", &self.low_spool_grams).on_input(Message::LowSpoolGramsChanged).width(80),
+            ],
+        ].width(Length::Fill)
+    }
+    fn cost_settings(&self) -> Column<'_, Message> {
+        let currency = self.config.get_currency();
+        let material_names: Vec<String> = self.config.get_materials().into_iter().map(|material| material.name).collect();
+        let mut costs = column![
+            text("Costs:").size(40),
+            text("Used to estimate what printing a project costs. Models that aren't sliced yet are estimated from their volume."),
+            row![
+                text("Currency:"),
+                Space::new().width(10),
+                text_input("$", &currency).on_input(Message::CurrencyChanged).width(60),
+                Space::new().width(30),
+                text("Electricity per kWh:"),
+                Space::new().width(10),
+                text_input("0.30", &self.electricity_rate).on_input(Message::ElectricityRateChanged).width(80),
+                Space::new().width(30),
+                text("Printer power (W):"),
+                Space::new().width(10),
+                text_input("120", &self.printer_wattage).on_input(Message::PrinterWattageChanged).width(80),
+            ],
+            row![
+                text("Default material:"),
+                Space::new().width(10),
+                pick_list(material_names, Some(self.config.get_default_material()), Message::DefaultMaterialChanged),
+                Space::new().width(30),
+                text("Model fill (%):"),
+                Space::new().width(10),
+                text_input("35", &self.model_fill).on_input(Message::ModelFillChanged).width(80),
+            ],
+        ].width(Length::Fill);
+        for material in self.config.get_materials() {
+            costs = costs.push(
+                row![
+                    button("Delete").on_press(Message::RemoveMaterial(material.name.clone())),
+                    Space::new().width(10),
+                    button("Edit").on_press(Message::EditMaterial(material.clone())),
+                    Space::new().width(20),
+                    text!("{} {}{:.2}/kg, {} g/cm³", material.name, currency, material.price_per_kg, material.density),
+                ]
+            );
+        }
+        let mut add_button = button("Add Material");
+        if self.material_to_add().is_some() {
+            add_button = add_button.on_press(Message::AddMaterial);
+        }
+        costs.push(
+            row![
+                text_input("Material name", &self.material_name).on_input(Message::MaterialNameChanged).width(200),
+                Space::new().width(10),
+                text_input("Price per kg", &self.material_price).on_input(Message::MaterialPriceChanged).width(110),
+                Space::new().width(10),
+                text_input("Density", &self.material_density).on_input(Message::MaterialDensityChanged).width(90),
+                Space::new().width(10),
+                text("g/cm³"),
+                Space::new().width(30),
+                add_button,
+            ]
+        )
+    }
+    fn workflow_settings(&self) -> Column<'_, Message> {
+        column![
+            text("Workflow:").size(40),
+            text("The statuses a project moves through in order, these are the columns of the board. \
+                Projects keep their status when it's renamed here and show under Other until moved."),
+            text_input("Idea, To Print, Printing, Done, Failed", &self.workflow_states).on_input(Message::WorkflowStatesChanged),
+        ].width(Length::Fill)
+    }
+    fn tag_rule_settings(&self) -> Column<'_, Message> {
+        let mut rules = column![
+            text("Tag Rules:").size(40),
+            text("Projects found while scanning get these tags when their directory or files match. \
+                Patterns are regular expressions, $1 in a tag is replaced by the first group in brackets."),
+        ].width(Length::Fill);
+        for (index, rule) in self.config.get_tag_rules().iter().enumerate() {
+            rules = rules.push(
+                row![
+                    button("Delete").on_press(Message::RemoveTagRule(index)),
+                    Space::new().width(10),
+                    button("Edit").on_press(Message::EditTagRule(index)),
+                    Space::new().width(20),
+                    text!("{} matches {} → {}", rule.target, rule.pattern, rule.tags.join(", ")),
+                ]
+            );
+        }
+        let rule_to_add = self.rule_to_add();
+        let mut add_button = button("Add Rule");
+        if rule_to_add.is_ok() {
+            add_button = add_button.on_press(Message::AddTagRule);
+        }
+        rules = rules.push(
+            row![
+                pick_list(RuleTarget::ALL, Some(self.rule_target), Message::RuleTargetChanged),
+                Space::new().width(10),
+                text_input("Pattern, e.g. /Minis/(\\w+)/", &self.rule_pattern).on_input(Message::RulePatternChanged).width(300),
+                Space::new().width(10),
+                text_input("Tags, e.g. Minis > $1, supportless", &self.rule_tags).on_input(Message::RuleTagsChanged).width(300),
+                Space::new().width(30),
+                add_button,
+            ]
+        );
+        if let Err(error) = rule_to_add && !self.rule_pattern.is_empty() {
+            rules = rules.push(text(error).style(text::danger));
+        }
+        rules = rules.push(
+            row![
+                button("Preview Rules on Library").on_press_maybe((!self.rules_busy).then_some(Message::PreviewTagRules)),
+                Space::new().width(20),
+                text(self.rule_status.clone().unwrap_or_default()),
+            ]
+        );
+        if let Some(preview) = &self.rule_preview {
+            let tag_count: usize = preview.iter().map(|(_, tags)| tags.len()).sum();
+            rules = rules.push(text!("{} tags would be added to {} projects:", tag_count, preview.len()));
+            for (project, tags) in preview.iter() {
+                rules = rules.push(text!("{}: {}", project.name, tags.join(", ")));
+            }
+            let mut apply_button = button("Apply");
+            if tag_count > 0 {
+                apply_button = apply_button.on_press(Message::ApplyTagRules);
+            }
+            rules = rules.push(
+                row![
+                    button("Close").on_press(Message::ClosePreview),
+                    Space::new().width(10),
+                    apply_button,
+                ]
+            );
+        }
+        rules
+    }
+    /**
+     * The rule described by the input boxes, or what's wrong with them.
+     */
+    fn rule_to_add(&self) -> Result<TagRule, String> {
+        let tags: Vec<String> = self.rule_tags.split(',').map(ProjectTag::normalize).filter(|tag| !tag.is_empty()).collect();
+        let rule = TagRule { target: self.rule_target, pattern: self.rule_pattern.trim().to_string(), tags };
+        if rule.pattern.is_empty() {
+            return Err("The pattern is empty".to_string());
+        }
+        rule.regex().map_err(|e| format!("The pattern isn't a valid regular expression: {}", e))?;
+        if rule.tags.is_empty() {
+            return Err("Add at least one tag".to_string());
+        }
+        Ok(rule)
+    }
+    /**
+     * The printer described by the input boxes, if they're all filled in with valid sizes.
+     */
+    fn printer_to_add(&self) -> Option<PrinterProfile> {
+        let name = self.printer_name.trim();
+        if name.is_empty() { return None }
+        let x = self.printer_x.trim().parse::<f64>().ok().filter(|value| *value > 0.0)?;
+        let y = self.printer_y.trim().parse::<f64>().ok().filter(|value| *value > 0.0)?;
+        let z = self.printer_z.trim().parse::<f64>().ok().filter(|value| *value > 0.0)?;
+        Some(PrinterProfile { name: name.to_string(), x, y, z })
+    }
+
+    fn material_to_add(&self) -> Option<Material> {
+        let name = self.material_name.trim();
+        if name.is_empty() { return None }
+        let price_per_kg = self.material_price.trim().parse::<f64>().ok().filter(|value| *value >= 0.0)?;
+        let density = self.material_density.trim().parse::<f64>().ok().filter(|value| *value > 0.0)?;
+        Some(Material::new(name, price_per_kg, density))
     }
 
     pub fn add_project_directory (&mut self) {
@@ -0,0 +1,250 @@
+/*
+ * Copyright (c) 2025-2026 Mitch Tuck
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+use iced::{Element, Length};
+use iced::alignment::Vertical;
+use iced::widget::{button, column, container, progress_bar, row, scrollable, text, text_input, Column, Container};
+use iced_dialog::dialog;
+use log::{error, info};
+use crate::ThreeDManager;
+use crate::config::Config;
+use crate::db_manager::DbManager;
+use crate::models::spool::Spool;
+
+pub struct SpoolsPage {
+    config: Config,
+    db_manager: DbManager,
+    spools: Vec<Spool>,
+    /// Id of the spool being edited, 0 for a new one.
+    editing: i32,
+    brand: String,
+    material: String,
+    color: String,
+    diameter: String,
+    initial_weight: String,
+    remaining_weight: String,
+    price: String,
+    error: Option<String>,
+    confirm_delete: Option<Spool>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    BackToMain,
+    SelectSpool(Spool),
+    NewSpool,
+    BrandChanged(String),
+    MaterialChanged(String),
+    ColorChanged(String),
+    DiameterChanged(String),
+    InitialWeightChanged(String),
+    RemainingWeightChanged(String),
+    PriceChanged(String),
+    Save,
+    Delete,
+    ConfirmDelete,
+    CancelDelete,
+}
+
+impl SpoolsPage {
+    pub fn new(config: Config) -> SpoolsPage {
+        let mut spools_page = SpoolsPage {
+            config,
+            db_manager: ThreeDManager::setup_db_connection(),
+            spools: vec![],
+            editing: 0,
+            brand: "".to_string(),
+            material: "".to_string(),
+            color: "".to_string(),
+            diameter: "".to_string(),
+            initial_weight: "".to_string(),
+            remaining_weight: "".to_string(),
+            price: "".to_string(),
+            error: None,
+            confirm_delete: None,
+        };
+        spools_page.spools = spools_page.db_manager.get_spools();
+        spools_page.update(Message::NewSpool);
+        spools_page
+    }
+
+    pub fn update(&mut self, message: Message) {
+        match message {
+            Message::BackToMain => {} //handled in main update
+            Message::SelectSpool(spool) => {
+                self.editing = spool.id;
+                self.brand = spool.brand;
+                self.material = spool.material;
+                self.color = spool.color;
+                self.diameter = spool.diameter.to_string();
+                self.initial_weight = spool.initial_weight.to_string();
+                self.remaining_weight = format!("{:.1}", spool.remaining_weight);
+                self.price = spool.price.map(|price| price.to_string()).unwrap_or_default();
+                self.error = None;
+            }
+            Message::NewSpool => {
+                self.editing = 0;
+                self.brand = "".to_string();
+                self.material = "PLA".to_string();
+                self.color = "".to_string();
+                self.diameter = "1.75".to_string();
+                self.initial_weight = "1000".to_string();
+                self.remaining_weight = "".to_string();
+                self.price = "".to_string();
+                self.error = None;
+            }
+            Message::BrandChanged(brand) => self.brand = brand,
+            Message::MaterialChanged(material) => self.material = material,
+            Message::ColorChanged(color) => self.color = color,
+            Message::DiameterChanged(diameter) => self.diameter = diameter,
+            Message::InitialWeightChanged(initial_weight) => self.initial_weight = initial_weight,
+            Message::RemainingWeightChanged(remaining_weight) => self.remaining_weight = remaining_weight,
+            Message::PriceChanged(price) => self.price = price,
+            Message::Save => {
+                match self.spool_to_save() {
+                    Ok(spool) => {
+                        info!("Saving spool {}", spool);
+                        self.editing = self.db_manager.save_spool(&spool);
+                        self.spools = self.db_manager.get_spools();
+                        self.error = None;
+                    }
+                    Err(e) => self.error = Some(e),
+                }
+            }
+            Message::Delete => {
+                self.confirm_delete = self.spools.iter().find(|spool| spool.id == self.editing).cloned();
+            }
+            Message::ConfirmDelete => {
+                if let Some(spool) = self.confirm_delete.take() {
+                    info!("Deleting spool {}", spool);
+                    if let Err(e) = self.db_manager.delete_spool(spool.id) {
+                        error!("Could not delete spool {}: {}", spool, e);
+                        return;
+                    }
+                    self.spools = self.db_manager.get_spools();
+                    self.update(Message::NewSpool);
+                }
+            }
+            Message::CancelDelete => {
+                self.confirm_delete = None;
+            }
+        }
+    }
+
+    /**
+     * The spool as typed, a new spool without a remaining weight is full.
+     */
+    fn spool_to_save(&self) -> Result<Spool, String> {
+        let number = |value: &str, name: &str| value.trim().parse::<f64>().ok().filter(|number| *number >= 0.0)
+            .ok_or(format!("{} should be a number", name));
+        if self.brand.trim().is_empty() || self.material.trim().is_empty() {
+            return Err("A spool needs a brand and material".to_string());
+        }
+        let initial_weight = number(&self.initial_weight, "Weight")?;
+        let remaining_weight = match self.remaining_weight.trim() {
+            "" => initial_weight,
+            remaining_weight => number(remaining_weight, "Remaining weight")?,
+        };
+        let price = match self.price.trim() {
+            "" => None,
+            price => Some(number(price, "Price")?),
+        };
+        Ok(Spool {
+            id: self.editing,
+            brand: self.brand.trim().to_string(),
+            material: self.material.trim().to_string(),
+            color: self.color.trim().to_string(),
+            diameter: number(&self.diameter, "Diameter")?,
+            initial_weight,
+            remaining_weight,
+            price,
+        })
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let header = row![
+            text("Filament").size(50).width(Length::Fill),
+            button(text("New Spool")).style(ThreeDManager::rounded_button).on_press(Message::NewSpool),
+            button(text("Back")).style(ThreeDManager::rounded_button).on_press(Message::BackToMain),
+        ].spacing(10).align_y(Vertical::Center);
+
+        let low_grams = self.config.get_low_spool_grams();
+        let mut spool_list = column![].width(Length::Fill).spacing(5);
+        for spool in self.spools.iter() {
+            let remaining = text!("{:.0} / {:.0} g", spool.remaining_weight, spool.initial_weight);
+            spool_list = spool_list.push(
+                button(
+                    column![
+                        row![
+                            text!("{} {} {}", spool.brand, spool.material, spool.color).width(Length::Fill),
+                            if spool.remaining_weight < low_grams { remaining.style(text::danger) } else { remaining },
+                        ].spacing(10),
+                        progress_bar(0.0..=1.0, spool.remaining_fraction()).girth(6),
+                    ].spacing(3)
+                )
+                    .style(if spool.id == self.editing { button::primary } else { button::text })
+                    .on_press(Message::SelectSpool(spool.clone()))
+                    .width(Length::Fill)
+            );
+        }
+        if self.spools.is_empty() {
+            spool_list = spool_list.push(text("Add your spools to have prints take the filament they used off them."));
+        }
+
+        let main_content = column![
+            header,
+            row![
+                scrollable(spool_list).width(Length::FillPortion(1)).height(Length::Fill),
+                container(self.spool_editor()).width(Length::FillPortion(1)).padding([0, 20]),
+            ].height(Length::Fill),
+        ].spacing(10);
+
+        let confirm_content = match &self.confirm_delete {
+            Some(spool) => format!("Delete {}? Prints made with it keep how much filament they used.", spool),
+            None => "".to_string(),
+        };
+        dialog(self.confirm_delete.is_some(), Container::new(main_content).width(Length::Fill).height(Length::Fill), text(confirm_content))
+            .title("Delete Spool")
+            .push_button(iced_dialog::button("Cancel", Message::CancelDelete))
+            .push_button(iced_dialog::button("Delete", Message::ConfirmDelete))
+            .width(400)
+            .height(234)
+            .into()
+    }
+
+    fn spool_editor(&self) -> Column<'_, Message> {
+        column![
+            text(if self.editing == 0 { "New spool" } else { "Edit spool" }).size(30),
+            text_input("Brand", &self.brand).on_input(Message::BrandChanged),
+            row![
+                text_input("Material, e.g. PLA", &self.material).on_input(Message::MaterialChanged),
+                text_input("Colour", &self.color).on_input(Message::ColorChanged),
+            ].spacing(5),
+            row![text("Diameter (mm):").width(160), text_input("1.75", &self.diameter).on_input(Message::DiameterChanged)].align_y(Vertical::Center),
+            row![text("Weight (g):").width(160), text_input("1000", &self.initial_weight).on_input(Message::InitialWeightChanged)].align_y(Vertical::Center),
+            row![text("Left (g):").width(160), text_input("Full", &self.remaining_weight).on_input(Message::RemainingWeightChanged)].align_y(Vertical::Center),
+            row![text("Price:").width(160), text_input("Price of the whole spool", &self.price).on_input(Message::PriceChanged)].align_y(Vertical::Center),
+        ]
+            .spacing(10)
+            .push(self.error.as_ref().map(|error| text(error.clone()).style(text::danger)))
+            .push(
+                row![
+                    button(text("Save Spool")).style(ThreeDManager::rounded_button).on_press(Message::Save),
+                    button(text("Delete")).style(button::danger).on_press_maybe((self.editing > 0).then_some(Message::Delete)),
+                ].spacing(10)
+            )
+    }
+}
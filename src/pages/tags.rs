@@ -0,0 +1,412 @@
+/*
+ * Copyright (c) 2025-2026 Mitch Tuck
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+use iced::{Color, Element, Length};
+use iced::alignment::Vertical;
+use iced::widget::{button, column, container, pick_list, row, scrollable, text, text_input, Column, Container, Row, Space};
+use iced_dialog::dialog;
+use log::info;
+use crate::ThreeDManager;
+use crate::db_manager::DbManager;
+use crate::models::project_tag::ProjectTag;
+
+/// Colours offered for tags, any other hex colour can be typed in.
+const TAG_COLORS: [&str; 10] = [
+    "#d64545", "#e8833a", "#e0c341", "#5aa454", "#2f9e8f",
+    "#3a7bd5", "#7b5cd6", "#c2569b", "#8a6a4f", "#6b7280",
+];
+
+/// A change that can't be undone, waiting for the user to confirm it.
+#[derive(Debug, Clone)]
+enum TagAction {
+    Delete(ProjectTag, usize),
+    Merge(ProjectTag, ProjectTag),
+    DeleteUnused(usize),
+}
+
+pub struct TagsPage {
+    db_manager: DbManager,
+    /// Every tag with how many projects use it.
+    tags: Vec<(ProjectTag, usize)>,
+    selected_tag: Option<ProjectTag>,
+    rename_to: String,
+    merge_into: Option<ProjectTag>,
+    color_input: String,
+    /// Other names that resolve to the selected tag.
+    aliases: Vec<String>,
+    alias_to_add: String,
+    error: Option<String>,
+    confirm: Option<TagAction>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    BackToMain,
+    SelectTag(ProjectTag),
+    RenameChanged(String),
+    Rename,
+    MergeIntoChanged(ProjectTag),
+    Merge,
+    Delete,
+    DeleteUnused,
+    SetColor(Option<String>),
+    ColorInputChanged(String),
+    SaveColorInput,
+    ParentChanged(Option<ProjectTag>),
+    AliasToAddChanged(String),
+    AddAlias,
+    RemoveAlias(String),
+    Confirm,
+    Cancel,
+}
+
+impl TagsPage {
+    pub fn new() -> TagsPage {
+        let mut tags_page = TagsPage {
+            db_manager: ThreeDManager::setup_db_connection(),
+            tags: vec![],
+            selected_tag: None,
+            rename_to: "".to_string(),
+            merge_into: None,
+            color_input: "".to_string(),
+            aliases: vec![],
+            alias_to_add: "".to_string(),
+            error: None,
+            confirm: None,
+        };
+        tags_page.load_tags();
+        tags_page
+    }
+
+    pub fn update(&mut self, message: Message) {
+        match message {
+            Message::BackToMain => {} //handled in main update
+            Message::SelectTag(tag) => {
+                self.rename_to = tag.tag.clone();
+                self.color_input = tag.color.clone().unwrap_or_default();
+                self.merge_into = None;
+                self.error = None;
+                self.alias_to_add = "".to_string();
+                self.aliases = self.db_manager.get_tag_aliases(tag.id);
+                self.selected_tag = Some(tag);
+            }
+            Message::RenameChanged(name) => {
+                self.rename_to = name;
+                self.error = None;
+            }
+            Message::Rename => {
+                let Some(tag) = self.selected_tag.clone() else { return };
+                let name = ProjectTag::normalize(&self.rename_to);
+                if name.is_empty() {
+                    return;
+                }
+                //the same name is fine under different parents
+                if let Some((existing, _)) = self.tags.iter()
+                    .find(|(other, _)| other.id != tag.id && other.parent_id == tag.parent_id && other.tag.eq_ignore_ascii_case(&name)) {
+                    self.error = Some(format!("{} already exists, merge into it instead", existing.tag));
+                    return;
+                }
+                //aliases are looked up before names so a tag named after another tag's alias could never be found,
+                //one of its own aliases just becomes its name
+                if let Some(alias_tag) = self.db_manager.get_alias_tag(&name) {
+                    if alias_tag.id != tag.id {
+                        self.error = Some(format!("{} is an alias of {}, merge into it instead", name, alias_tag.tag));
+                        return;
+                    }
+                    self.db_manager.remove_tag_alias(&name);
+                }
+                info!("Renaming tag {} to {}", tag.tag, name);
+                self.db_manager.rename_tag(tag.id, &name);
+                self.load_tags();
+            }
+            Message::MergeIntoChanged(tag) => {
+                self.merge_into = Some(tag);
+            }
+            Message::Merge => {
+                if let (Some(tag), Some(into)) = (self.selected_tag.clone(), self.merge_into.clone()) {
+                    self.confirm = Some(TagAction::Merge(tag, into));
+                }
+            }
+            Message::Delete => {
+                if let Some(tag) = self.selected_tag.clone() {
+                    let count = self.usage(tag.id);
+                    self.confirm = Some(TagAction::Delete(tag, count));
+                }
+            }
+            Message::DeleteUnused => {
+                self.confirm = Some(TagAction::DeleteUnused(self.unused_count()));
+            }
+            Message::SetColor(color) => {
+                let Some(tag) = &self.selected_tag else { return };
+                self.color_input = color.clone().unwrap_or_default();
+                self.db_manager.set_tag_color(tag.id, color);
+                self.load_tags();
+            }
+            Message::ColorInputChanged(color) => {
+                self.color_input = color;
+            }
+            Message::SaveColorInput => {
+                let Some(tag) = &self.selected_tag else { return };
+                let color = self.color_input.trim();
+                if color.is_empty() {
+                    return self.update(Message::SetColor(None));
+                }
+                let Ok(parsed) = color.parse::<Color>() else {
+                    self.error = Some(format!("{} isn't a colour, use one like #3a7bd5", color));
+                    return;
+                };
+                self.error = None;
+                self.db_manager.set_tag_color(tag.id, Some(parsed.to_string()));
+                self.load_tags();
+            }
+            Message::ParentChanged(parent) => {
+                let Some(tag) = &self.selected_tag else { return };
+                if !self.db_manager.set_tag_parent(tag.id, parent.as_ref().map(|parent| parent.id)) {
+                    self.error = Some(format!("{} can't go inside one of its own children", tag.tag));
+                    return;
+                }
+                self.error = None;
+                self.load_tags();
+            }
+            Message::AliasToAddChanged(alias) => {
+                self.alias_to_add = alias;
+                self.error = None;
+            }
+            Message::AddAlias => {
+                let Some(tag) = &self.selected_tag else { return };
+                let alias = ProjectTag::normalize(&self.alias_to_add);
+                if alias.is_empty() {
+                    return;
+                }
+                match self.db_manager.add_tag_alias(tag.id, &alias) {
+                    Ok(()) => {
+                        self.alias_to_add = "".to_string();
+                        self.aliases = self.db_manager.get_tag_aliases(tag.id);
+                    }
+                    Err(e) => self.error = Some(e),
+                }
+            }
+            Message::RemoveAlias(alias) => {
+                let Some(tag) = &self.selected_tag else { return };
+                self.db_manager.remove_tag_alias(&alias);
+                self.aliases = self.db_manager.get_tag_aliases(tag.id);
+            }
+            Message::Confirm => {
+                match self.confirm.take() {
+                    Some(TagAction::Delete(tag, _)) => {
+                        info!("Deleting tag {}", tag.tag);
+                        self.db_manager.delete_tag(tag.id);
+                        self.selected_tag = None;
+                    }
+                    Some(TagAction::Merge(tag, into)) => {
+                        info!("Merging tag {} into {}", tag.tag, into.tag);
+                        if self.db_manager.merge_tag(tag.id, into.id) {
+                            self.selected_tag = None;
+                        } else {
+                            self.error = Some(format!("Could not merge {} into {}", tag.tag, into.tag));
+                        }
+                    }
+                    Some(TagAction::DeleteUnused(_)) => {
+                        let deleted = self.db_manager.delete_unused_tags();
+                        info!("Deleted {} unused tags", deleted);
+                    }
+                    None => {}
+                }
+                self.load_tags();
+            }
+            Message::Cancel => {
+                self.confirm = None;
+            }
+        }
+    }
+
+    /**
+     * Reload the tags and keep the selection pointing at the stored version of the selected tag.
+     */
+    fn load_tags(&mut self) {
+        self.tags = self.db_manager.get_tag_usage();
+        self.selected_tag = self.selected_tag.as_ref()
+            .and_then(|selected| self.tags.iter().find(|(tag, _)| tag.id == selected.id))
+            .map(|(tag, _)| tag.clone());
+        match &self.selected_tag {
+            Some(tag) => self.aliases = self.db_manager.get_tag_aliases(tag.id),
+            None => {
+                self.rename_to = "".to_string();
+                self.color_input = "".to_string();
+                self.merge_into = None;
+                self.aliases = vec![];
+            }
+        }
+    }
+
+    fn usage(&self, tag_id: i32) -> usize {
+        self.tags.iter().find(|(tag, _)| tag.id == tag_id).map(|(_, count)| *count).unwrap_or(0)
+    }
+
+    /**
+     * Tags without projects or children, the ones delete unused would remove.
+     */
+    fn unused_count(&self) -> usize {
+        self.tags.iter()
+            .filter(|(tag, count)| *count == 0 && !self.tags.iter().any(|(child, _)| child.parent_id == Some(tag.id)))
+            .count()
+    }
+
+    fn tag_list(&self) -> Vec<ProjectTag> {
+        self.tags.iter().map(|(tag, _)| tag.clone()).collect()
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let unused = self.unused_count();
+        let header = row![
+            text("Tags").size(50).width(Length::Fill),
+            button(text!("Delete Unused Tags ({})", unused))
+                .style(button::danger)
+                .on_press_maybe((unused > 0).then_some(Message::DeleteUnused)),
+            button(text("Back")).style(ThreeDManager::rounded_button).on_press(Message::BackToMain),
+        ].spacing(10).align_y(Vertical::Center);
+
+        let mut tag_list = column![].width(Length::Fill).spacing(5);
+        for (tag, depth) in ProjectTag::tree_order(&self.tag_list()) {
+            let count = self.usage(tag.id);
+            let selected = self.selected_tag.as_ref().is_some_and(|selected| selected.id == tag.id);
+            tag_list = tag_list.push(
+                button(
+                    row![
+                        Space::new().width(depth as f32 * 20.0),
+                        container(text(tag.tag.clone()).width(Length::Fill)).width(Length::Fill),
+                        text(if count == 1 { "1 project".to_string() } else { format!("{} projects", count) }),
+                    ].spacing(10)
+                )
+                    .style(if selected { button::primary } else { button::text })
+                    .on_press(Message::SelectTag(tag.clone()))
+                    .width(Length::Fill)
+            );
+        }
+        if self.tags.is_empty() {
+            tag_list = tag_list.push(text("Tags added to projects show up here."));
+        }
+
+        let main_content = column![
+            header,
+            row![
+                scrollable(tag_list).width(Length::FillPortion(1)).height(Length::Fill),
+                Space::new().width(20),
+                self.tag_editor().width(Length::FillPortion(1)),
+            ].height(Length::Fill),
+        ].spacing(10);
+
+        let confirm_content = match &self.confirm {
+            Some(TagAction::Delete(tag, count)) => format!("Delete {} and take it off {} projects?", tag.tag, count),
+            Some(TagAction::Merge(tag, into)) => format!("Move every project tagged {} over to {} and delete {}? {} will become an alias of {}.", tag.tag, into.tag, tag.tag, tag.tag, into.tag),
+            Some(TagAction::DeleteUnused(count)) => format!("Delete {} tags that no project uses?", count),
+            None => "".to_string(),
+        };
+        dialog(self.confirm.is_some(), Container::new(main_content).width(Length::Fill).height(Length::Fill), text(confirm_content))
+            .title("Change Tags")
+            .push_button(iced_dialog::button("Cancel", Message::Cancel))
+            .push_button(iced_dialog::button("OK", Message::Confirm))
+            .width(400)
+            .height(234)
+            .into()
+    }
+
+    /**
+     * Rename, recolour, merge or delete the selected tag.
+     */
+    fn tag_editor(&self) -> Column<'_, Message> {
+        let Some(tag) = &self.selected_tag else {
+            return column![
+                text("Pick a tag to rename, recolour, merge or delete it."),
+                text("Type a path like Games > Warhammer > Terrain when adding a tag to a project to file it under others."),
+            ].spacing(10);
+        };
+        let mut editor = column![
+            button(text(tag.tag.clone())).style(ThreeDManager::button_tag_style(tag.color())).padding(3),
+            text("Name:"),
+            row![
+                text_input("Tag name", &self.rename_to).on_input(Message::RenameChanged).on_submit(Message::Rename),
+                button(text("Rename"))
+                    .style(ThreeDManager::rounded_button)
+                    .on_press_maybe((self.rename_to.trim() != tag.tag && !self.rename_to.trim().is_empty()).then_some(Message::Rename)),
+            ].spacing(10),
+        ].spacing(10);
+        editor = editor.push(self.error.as_ref().map(|error| text(error.clone()).style(text::danger)));
+
+        let mut swatches = row![].spacing(5);
+        for color in TAG_COLORS {
+            let swatch = color.parse::<Color>().unwrap();
+            swatches = swatches.push(
+                button(Space::new().width(20).height(20))
+                    .style(move |_, _| button::Style { background: Some(swatch.into()), ..Default::default() })
+                    .on_press(Message::SetColor(Some(color.to_string())))
+            );
+        }
+        swatches = swatches.push(button(text("None")).style(button::text).on_press(Message::SetColor(None)));
+        editor = editor
+            .push(text("Colour:"))
+            .push(Row::wrap(swatches))
+            .push(text_input("#3a7bd5", &self.color_input).on_input(Message::ColorInputChanged).on_submit(Message::SaveColorInput).width(120));
+
+        let tags = self.tag_list();
+        let descendants = tag.descendant_ids(&tags);
+        let parents: Vec<ProjectTag> = tags.iter()
+            .filter(|other| other.id != tag.id && !descendants.contains(&other.id))
+            .cloned()
+            .collect();
+        let parent = tags.iter().find(|other| Some(other.id) == tag.parent_id).cloned();
+        editor = editor
+            .push(text("Inside:"))
+            .push(
+                row![
+                    pick_list(parents, parent, |parent| Message::ParentChanged(Some(parent))).placeholder("Top level"),
+                    button(text("Move to Top Level"))
+                        .style(ThreeDManager::rounded_button)
+                        .on_press_maybe(tag.parent_id.map(|_| Message::ParentChanged(None))),
+                ].spacing(10)
+            );
+
+        let mut alias_list = row![].spacing(5);
+        for alias in self.aliases.iter() {
+            alias_list = alias_list.push(
+                button(text!("{} ✕", alias)).style(ThreeDManager::button_tag_style(None)).padding(3).on_press(Message::RemoveAlias(alias.clone()))
+            );
+        }
+        editor = editor
+            .push(text("Also known as:"))
+            .push(Row::wrap(alias_list))
+            .push(
+                row![
+                    text_input("Alias, e.g. dnd", &self.alias_to_add).on_input(Message::AliasToAddChanged).on_submit(Message::AddAlias),
+                    button(text("Add Alias"))
+                        .style(ThreeDManager::rounded_button)
+                        .on_press_maybe((!self.alias_to_add.trim().is_empty()).then_some(Message::AddAlias)),
+                ].spacing(10)
+            );
+
+        //merging into a tag below this one would leave it as its own parent
+        let others: Vec<ProjectTag> = tags.iter().filter(|other| other.id != tag.id && !descendants.contains(&other.id)).cloned().collect();
+        editor
+            .push(text("Merge into:"))
+            .push(
+                row![
+                    pick_list(others, self.merge_into.clone(), Message::MergeIntoChanged).placeholder("Tag to keep"),
+                    button(text("Merge")).style(ThreeDManager::rounded_button).on_press_maybe(self.merge_into.as_ref().map(|_| Message::Merge)),
+                ].spacing(10)
+            )
+            .push(button(text!("Delete {}", tag.tag)).style(button::danger).on_press(Message::Delete))
+    }
+}
@@ -0,0 +1,326 @@
+/*
+ * Copyright (c) 2025-2026 Mitch Tuck
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::io;
+use serde::{Serialize, Deserialize};
+use crate::mesh::{Mesh, Vec3};
+
+/// Fraction of the image the model's bounding sphere fills at zoom 1.
+const FILL_FACTOR: f32 = 0.9;
+const AMBIENT: f32 = 0.25;
+const BASE_COLOR: [f32; 3] = [0.36, 0.56, 0.86];
+/// Cosine of the crease angle (40 degrees) past which smooth shading keeps an edge hard.
+const CREASE_COS: f32 = 0.766;
+/// Largest thumbnail edge in pixels, thumbnails are rendered at twice this.
+pub const MAX_THUMBNAIL_SIZE: u32 = 2048;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Shading {
+    Flat,
+    Smooth,
+}
+
+impl Shading {
+    pub const ALL: [Shading; 2] = [Shading::Flat, Shading::Smooth];
+}
+
+impl Display for Shading {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Shading::Flat => write!(f, "Flat"),
+            Shading::Smooth => write!(f, "Smooth"),
+        }
+    }
+}
+
+/// Orbit camera around the center of the model.  Angles are in degrees, yaw spins around the
+/// Z (up) axis and pitch is the elevation above the XY plane.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Camera {
+    pub yaw: f32,
+    pub pitch: f32,
+    pub zoom: f32,
+    pub pan_x: f32,
+    pub pan_y: f32,
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Camera {
+            yaw: 30.0,
+            pitch: 30.0,
+            zoom: 1.0,
+            pan_x: 0.0,
+            pan_y: 0.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RenderSettings {
+    pub size: u32,
+    pub camera: Camera,
+    pub shading: Shading,
+}
+
+impl Default for RenderSettings {
+    fn default() -> Self {
+        RenderSettings {
+            size: 256,
+            camera: Camera::default(),
+            shading: Shading::Smooth,
+        }
+    }
+}
+
+/// The face normals touching each vertex position, for smooth shading.  Worth keeping with a
+/// mesh that is drawn over and over, like in the viewer, as building it walks every triangle.
+#[derive(Debug, Clone, Default)]
+pub struct VertexNormals {
+    normals: HashMap<[u32; 3], Vec<Vec3>>,
+}
+
+impl VertexNormals {
+    /**
+     * Collect the face normals of the mesh.  They are left unnormalized so larger faces weigh
+     * more when averaged.
+     */
+    pub fn new(mesh: &Mesh) -> VertexNormals {
+        let mut normals: HashMap<[u32; 3], Vec<Vec3>> = HashMap::new();
+        for triangle in mesh.triangles.iter() {
+            let normal = (triangle[1] - triangle[0]).cross(triangle[2] - triangle[0]);
+            for vertex in triangle.iter() {
+                normals.entry(vertex_key(vertex)).or_default().push(normal);
+            }
+        }
+        VertexNormals { normals }
+    }
+
+    /**
+     * Average only the neighbouring faces within the crease angle so hard edges stay sharp.
+     */
+    fn for_vertex(&self, vertex: &Vec3, face_normal: Vec3) -> Vec3 {
+        let face_unit = face_normal.normalize();
+        let mut sum = Vec3::default();
+        for normal in self.normals.get(&vertex_key(vertex)).into_iter().flatten() {
+            if normal.normalize().dot(face_unit) >= CREASE_COS {
+                sum = sum + *normal;
+            }
+        }
+        if sum.length() == 0.0 { face_unit } else { sum.normalize() }
+    }
+}
+
+/// Triangle already transformed to screen space: x and y are pixels, z is depth (larger is farther).
+struct ScreenTriangle {
+    points: [Vec3; 3],
+    normals: [Vec3; 3],
+}
+
+/**
+ * Write an existing image, such as a slicer thumbnail, as a png no bigger than the thumbnail size.
+ */
+pub fn save_image_to_file(image: &image::RgbaImage, settings: &RenderSettings, file: &str) -> io::Result<()> {
+    let size = settings.size.max(1);
+    let (width, height) = image.dimensions();
+    let result = if width > size || height > size {
+        let scale = size as f32 / width.max(height) as f32;
+        let new_width = ((width as f32 * scale).round() as u32).max(1);
+        let new_height = ((height as f32 * scale).round() as u32).max(1);
+        image::imageops::resize(image, new_width, new_height, image::imageops::FilterType::Triangle)
+            .save_with_format(file, image::ImageFormat::Png)
+    } else {
+        image.save_with_format(file, image::ImageFormat::Png)
+    };
+    result.map_err(io::Error::other)
+}
+
+/**
+ * Render the mesh and write it as a png with a transparent background.
+ * Renders at twice the size and scales down to smooth the edges.
+ */
+pub fn render_to_file(mesh: &Mesh, settings: &RenderSettings, file: &str) -> io::Result<()> {
+    let size = settings.size.clamp(1, MAX_THUMBNAIL_SIZE);
+    let normals = match settings.shading {
+        Shading::Smooth => Some(VertexNormals::new(mesh)),
+        Shading::Flat => None,
+    };
+    let pixels = render_rgba(mesh, normals.as_ref(), size * 2, size * 2, &settings.camera, false)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "thumbnail size is too large"))?;
+    let pixels = downsample(&pixels, size * 2, size * 2);
+    image::save_buffer(file, &pixels, size, size, image::ExtendedColorType::Rgba8)
+        .map_err(io::Error::other)
+}
+
+/**
+ * Rasterize the mesh into a width * height RGBA buffer, smooth shaded when the vertex normals
+ * are given and flat otherwise.  Wireframe draws every edge without hidden line removal.
+ * None when the buffer size doesn't fit in memory addresses.
+ */
+pub fn render_rgba(mesh: &Mesh, vertex_normals: Option<&VertexNormals>, width: u32, height: u32, camera: &Camera, wireframe: bool) -> Option<Vec<u8>> {
+    let pixel_count = (width as usize).checked_mul(height as usize)?;
+    let mut pixels = vec![0u8; pixel_count.checked_mul(4)?];
+    let mut depth = vec![f32::INFINITY; pixel_count];
+    let Some((min, max)) = mesh.bounds() else { return Some(pixels) };
+    let center = (min + max) * 0.5;
+    let radius = ((max - min).length() * 0.5).max(f32::EPSILON);
+    let scale = width.min(height) as f32 / (2.0 * radius) * FILL_FACTOR * camera.zoom;
+
+    let (sin_yaw, cos_yaw) = camera.yaw.to_radians().sin_cos();
+    let (sin_pitch, cos_pitch) = camera.pitch.to_radians().sin_cos();
+    // world (Z up) -> view space: x right, y up, z away from the viewer
+    let to_view = |point: Vec3| -> Vec3 {
+        let x = point.x * cos_yaw - point.y * sin_yaw;
+        let y = point.x * sin_yaw + point.y * cos_yaw;
+        Vec3::new(x, point.z * cos_pitch + y * sin_pitch, y * cos_pitch - point.z * sin_pitch)
+    };
+    let to_screen = |point: Vec3| -> Vec3 {
+        let view = to_view(point - center);
+        Vec3::new(
+            width as f32 * 0.5 + view.x * scale + camera.pan_x,
+            height as f32 * 0.5 - view.y * scale + camera.pan_y,
+            view.z,
+        )
+    };
+
+    if wireframe {
+        for triangle in mesh.triangles.iter() {
+            let points = triangle.map(to_screen);
+            if !points.iter().all(Vec3::is_finite) {
+                continue;
+            }
+            for (start, end) in [(0, 1), (1, 2), (2, 0)] {
+                draw_line(points[start], points[end], width, height, &mut pixels);
+            }
+        }
+        return Some(pixels);
+    }
+
+    // light comes from over the viewer's left shoulder
+    let light = Vec3::new(-0.4, 0.6, -1.0).normalize();
+
+    for triangle in mesh.triangles.iter() {
+        let world_normal = (triangle[1] - triangle[0]).cross(triangle[2] - triangle[0]);
+        let face_normal = to_view(world_normal).normalize();
+        let normals = match vertex_normals {
+            Some(normals) => triangle.map(|vertex| to_view(normals.for_vertex(&vertex, world_normal))),
+            None => [face_normal; 3],
+        };
+        let screen = ScreenTriangle {
+            points: triangle.map(to_screen),
+            normals,
+        };
+        // the edge tests in rasterize pass every pixel for NaN, so one broken triangle would fill the image
+        if !screen.points.iter().all(Vec3::is_finite) {
+            continue;
+        }
+        rasterize(&screen, face_normal, light, width, height, &mut pixels, &mut depth);
+    }
+    Some(pixels)
+}
+
+fn vertex_key(vertex: &Vec3) -> [u32; 3] {
+    [vertex.x.to_bits(), vertex.y.to_bits(), vertex.z.to_bits()]
+}
+
+fn rasterize(triangle: &ScreenTriangle, face_normal: Vec3, light: Vec3, width: u32, height: u32, pixels: &mut [u8], depth: &mut [f32]) {
+    let [a, b, c] = triangle.points;
+    let area = edge(a, b, c);
+    if area.abs() < f32::EPSILON {
+        return;
+    }
+    let min_x = a.x.min(b.x).min(c.x).floor().max(0.0) as u32;
+    let max_x = a.x.max(b.x).max(c.x).ceil().min(width as f32 - 1.0);
+    let min_y = a.y.min(b.y).min(c.y).floor().max(0.0) as u32;
+    let max_y = a.y.max(b.y).max(c.y).ceil().min(height as f32 - 1.0);
+    if max_x < 0.0 || max_y < 0.0 {
+        return;
+    }
+    // STL winding is unreliable, so light whichever side faces the camera
+    let flip = if face_normal.z > 0.0 { -1.0 } else { 1.0 };
+    for y in min_y..=max_y as u32 {
+        for x in min_x..=max_x as u32 {
+            let point = Vec3::new(x as f32 + 0.5, y as f32 + 0.5, 0.0);
+            let w0 = edge(b, c, point) / area;
+            let w1 = edge(c, a, point) / area;
+            let w2 = edge(a, b, point) / area;
+            if w0 < 0.0 || w1 < 0.0 || w2 < 0.0 {
+                continue;
+            }
+            let z = a.z * w0 + b.z * w1 + c.z * w2;
+            let index = y as usize * width as usize + x as usize;
+            if z >= depth[index] {
+                continue;
+            }
+            depth[index] = z;
+            let normal = (triangle.normals[0] * w0 + triangle.normals[1] * w1 + triangle.normals[2] * w2).normalize() * flip;
+            let intensity = AMBIENT + (1.0 - AMBIENT) * normal.dot(light).max(0.0);
+            for channel in 0..3 {
+                pixels[index * 4 + channel] = (BASE_COLOR[channel] * intensity * 255.0).min(255.0) as u8;
+            }
+            pixels[index * 4 + 3] = 255;
+        }
+    }
+}
+
+fn draw_line(start: Vec3, end: Vec3, width: u32, height: u32, pixels: &mut [u8]) {
+    let steps = (end.x - start.x).abs().max((end.y - start.y).abs()).ceil().max(1.0) as u32;
+    for step in 0..=steps {
+        let t = step as f32 / steps as f32;
+        let x = start.x + (end.x - start.x) * t;
+        let y = start.y + (end.y - start.y) * t;
+        if x < 0.0 || y < 0.0 || x >= width as f32 || y >= height as f32 {
+            continue;
+        }
+        let index = (y as usize * width as usize + x as usize) * 4;
+        for channel in 0..3 {
+            pixels[index + channel] = (BASE_COLOR[channel] * 255.0) as u8;
+        }
+        pixels[index + 3] = 255;
+    }
+}
+
+fn edge(a: Vec3, b: Vec3, point: Vec3) -> f32 {
+    (b.x - a.x) * (point.y - a.y) - (b.y - a.y) * (point.x - a.x)
+}
+
+/**
+ * Halve an RGBA buffer by averaging each 2x2 block.
+ */
+fn downsample(pixels: &[u8], width: u32, height: u32) -> Vec<u8> {
+    let (width, height) = (width as usize, height as usize);
+    let (out_width, out_height) = (width / 2, height / 2);
+    let mut output = vec![0u8; out_width * out_height * 4];
+    for y in 0..out_height {
+        for x in 0..out_width {
+            let samples = [(0, 0), (1, 0), (0, 1), (1, 1)].map(|(dx, dy)| ((y * 2 + dy) * width + x * 2 + dx) * 4);
+            let alpha: u32 = samples.iter().map(|sample| pixels[sample + 3] as u32).sum();
+            let target = (y * out_width + x) * 4;
+            output[target + 3] = (alpha / 4) as u8;
+            if alpha == 0 {
+                continue;
+            }
+            // weight by alpha so the transparent background does not darken the edges
+            for channel in 0..3 {
+                let sum: u32 = samples.iter().map(|sample| pixels[sample + channel] as u32 * pixels[sample + 3] as u32).sum();
+                output[target + channel] = (sum / alpha) as u8;
+            }
+        }
+    }
+    output
+}
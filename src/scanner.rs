@@ -0,0 +1,463 @@
+/*
+ * Copyright (c) 2025-2026 Mitch Tuck
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, PoisonError};
+use std::thread;
+use iced::Task;
+use iced::futures::channel::oneshot;
+use regex::Regex;
+#[allow(unused)]
+use log::{error, warn, info, debug, trace};
+use crate::ThreeDManager;
+use crate::config::Config;
+use crate::db_manager::DbManager;
+use crate::file_hash::{self, FileIdentity};
+use crate::gcode::Gcode;
+use crate::mesh::Mesh;
+use crate::models::file::ProjectFile;
+use crate::models::gcode_info::GcodeInfo;
+use crate::models::mesh_info::MeshInfo;
+use crate::models::project::Project;
+use crate::models::project_filter::ProjectFilter;
+use crate::models::project_tag::ProjectTag;
+use crate::models::tag_rule::{RuleTarget, TagRule};
+
+/// What a full rescan changed.
+#[derive(Debug, Clone, Default)]
+pub struct RescanSummary {
+    pub projects_added: usize,
+    pub projects_missing: usize,
+    pub projects_restored: usize,
+    pub files_added: usize,
+    pub files_removed: usize,
+}
+
+impl RescanSummary {
+    pub fn has_changes(&self) -> bool {
+        self.projects_added + self.projects_missing + self.projects_restored + self.files_added + self.files_removed > 0
+    }
+}
+
+/// Days a removed file is remembered in case it turns up again under another name.
+const REMOVED_FILE_DAYS: i64 = 30;
+
+/// Held by background scans so only one of them writes projects and files at a time.
+static SCAN_LOCK: Mutex<()> = Mutex::new(());
+
+/// Keeps the database in step with the project directories under the configured print paths.
+/// Projects are found with each print path's scan settings.
+pub struct Scanner {
+    config: Config,
+    db_manager: DbManager,
+    /// The tag rules with their patterns compiled, rules that don't compile are left out.
+    tag_rules: Vec<(TagRule, Regex)>,
+    /// When this scanner was made, each background sync uses its own so this marks the batch
+    /// files from other projects can be moved in from.
+    started: i64,
+}
+
+impl Scanner {
+    pub fn new(config: Config) -> Scanner {
+        let tag_rules = config.get_tag_rules().into_iter()
+            .filter_map(|rule| rule.regex().ok().map(|regex| (rule, regex)))
+            .collect();
+        Scanner {
+            config,
+            db_manager: ThreeDManager::setup_db_connection(),
+            tag_rules,
+            started: file_hash::now_secs(),
+        }
+    }
+
+    /**
+     * Sync the given changed paths on a worker thread, resolves to whether any project was touched.
+     */
+    pub fn sync_paths_in_background(config: Config, paths: Vec<PathBuf>) -> Task<bool> {
+        let (sender, receiver) = oneshot::channel();
+        thread::spawn(move || {
+            let _scan = SCAN_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+            let changed = Scanner::new(config).sync_paths(&paths);
+            let _ = sender.send(changed);
+        });
+        Task::perform(receiver, |changed| changed.unwrap_or(false))
+    }
+
+    /**
+     * Look for new project directories on a worker thread.
+     */
+    pub fn scan_project_dirs_in_background(config: Config) -> Task<()> {
+        let (sender, receiver) = oneshot::channel();
+        thread::spawn(move || {
+            let _scan = SCAN_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+            Scanner::new(config).scan_project_dirs();
+            let _ = sender.send(());
+        });
+        Task::perform(receiver, |_| ())
+    }
+
+    /**
+     * Work out what the tag rules would add on a worker thread, it goes through the whole library.
+     */
+    pub fn preview_tag_rules_in_background(config: Config) -> Task<Vec<(Project, Vec<String>)>> {
+        let (sender, receiver) = oneshot::channel();
+        thread::spawn(move || {
+            let preview = Scanner::new(config).preview_tag_rules();
+            let _ = sender.send(preview);
+        });
+        Task::perform(receiver, |preview| preview.unwrap_or_default())
+    }
+
+    /**
+     * Add the tags from a preview on a worker thread, resolves to how many tags were added to how many projects.
+     */
+    pub fn apply_tag_rules_in_background(config: Config, preview: Vec<(Project, Vec<String>)>) -> Task<(usize, usize)> {
+        let (sender, receiver) = oneshot::channel();
+        thread::spawn(move || {
+            let _scan = SCAN_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+            let added = Scanner::new(config).apply_tag_rules(&preview);
+            let _ = sender.send(added);
+        });
+        Task::perform(receiver, |added| added.unwrap_or_default())
+    }
+
+    /**
+     * Run a full rescan on a worker thread.
+     */
+    pub fn rescan_in_background(config: Config) -> Task<RescanSummary> {
+        let (sender, receiver) = oneshot::channel();
+        thread::spawn(move || {
+            let _scan = SCAN_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+            let summary = Scanner::new(config).rescan();
+            let _ = sender.send(summary);
+        });
+        Task::perform(receiver, |summary| summary.unwrap_or_default())
+    }
+
+    /**
+     * Look for new project directories, refresh the files of every known project and flag the
+     * projects whose directory has gone.
+     */
+    pub fn rescan(&self) -> RescanSummary {
+        let mut summary = RescanSummary::default();
+        for project_dir in self.new_project_dirs() {
+            let Some(project) = self.create_project(&project_dir) else { continue };
+            summary.projects_added += 1;
+            summary.files_added += project.files.len();
+        }
+        for mut project in self.db_manager.get_filtered_projects(&ProjectFilter::default()) {
+            if !self.is_project_dir(Path::new(&project.path)) {
+                if !project.missing {
+                    self.db_manager.set_project_missing(project.id, true);
+                    summary.projects_missing += 1;
+                }
+                continue;
+            }
+            if project.missing {
+                self.db_manager.set_project_missing(project.id, false);
+                summary.projects_restored += 1;
+            }
+            let (added, removed) = self.update_project_files(&mut project);
+            summary.files_added += added;
+            summary.files_removed += removed;
+        }
+        self.db_manager.purge_removed_files(file_hash::now_secs() - REMOVED_FILE_DAYS * 24 * 60 * 60);
+        self.update_file_metadata();
+        info!("Rescan finished: {:?}", summary);
+        summary
+    }
+
+    /**
+     * Move a missing project to the directory it now lives in and pick up its files there.
+     */
+    pub fn relink_project(&self, project: &Project, new_path: &Path) {
+        let Some(new_path) = new_path.to_str() else { return };
+        if !self.db_manager.relink_project(project, new_path) {
+            warn!("Could not move {} to {}, it may already be a project", project.name, new_path);
+            return;
+        }
+        let mut project = self.db_manager.get_project(project.id);
+        self.db_manager.update_project_files(project.clone(), project.get_file_system_files(), self.started);
+        self.update_file_metadata();
+    }
+
+    /**
+     * Create projects for directories in the print paths that aren't in the database yet.
+     */
+    pub fn scan_project_dirs(&self) {
+        for project_dir in self.new_project_dirs() {
+            self.create_project(&project_dir);
+        }
+        self.update_file_metadata();
+    }
+
+    /**
+     * Directories in the print paths that aren't projects yet.
+     */
+    fn new_project_dirs(&self) -> Vec<PathBuf> {
+        let mut project_dirs = vec![];
+        for print_path in self.config.print_paths.clone().unwrap_or_default().iter() {
+            for project_dir in self.config.get_scan_settings(print_path).project_dirs() {
+                let Some(path) = project_dir.to_str() else { continue };
+                if self.db_manager.get_project_by_path(path).is_none() {
+                    debug!("Found new project directory {}", project_dir.display());
+                    project_dirs.push(project_dir);
+                }
+            }
+        }
+        project_dirs
+    }
+
+    /**
+     * Bring the projects containing the changed paths up to date, returns true when any project was touched.
+     */
+    pub fn sync_paths(&self, paths: &[PathBuf]) -> bool {
+        let project_dirs: HashSet<PathBuf> = paths.iter().filter_map(|path| self.project_dir_for(path)).collect();
+        let mut changed = false;
+        for project_dir in project_dirs.iter() {
+            changed |= self.sync_project_dir(project_dir);
+        }
+        if changed {
+            self.refresh_file_metadata(paths);
+            self.update_file_metadata();
+        }
+        changed
+    }
+
+    /**
+     * Update one project directory: create it, refresh its files or flag it as missing.
+     */
+    fn sync_project_dir(&self, project_dir: &Path) -> bool {
+        let Some(path) = project_dir.to_str() else { return false };
+        let project = self.db_manager.get_project_by_path(path);
+        match project {
+            Some(mut project) if self.is_project_dir(project_dir) => {
+                if project.missing {
+                    info!("Project directory {} is back", project_dir.display());
+                    self.db_manager.set_project_missing(project.id, false);
+                }
+                self.update_project_files(&mut project);
+                true
+            }
+            Some(project) => {
+                if !project.missing {
+                    info!("Project directory {} was removed or is no longer a project", project_dir.display());
+                    self.db_manager.set_project_missing(project.id, true);
+                }
+                true
+            }
+            None if project_dir.is_dir() => self.create_project(project_dir).is_some(),
+            None => false,
+        }
+    }
+
+    /**
+     * The project directory a path belongs to.  Known projects are checked first so removed
+     * directories and projects found with older scan settings still sync, otherwise the print
+     * path's scan settings decide.  Our own thumbnail folders are ignored so generating
+     * thumbnails doesn't trigger a sync.
+     */
+    fn project_dir_for(&self, path: &Path) -> Option<PathBuf> {
+        if path.components().any(|component| component.as_os_str() == ".3DManager") {
+            return None;
+        }
+        let print_paths = self.config.print_paths.clone().unwrap_or_default();
+        let print_path = print_paths.iter().find(|print_path| path.starts_with(print_path))?;
+        let known_project = path.ancestors()
+            .take_while(|dir| *dir != Path::new(print_path))
+            .find_map(|dir| self.db_manager.get_project_by_path(dir.to_str()?));
+        if let Some(project) = known_project {
+            return Some(PathBuf::from(project.path));
+        }
+        self.config.get_scan_settings(print_path).project_dir_for(path)
+    }
+
+    /**
+     * Whether a known project's directory is still there and its print path's scan settings
+     * still find it, after a depth change a project can be a level too deep or too shallow.
+     * Settings that can't find any project are ignored rather than losing every project.
+     */
+    fn is_project_dir(&self, project_dir: &Path) -> bool {
+        if !project_dir.is_dir() {
+            return false;
+        }
+        let print_paths = self.config.print_paths.clone().unwrap_or_default();
+        let Some(print_path) = print_paths.iter().find(|print_path| project_dir.starts_with(print_path)) else { return true };
+        let scan_settings = self.config.get_scan_settings(print_path);
+        scan_settings.finds_nothing() || scan_settings.project_dir_for(project_dir).as_deref() == Some(project_dir)
+    }
+
+    fn create_project(&self, project_dir: &Path) -> Option<Project> {
+        let new_project = Project {
+            name: project_dir.file_name()?.to_str()?.to_string(),
+            path: project_dir.to_str()?.to_string(),
+            ..Default::default()
+        };
+        let mut project = match self.db_manager.create_project(new_project) {
+            Ok(project) => project,
+            Err(e) => {
+                error!("Could not create project {}: {}", project_dir.display(), e);
+                return None;
+            }
+        };
+        let files = project.get_file_system_files();
+        self.db_manager.update_project_files(project.clone(), files.clone(), self.started);
+        let project = self.db_manager.get_project(project.id);
+        let tags = self.rule_tags(&project, &files, true);
+        Some(self.add_tags(project, &tags))
+    }
+
+    /**
+     * Bring a known project's files up to date and tag it for any new files the rules match.
+     * Directory rules were applied when the project was created so they're not run again,
+     * that way tags the user removed stay removed.
+     */
+    fn update_project_files(&self, project: &mut Project) -> (usize, usize) {
+        let files = project.get_file_system_files();
+        let new_files: Vec<String> = files.iter()
+            .filter(|path| !project.files.iter().any(|file| file.path == **path))
+            .cloned()
+            .collect();
+        let changes = self.db_manager.update_project_files(project.clone(), files, self.started);
+        let tags = self.rule_tags(project, &new_files, false);
+        if !tags.is_empty() {
+            self.add_tags(project.clone(), &tags);
+        }
+        changes
+    }
+
+    /**
+     * Tags the configured rules give a project that it doesn't have yet.
+     */
+    fn rule_tags(&self, project: &Project, files: &[String], include_directory: bool) -> Vec<String> {
+        let mut tags: Vec<String> = vec![];
+        for (rule, regex) in self.tag_rules.iter().filter(|(rule, _)| include_directory || rule.target != RuleTarget::Directory) {
+            for tag in rule.tags_for(regex, &project.path, files) {
+                if !tags.contains(&tag) && !self.has_tag(project, &tag) {
+                    tags.push(tag);
+                }
+            }
+        }
+        tags
+    }
+
+    /**
+     * Whether the project already has the tag at the end of a tag path, by name or alias.
+     */
+    fn has_tag(&self, project: &Project, tag: &str) -> bool {
+        let Some(name) = ProjectTag::split_path(tag).pop() else { return true };
+        let resolved = self.db_manager.get_tag_by_tag(name.clone()).ok();
+        project.tags.iter().any(|existing| {
+            existing.tag.eq_ignore_ascii_case(&name) || resolved.as_ref().is_some_and(|resolved| resolved.id == existing.id)
+        })
+    }
+
+    fn add_tags(&self, mut project: Project, tags: &[String]) -> Project {
+        for tag in tags.iter() {
+            info!("Tagging {} with {}", project.name, tag);
+            project = self.db_manager.project_add_tag(project, tag.clone());
+        }
+        project
+    }
+
+    /**
+     * The tags every rule would add across the whole library, for previewing before applying them.
+     */
+    pub fn preview_tag_rules(&self) -> Vec<(Project, Vec<String>)> {
+        self.db_manager.get_filtered_projects(&ProjectFilter::default()).into_iter()
+            .filter_map(|project| {
+                let files: Vec<String> = project.files.iter().map(|file| file.path.clone()).collect();
+                let tags = self.rule_tags(&project, &files, true);
+                (!tags.is_empty()).then_some((project, tags))
+            })
+            .collect()
+    }
+
+    /**
+     * Add the tags from a preview, returns how many tags were added to how many projects.
+     * Projects deleted or purged since the preview are skipped.
+     */
+    pub fn apply_tag_rules(&self, preview: &[(Project, Vec<String>)]) -> (usize, usize) {
+        let mut added = 0;
+        let mut projects = 0;
+        for (project, tags) in preview.iter() {
+            if !self.db_manager.project_exists(project.id) {
+                continue;
+            }
+            self.add_tags(self.db_manager.get_project(project.id), tags);
+            added += tags.len();
+            projects += 1;
+        }
+        (added, projects)
+    }
+
+    /**
+     * Re-read the metadata of files that changed on disk.
+     */
+    fn refresh_file_metadata(&self, paths: &[PathBuf]) {
+        for path in paths.iter().filter(|path| path.is_file()) {
+            let Some(project_dir) = self.project_dir_for(path) else { continue };
+            let Some(project_dir) = project_dir.to_str() else { continue };
+            let Some(project) = self.db_manager.get_project_by_path(project_dir) else { continue };
+            let Some(path) = path.to_str() else { continue };
+            for project_file in project.files.iter().filter(|file| file.path == path) {
+                self.extract_metadata(project_file);
+            }
+        }
+    }
+
+    /**
+     * Measure models and read G-code settings for every file that doesn't have them stored yet.
+     */
+    pub fn update_file_metadata(&self) {
+        for project_file in self.db_manager.get_files_missing_mesh_info() {
+            self.extract_metadata(&project_file);
+        }
+        for project_file in self.db_manager.get_files_missing_gcode_info() {
+            self.extract_metadata(&project_file);
+        }
+    }
+
+    /**
+     * Read and store the metadata of one file.  A file that can't be read is remembered with its
+     * size and modification time, so it is only tried again once it changes.
+     */
+    fn extract_metadata(&self, project_file: &ProjectFile) {
+        let Ok(metadata) = fs::metadata(&project_file.path) else { return };
+        if let Some((size, mtime)) = self.db_manager.get_metadata_failure(project_file.id)
+            && FileIdentity::is_current(Some(size), Some(mtime), &metadata) {
+            return;
+        }
+        let read = if project_file.is_model_type() {
+            Mesh::load(&project_file.path)
+                .map(|mesh| self.db_manager.save_mesh_info(&MeshInfo::from_mesh(project_file.id, &mesh)))
+                .map_err(|e| warn!("Could not read model {}: {}", project_file.path, e))
+        } else if project_file.is_gcode_type() {
+            Gcode::load(&project_file.path)
+                .map(|gcode| self.db_manager.save_gcode_info(&GcodeInfo::from_gcode(project_file.id, &gcode)))
+                .map_err(|e| warn!("Could not read G-code {}: {}", project_file.path, e))
+        } else {
+            return;
+        };
+        match read {
+            Ok(()) => self.db_manager.clear_metadata_failure(project_file.id),
+            Err(()) => self.db_manager.save_metadata_failure(project_file.id, metadata.len() as i64, file_hash::modified_secs(&metadata)),
+        }
+    }
+}
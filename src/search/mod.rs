@@ -0,0 +1,99 @@
+/*
+ * Copyright (c) 2025-2026 Mitch Tuck
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Helpers for the full text index, which covers project names and notes, file paths and
+//! notes, tag names and sources.
+
+pub mod query;
+
+/// Characters the index wraps around matched words in snippets, picked because they never
+/// appear in names or notes.
+pub const HIGHLIGHT_START: &str = "\u{2}";
+pub const HIGHLIGHT_END: &str = "\u{3}";
+
+/// Where a project matched a search, the text has the matched words wrapped in the highlight markers.
+#[derive(Debug, Clone)]
+pub struct SearchSnippet {
+    /// project, file, tag or source
+    pub kind: String,
+    pub text: String,
+}
+
+impl SearchSnippet {
+    pub fn label(&self) -> &str {
+        match self.kind.as_str() {
+            "file" => "File: ",
+            "tag" => "Tag: ",
+            "source" => "Source: ",
+            _ => "",
+        }
+    }
+}
+
+/**
+ * Turn one word or phrase the user typed into an FTS5 string that matches it literally.
+ * Single words also match as a prefix so results show up while typing.
+ */
+pub fn fts_term(term: &str) -> Option<String> {
+    let term = term.trim();
+    if term.is_empty() {
+        return None;
+    }
+    let quoted = format!("\"{}\"", term.replace('"', "\"\""));
+    if term.contains(char::is_whitespace) {
+        Some(quoted)
+    } else {
+        Some(format!("{}*", quoted))
+    }
+}
+
+/**
+ * A LIKE pattern finding the word or phrase anywhere, for when there is no full text index.
+ * Backslash is the escape character.
+ */
+pub fn like_term(term: &str) -> String {
+    let escaped = term.trim().replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+    format!("%{}%", escaped)
+}
+
+/**
+ * An FTS5 query matching rows that contain any of the terms, used for ranking and snippets.
+ */
+pub fn fts_any(terms: &[String]) -> Option<String> {
+    let parts: Vec<String> = terms.iter().filter_map(|term| fts_term(term)).collect();
+    if parts.is_empty() {
+        return None;
+    }
+    Some(parts.join(" OR "))
+}
+
+/**
+ * Split a snippet into (text, highlighted) parts.
+ */
+pub fn highlight_parts(snippet: &str) -> Vec<(String, bool)> {
+    let mut parts = vec![];
+    for (index, part) in snippet.split(HIGHLIGHT_START).enumerate() {
+        if index == 0 {
+            parts.push((part.to_string(), false));
+            continue;
+        }
+        let (highlighted, rest) = part.split_once(HIGHLIGHT_END).unwrap_or((part, ""));
+        parts.push((highlighted.to_string(), true));
+        parts.push((rest.to_string(), false));
+    }
+    parts.into_iter().filter(|(text, _)| !text.is_empty()).collect()
+}
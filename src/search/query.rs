@@ -0,0 +1,341 @@
+/*
+ * Copyright (c) 2025-2026 Mitch Tuck
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! The search box syntax, for example `dragon tag:articulated -tag:supports-needed path:minis sort:added`.
+//!
+//! * `word` or `"a phrase"` has to appear somewhere in the project, `-word` must not.
+//! * `tag:name` requires the tag, `tag:a|b` either tag and `-tag:name` excludes it.
+//! * `name:text` and `path:text` match part of the project name or directory.
+//! * `fits:220x220x250` and `printer:"Prusa MK4"` check the measured models fit.
+//! * `printed:never`, `printed:yes` or `printed:failed` check the print history, failed means the last print failed.
+//! * `status:"to print"` matches the workflow state, `status:none` projects without one.
+//! * `sort:relevance`, `sort:name` or `sort:added`, optionally followed by `:asc` or `:desc`.
+
+use std::fmt::Display;
+use crate::models::print_job::PrintHistory;
+use crate::models::project_filter::BedSize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    Relevance,
+    Name,
+    Added,
+}
+
+impl SortKey {
+    pub const ALL: [SortKey; 3] = [SortKey::Relevance, SortKey::Name, SortKey::Added];
+
+    fn keyword(&self) -> &'static str {
+        match self {
+            SortKey::Relevance => "relevance",
+            SortKey::Name => "name",
+            SortKey::Added => "added",
+        }
+    }
+}
+
+impl Display for SortKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            SortKey::Relevance => "Best match",
+            SortKey::Name => "Name",
+            SortKey::Added => "Recently added",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sort {
+    pub key: SortKey,
+    pub descending: bool,
+}
+
+impl Sort {
+    /**
+     * The natural direction for a key: best match first, A to Z and newest first.
+     */
+    pub fn new(key: SortKey) -> Sort {
+        Sort { key, descending: key == SortKey::Added }
+    }
+
+    /**
+     * Parse the value of a sort: filter, "added" or "name:desc".
+     */
+    pub fn parse(value: &str) -> Result<Sort, String> {
+        let (key, direction) = value.split_once(':').unwrap_or((value, ""));
+        let key = match key.to_lowercase().as_str() {
+            "relevance" | "rank" => SortKey::Relevance,
+            "name" => SortKey::Name,
+            "added" | "new" | "date" => SortKey::Added,
+            _ => return Err(format!("Can't sort by {}, use relevance, name or added", key)),
+        };
+        let mut sort = Sort::new(key);
+        match direction.to_lowercase().as_str() {
+            "" => {}
+            "asc" => sort.descending = false,
+            "desc" => sort.descending = true,
+            _ => return Err(format!("Sort direction {} should be asc or desc", direction)),
+        }
+        Ok(sort)
+    }
+}
+
+/// Written the way a sort: filter is typed so it can be parsed back.
+impl Display for Sort {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.key.keyword(), if self.descending { "desc" } else { "asc" })
+    }
+}
+
+/// A parsed search.  Tags are compared case insensitively by name.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SearchQuery {
+    pub terms: Vec<String>,
+    pub excluded_terms: Vec<String>,
+    /// Each group needs at least one of its tags, every group has to match.
+    pub tags: Vec<Vec<String>>,
+    pub excluded_tags: Vec<String>,
+    pub name: Option<String>,
+    pub path: Option<String>,
+    pub fits: Option<BedSize>,
+    pub printer: Option<String>,
+    pub printed: Option<PrintHistory>,
+    /// Workflow state, empty for projects without one.
+    pub status: Option<String>,
+    pub sort: Option<Sort>,
+}
+
+impl SearchQuery {
+    pub fn parse(text: &str) -> Result<SearchQuery, String> {
+        let mut query = SearchQuery::default();
+        for token in tokenize(text)? {
+            query.add_token(token)?;
+        }
+        Ok(query)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == SearchQuery::default()
+    }
+
+    fn add_token(&mut self, token: Token) -> Result<(), String> {
+        let Some(field) = token.field else {
+            if token.negated {
+                self.excluded_terms.push(token.value);
+            } else {
+                self.terms.push(token.value);
+            }
+            return Ok(());
+        };
+        if token.value.is_empty() {
+            return Err(format!("{}: needs a value", field));
+        }
+        let value = token.value;
+        match (field.as_str(), token.negated) {
+            ("tag", false) => {
+                let group: Vec<String> = value.split('|').map(|tag| tag.trim().to_lowercase()).filter(|tag| !tag.is_empty()).collect();
+                if group.is_empty() {
+                    return Err("tag: needs a value".to_string());
+                }
+                self.tags.push(group);
+            }
+            ("tag", true) => self.excluded_tags.extend(value.split('|').map(|tag| tag.trim().to_lowercase()).filter(|tag| !tag.is_empty())),
+            ("name", false) => self.name = Some(value),
+            ("path", false) => self.path = Some(value),
+            ("fits", false) => {
+                self.fits = Some(BedSize::parse(&value).ok_or(format!("fits:{} should look like fits:220x220 or fits:220x220x250", value))?);
+            }
+            ("printer", false) => self.printer = Some(value),
+            ("printed", false) => self.printed = Some(PrintHistory::parse(&value)?),
+            ("status", false) => self.status = Some(if value.eq_ignore_ascii_case("none") { "".to_string() } else { value }),
+            ("sort", false) => self.sort = Some(Sort::parse(&value)?),
+            ("name" | "path" | "fits" | "printer" | "printed" | "status" | "sort", true) => return Err(format!("-{}: can't be negated, only -tag: can", field)),
+            _ => return Err(format!("Unknown filter {}:, put it in quotes to search for the text", field)),
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct Token {
+    negated: bool,
+    field: Option<String>,
+    value: String,
+}
+
+/**
+ * Split on whitespace outside quotes.  A field name is only recognised before any quote so
+ * `"http://example.com"` searches for the text.
+ */
+fn tokenize(text: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = vec![];
+    let mut chars = text.chars().peekable();
+    loop {
+        while chars.peek().is_some_and(|c| c.is_whitespace()) {
+            chars.next();
+        }
+        if chars.peek().is_none() {
+            break;
+        }
+        let mut token = Token { negated: false, field: None, value: String::new() };
+        if chars.peek() == Some(&'-') {
+            chars.next();
+            token.negated = true;
+        }
+        let mut quoted = false;
+        let mut was_quoted = false;
+        for c in chars.by_ref() {
+            match c {
+                '"' => {
+                    quoted = !quoted;
+                    was_quoted = true;
+                }
+                ':' if !quoted && !was_quoted && token.field.is_none() && !token.value.is_empty() => {
+                    token.field = Some(token.value.to_lowercase());
+                    token.value.clear();
+                }
+                c if c.is_whitespace() && !quoted => break,
+                c => token.value.push(c),
+            }
+        }
+        if quoted {
+            return Err("A quote is missing its closing \"".to_string());
+        }
+        if token.field.is_none() && token.value.trim().is_empty() {
+            if token.negated && !was_quoted {
+                return Err("- needs a word or tag: after it".to_string());
+            }
+            continue;
+        }
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(text: &str) -> Vec<(bool, Option<String>, String)> {
+        tokenize(text).unwrap().into_iter().map(|token| (token.negated, token.field, token.value)).collect()
+    }
+
+    #[test]
+    fn tokenize_splits_on_whitespace_outside_quotes() {
+        assert_eq!(values("  dragon \"big boat\"\t-supports "), vec![
+            (false, None, "dragon".to_string()),
+            (false, None, "big boat".to_string()),
+            (true, None, "supports".to_string()),
+        ]);
+        assert!(values("   ").is_empty());
+    }
+
+    #[test]
+    fn tokenize_reads_fields_before_quotes_only() {
+        assert_eq!(values("Printer:\"Prusa MK4\" \"http://example.com\" sort:name:desc"), vec![
+            (false, Some("printer".to_string()), "Prusa MK4".to_string()),
+            (false, None, "http://example.com".to_string()),
+            (false, Some("sort".to_string()), "name:desc".to_string()),
+        ]);
+        assert_eq!(values(":colon"), vec![(false, None, ":colon".to_string())]);
+    }
+
+    #[test]
+    fn tokenize_rejects_bad_input() {
+        assert!(tokenize("\"unclosed").is_err());
+        assert!(tokenize("dragon -").is_err());
+        assert!(values("-\"\"").is_empty());
+    }
+
+    #[test]
+    fn sort_parse_uses_the_natural_direction() {
+        assert_eq!(Sort::parse("added"), Ok(Sort { key: SortKey::Added, descending: true }));
+        assert_eq!(Sort::parse("Name"), Ok(Sort { key: SortKey::Name, descending: false }));
+        assert_eq!(Sort::parse("rank"), Ok(Sort { key: SortKey::Relevance, descending: false }));
+        assert_eq!(Sort::parse("name:DESC"), Ok(Sort { key: SortKey::Name, descending: true }));
+        assert_eq!(Sort::parse("date:asc"), Ok(Sort { key: SortKey::Added, descending: false }));
+    }
+
+    #[test]
+    fn sort_parse_round_trips_through_display() {
+        for key in SortKey::ALL {
+            for descending in [false, true] {
+                let sort = Sort { key, descending };
+                assert_eq!(Sort::parse(&sort.to_string()), Ok(sort));
+            }
+        }
+    }
+
+    #[test]
+    fn sort_parse_rejects_unknown_keys_and_directions() {
+        assert!(Sort::parse("size").is_err());
+        assert!(Sort::parse("").is_err());
+        assert!(Sort::parse("name:up").is_err());
+    }
+
+    #[test]
+    fn parse_reads_every_filter() {
+        let query = SearchQuery::parse(
+            "dragon -broken tag:Articulated|flexi -tag:supports name:rex path:minis fits:220x220x250 \
+            printer:\"Prusa MK4\" printed:never status:none sort:added:asc",
+        ).unwrap();
+        assert_eq!(query, SearchQuery {
+            terms: vec!["dragon".to_string()],
+            excluded_terms: vec!["broken".to_string()],
+            tags: vec![vec!["articulated".to_string(), "flexi".to_string()]],
+            excluded_tags: vec!["supports".to_string()],
+            name: Some("rex".to_string()),
+            path: Some("minis".to_string()),
+            fits: Some(BedSize { x: 220.0, y: 220.0, z: Some(250.0) }),
+            printer: Some("Prusa MK4".to_string()),
+            printed: Some(PrintHistory::NeverPrinted),
+            status: Some("".to_string()),
+            sort: Some(Sort { key: SortKey::Added, descending: false }),
+        });
+    }
+
+    #[test]
+    fn parse_keeps_each_tag_group() {
+        let query = SearchQuery::parse("tag:a tag:b|c -tag:d|e").unwrap();
+        assert_eq!(query.tags, vec![vec!["a".to_string()], vec!["b".to_string(), "c".to_string()]]);
+        assert_eq!(query.excluded_tags, vec!["d".to_string(), "e".to_string()]);
+        assert_eq!(SearchQuery::parse("status:\"To Print\"").unwrap().status, Some("To Print".to_string()));
+    }
+
+    #[test]
+    fn parse_empty_text_is_empty() {
+        assert!(SearchQuery::parse("").unwrap().is_empty());
+        assert!(!SearchQuery::parse("dragon").unwrap().is_empty());
+    }
+
+    #[test]
+    fn parse_rejects_bad_filters() {
+        for text in [
+            "tag:",
+            "tag:|",
+            "-name:rex",
+            "-sort:name",
+            "colour:red",
+            "fits:big",
+            "printed:maybe",
+            "sort:size",
+            "\"unclosed",
+        ] {
+            assert!(SearchQuery::parse(text).is_err(), "{} should not parse", text);
+        }
+    }
+}
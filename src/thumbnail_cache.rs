@@ -0,0 +1,164 @@
+/*
+ * Copyright (c) 2025-2026 Mitch Tuck
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+#[allow(unused)]
+use log::{error, warn, info, debug, trace};
+use crate::ThreeDManager;
+use crate::config::Config;
+use crate::db_manager::DbManager;
+use crate::file_hash;
+use crate::models::thumbnail_cache_entry::ThumbnailCacheEntry;
+
+static TEMP_FILE_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Central thumbnail store under the config directory.  Images are named after the content
+/// hash of the model and the render settings, so identical files share one thumbnail while
+/// edited files and changed settings get a new one.
+pub struct ThumbnailCache {
+    db_manager: DbManager,
+    directory: PathBuf,
+    max_bytes: u64,
+    /// The render settings as they appear at the end of an image's key.
+    settings_key: String,
+}
+
+impl ThumbnailCache {
+    pub fn new(config: &Config) -> Self {
+        ThumbnailCache {
+            db_manager: ThreeDManager::setup_db_connection(),
+            directory: Self::cache_dir(),
+            max_bytes: config.get_thumbnail_cache_size_mb() * 1024 * 1024,
+            settings_key: Self::settings_key(config),
+        }
+    }
+
+    fn settings_key(config: &Config) -> String {
+        let settings = config.render_settings();
+        format!("{}-{}-{}-{:?}-{}", settings.size, settings.camera.yaw, settings.camera.pitch, settings.shading,
+            if config.use_embedded_thumbnails() { "embedded" } else { "rendered" })
+    }
+
+    pub fn cache_dir() -> PathBuf {
+        let mut directory = Config::get_config_dir().unwrap();
+        directory.push("thumbnails");
+        directory
+    }
+
+    /**
+     * Returns the cached thumbnail if the source is unchanged since it was made.  Only compares
+     * size and mtime so it is cheap enough to call while drawing.
+     */
+    pub fn lookup(&self, source_path: &str) -> Option<String> {
+        let metadata = fs::metadata(source_path).ok()?;
+        let entry = self.db_manager.get_thumbnail_cache_entry(source_path)?;
+        if entry.source_size != metadata.len() as i64 || entry.source_mtime != file_hash::modified_secs(&metadata) {
+            return None;
+        }
+        if !entry.hash.ends_with(&format!("-{}", self.settings_key)) {
+            return None;
+        }
+        let image_file = self.image_file(&entry.hash);
+        if !image_file.exists() {
+            return None;
+        }
+        self.db_manager.touch_thumbnail_cache_entry(source_path, file_hash::now_secs());
+        Some(image_file.to_str().unwrap().to_string())
+    }
+
+    /**
+     * Returns the thumbnail for the source, calling `generate` to create it when no file with
+     * the same contents has been cached yet.
+     */
+    pub fn get_or_create(&self, source_path: &str, generate: impl FnOnce(&str) -> io::Result<()>) -> io::Result<String> {
+        if let Some(image_file) = self.lookup(source_path) {
+            return Ok(image_file);
+        }
+        let metadata = fs::metadata(source_path)?;
+        let hash = format!("{}-{}", file_hash::content_hash(source_path)?, self.settings_key);
+        let image_file = self.image_file(&hash);
+        if !image_file.exists() {
+            fs::create_dir_all(&self.directory)?;
+            // render to a temporary name so a parallel worker never sees a half written png
+            let temp_id = TEMP_FILE_COUNTER.fetch_add(1, Ordering::Relaxed);
+            let temp_file = self.directory.join(format!("{}.{}-{}.tmp.png", hash, std::process::id(), temp_id));
+            let temp_path = temp_file.to_str().unwrap().to_string();
+            if let Err(err) = generate(&temp_path) {
+                let _ = fs::remove_file(&temp_file);
+                return Err(err);
+            }
+            fs::rename(&temp_file, &image_file)?;
+        }
+        self.db_manager.save_thumbnail_cache_entry(ThumbnailCacheEntry {
+            source_path: source_path.to_string(),
+            source_size: metadata.len() as i64,
+            source_mtime: file_hash::modified_secs(&metadata),
+            hash: hash.clone(),
+            image_size: fs::metadata(&image_file)?.len() as i64,
+            last_used: file_hash::now_secs(),
+        });
+        self.evict(&hash);
+        Ok(image_file.to_str().unwrap().to_string())
+    }
+
+    /**
+     * Drop least recently used thumbnails until the cache fits in its size limit.
+     * The image just created is never evicted.
+     */
+    fn evict(&self, keep_hash: &str) {
+        let usage = self.db_manager.get_thumbnail_cache_usage();
+        let mut total: u64 = usage.iter().map(|(_, size)| *size as u64).sum();
+        for (hash, size) in usage {
+            if total <= self.max_bytes {
+                break;
+            }
+            if hash == keep_hash {
+                continue;
+            }
+            debug!("Evicting cached thumbnail {}", hash);
+            let _ = fs::remove_file(self.image_file(&hash));
+            self.db_manager.delete_thumbnail_cache_hash(&hash);
+            total = total.saturating_sub(size as u64);
+        }
+    }
+
+    /**
+     * Total size in bytes of the cached images.
+     */
+    pub fn usage_bytes() -> u64 {
+        let Ok(entries) = fs::read_dir(Self::cache_dir()) else { return 0 };
+        entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| entry.metadata().ok())
+            .map(|metadata| metadata.len())
+            .sum()
+    }
+
+    pub fn clear(&self) -> io::Result<()> {
+        self.db_manager.clear_thumbnail_cache();
+        if self.directory.exists() {
+            fs::remove_dir_all(&self.directory)?;
+        }
+        Ok(())
+    }
+
+    fn image_file(&self, hash: &str) -> PathBuf {
+        self.directory.join(format!("{}.png", hash))
+    }
+}
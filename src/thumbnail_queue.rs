@@ -0,0 +1,130 @@
+/*
+ * Copyright (c) 2025-2026 Mitch Tuck
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+use std::collections::{HashSet, VecDeque};
+use std::thread;
+use iced::Task;
+use iced::futures::channel::oneshot;
+#[allow(unused)]
+use log::{error, warn, info, debug, trace};
+use crate::config::Config;
+use crate::models::file::ProjectFile;
+
+#[derive(Debug, Clone)]
+pub struct ThumbnailResult {
+    /// Path of the model file the thumbnail belongs to.
+    pub source: String,
+    /// Path of the generated image, empty when rendering failed.
+    pub image: String,
+}
+
+/// Renders thumbnails on background threads, at most `workers` at a time.
+pub struct ThumbnailQueue {
+    config: Config,
+    pending: VecDeque<ProjectFile>,
+    queued: HashSet<String>,
+    /// Paths of the files being rendered right now.
+    running: HashSet<String>,
+    completed: usize,
+    total: usize,
+}
+
+impl ThumbnailQueue {
+    pub fn new(config: Config) -> Self {
+        ThumbnailQueue {
+            config,
+            pending: VecDeque::new(),
+            queued: HashSet::new(),
+            running: HashSet::new(),
+            completed: 0,
+            total: 0,
+        }
+    }
+
+    /**
+     * Add files to the queue and start as many jobs as there are free workers.
+     * Files already queued or running are skipped.
+     */
+    pub fn enqueue(&mut self, files: Vec<ProjectFile>) -> Task<ThumbnailResult> {
+        for file in files {
+            if self.queued.insert(file.path.clone()) {
+                self.pending.push_back(file);
+                self.total += 1;
+            }
+        }
+        self.start_jobs()
+    }
+
+    /**
+     * Record a finished job and start the next one.  Results for files this queue isn't
+     * rendering, like ones from the queue of a main view that has since been replaced, are ignored.
+     */
+    pub fn finished(&mut self, result: &ThumbnailResult) -> Task<ThumbnailResult> {
+        if !self.running.remove(&result.source) {
+            return Task::none();
+        }
+        self.queued.remove(&result.source);
+        self.completed += 1;
+        if self.running.is_empty() && self.pending.is_empty() {
+            self.completed = 0;
+            self.total = 0;
+        }
+        self.start_jobs()
+    }
+
+    pub fn is_queued(&self, path: &str) -> bool {
+        self.queued.contains(path)
+    }
+
+    /**
+     * Returns (completed, total) while there is work in progress.
+     */
+    pub fn progress(&self) -> Option<(usize, usize)> {
+        if self.total == 0 {
+            return None;
+        }
+        Some((self.completed, self.total))
+    }
+
+    fn start_jobs(&mut self) -> Task<ThumbnailResult> {
+        let mut jobs = vec![];
+        while self.running.len() < self.config.get_thumbnail_workers() {
+            let Some(file) = self.pending.pop_front() else { break };
+            self.running.insert(file.path.clone());
+            jobs.push(Self::render(file, self.config.clone()));
+        }
+        Task::batch(jobs)
+    }
+
+    /**
+     * Rendering is CPU bound, so it gets its own thread instead of blocking the executor.
+     */
+    fn render(file: ProjectFile, config: Config) -> Task<ThumbnailResult> {
+        let source = file.path.clone();
+        let (sender, receiver) = oneshot::channel();
+        thread::spawn(move || {
+            let image = file.get_image_path(&config);
+            let _ = sender.send(image);
+        });
+        Task::perform(receiver, move |image| {
+            let image = image.unwrap_or_else(|_| {
+                error!("Thumbnail worker for {} stopped without a result", source);
+                "".to_string()
+            });
+            ThumbnailResult { source: source.clone(), image }
+        })
+    }
+}
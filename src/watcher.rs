@@ -0,0 +1,98 @@
+/*
+ * Copyright (c) 2025-2026 Mitch Tuck
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+use iced::Subscription;
+use iced::futures::{SinkExt, Stream, StreamExt};
+use iced::futures::channel::mpsc::{unbounded, UnboundedSender};
+use notify::{Event, RecursiveMode, Watcher};
+#[allow(unused)]
+use log::{error, warn, info, debug, trace};
+
+/// How long the file system has to be quiet before a batch of changes is reported,
+/// copying a project in produces a burst of events that should be handled once.
+const QUIET_PERIOD: Duration = Duration::from_millis(750);
+
+/**
+ * Watch every print path recursively, producing the paths that changed in each burst of activity.
+ * The watcher restarts when the list of print paths changes.
+ */
+pub fn watch(print_paths: Vec<String>) -> Subscription<Vec<PathBuf>> {
+    Subscription::run_with(print_paths, watch_stream)
+}
+
+//run_with hands the subscription data back by reference, so this has to take &Vec
+#[allow(clippy::ptr_arg)]
+fn watch_stream(print_paths: &Vec<String>) -> impl Stream<Item = Vec<PathBuf>> + use<> {
+    let print_paths = print_paths.clone();
+    iced::stream::channel(16, async move |mut output| {
+        let (event_sender, event_receiver) = mpsc::channel();
+        let mut watcher = match notify::recommended_watcher(event_sender) {
+            Ok(watcher) => watcher,
+            Err(e) => {
+                error!("Could not start the file system watcher: {}", e);
+                return;
+            }
+        };
+        for print_path in print_paths.iter() {
+            match watcher.watch(Path::new(print_path), RecursiveMode::Recursive) {
+                Ok(()) => info!("Watching {} for changes", print_path),
+                Err(e) => warn!("Could not watch {}: {}", print_path, e),
+            }
+        }
+        let (batch_sender, mut batch_receiver) = unbounded();
+        thread::spawn(move || collect_batches(event_receiver, batch_sender));
+        while let Some(batch) = batch_receiver.next().await {
+            if output.send(batch).await.is_err() {
+                break;
+            }
+        }
+        //dropping the watcher closes the event channel which ends the batching thread
+        drop(watcher);
+    })
+}
+
+/**
+ * Group events until the file system goes quiet, then send the set of paths involved.
+ */
+fn collect_batches(events: mpsc::Receiver<notify::Result<Event>>, batches: UnboundedSender<Vec<PathBuf>>) {
+    while let Ok(event) = events.recv() {
+        let mut paths = HashSet::new();
+        add_event_paths(&mut paths, event);
+        while let Ok(event) = events.recv_timeout(QUIET_PERIOD) {
+            add_event_paths(&mut paths, event);
+        }
+        if paths.is_empty() {
+            continue;
+        }
+        if batches.unbounded_send(paths.into_iter().collect()).is_err() {
+            break;
+        }
+    }
+}
+
+fn add_event_paths(paths: &mut HashSet<PathBuf>, event: notify::Result<Event>) {
+    match event {
+        Ok(event) if !event.kind.is_access() => paths.extend(event.paths),
+        Ok(_) => {}
+        Err(e) => warn!("File system watcher error: {}", e),
+    }
+}
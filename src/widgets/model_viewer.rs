@@ -0,0 +1,184 @@
+/*
+ * Copyright (c) 2025-2026 Mitch Tuck
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+use std::sync::Arc;
+use std::thread;
+use iced::{mouse, Point, Rectangle, Renderer, Task, Theme, Pixels};
+use iced::futures::channel::oneshot;
+use iced::widget::canvas::{self, Action, Cache, Event, Geometry, Image, Text};
+use iced::widget::image::Handle;
+#[allow(unused)]
+use log::{error, warn, info, debug, trace};
+use crate::mesh::Mesh;
+use crate::renderer::{self, Camera, VertexNormals};
+
+/// Degrees of rotation per pixel of mouse movement while orbiting.
+const ORBIT_SPEED: f32 = 0.5;
+const ZOOM_STEP: f32 = 1.1;
+
+/// A model loaded for the viewer along with its smooth shading normals, which are worked out
+/// once here instead of on every redraw.
+#[derive(Debug)]
+pub struct ViewerMesh {
+    pub mesh: Mesh,
+    normals: VertexNormals,
+}
+
+impl ViewerMesh {
+    pub fn new(mesh: Mesh) -> ViewerMesh {
+        let normals = VertexNormals::new(&mesh);
+        ViewerMesh { mesh, normals }
+    }
+
+    /**
+     * Load a model on a worker thread so big files don't freeze the window, resolves to None
+     * when it can't be read.
+     */
+    pub fn load_in_background(path: String) -> Task<Option<Arc<ViewerMesh>>> {
+        let (sender, receiver) = oneshot::channel();
+        thread::spawn(move || {
+            let viewer_mesh = match Mesh::load(&path) {
+                Ok(mesh) => Some(Arc::new(ViewerMesh::new(mesh))),
+                Err(err) => {
+                    error!("Could not load {} for the viewer: {}", path, err);
+                    None
+                }
+            };
+            let _ = sender.send(viewer_mesh);
+        });
+        Task::perform(receiver, |viewer_mesh| viewer_mesh.ok().flatten())
+    }
+}
+
+/// Orbit (left drag), pan (right or middle drag) and zoom (wheel) viewer for a mesh.  The
+/// camera lives with the caller so it can be reset, every change is published through
+/// `on_camera_change`.
+pub struct ModelViewer<'a, Message> {
+    mesh: &'a ViewerMesh,
+    camera: Camera,
+    wireframe: bool,
+    cache: &'a Cache,
+    on_camera_change: fn(Camera) -> Message,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DragMode {
+    Orbit,
+    Pan,
+}
+
+#[derive(Default)]
+pub struct ViewerState {
+    drag: Option<(DragMode, Point)>,
+}
+
+impl<'a, Message> ModelViewer<'a, Message> {
+    pub fn new(mesh: &'a ViewerMesh, camera: Camera, wireframe: bool, cache: &'a Cache, on_camera_change: fn(Camera) -> Message) -> Self {
+        ModelViewer { mesh, camera, wireframe, cache, on_camera_change }
+    }
+
+    fn dimensions_label(&self) -> String {
+        match self.mesh.mesh.bounds() {
+            Some((min, max)) => {
+                let size = max - min;
+                format!("X {:.2} mm × Y {:.2} mm × Z {:.2} mm", size.x, size.y, size.z)
+            }
+            None => "Empty model".to_string(),
+        }
+    }
+}
+
+impl<Message> canvas::Program<Message> for ModelViewer<'_, Message> {
+    type State = ViewerState;
+
+    fn update(&self, state: &mut ViewerState, event: &Event, bounds: Rectangle, cursor: mouse::Cursor) -> Option<Action<Message>> {
+        let Event::Mouse(mouse_event) = event else { return None };
+        match mouse_event {
+            mouse::Event::ButtonPressed(button) => {
+                let position = cursor.position_in(bounds)?;
+                let mode = match button {
+                    mouse::Button::Left => DragMode::Orbit,
+                    mouse::Button::Right | mouse::Button::Middle => DragMode::Pan,
+                    _ => return None,
+                };
+                state.drag = Some((mode, position));
+                Some(Action::capture())
+            }
+            mouse::Event::ButtonReleased(_) => {
+                state.drag.take().map(|_| Action::capture())
+            }
+            mouse::Event::CursorMoved { .. } => {
+                let (mode, last) = state.drag?;
+                // keep following the cursor when a drag leaves the viewer
+                let position = cursor.position().map(|point| Point::new(point.x - bounds.x, point.y - bounds.y))?;
+                let (delta_x, delta_y) = (position.x - last.x, position.y - last.y);
+                state.drag = Some((mode, position));
+                let mut camera = self.camera;
+                match mode {
+                    DragMode::Orbit => {
+                        camera.yaw -= delta_x * ORBIT_SPEED;
+                        camera.pitch = (camera.pitch + delta_y * ORBIT_SPEED).clamp(-90.0, 90.0);
+                    }
+                    DragMode::Pan => {
+                        camera.pan_x += delta_x;
+                        camera.pan_y += delta_y;
+                    }
+                }
+                Some(Action::publish((self.on_camera_change)(camera)).and_capture())
+            }
+            mouse::Event::WheelScrolled { delta } => {
+                cursor.position_in(bounds)?;
+                let lines = match delta {
+                    mouse::ScrollDelta::Lines { y, .. } => *y,
+                    mouse::ScrollDelta::Pixels { y, .. } => *y / 50.0,
+                };
+                let mut camera = self.camera;
+                camera.zoom = (camera.zoom * ZOOM_STEP.powf(lines)).clamp(0.05, 50.0);
+                Some(Action::publish((self.on_camera_change)(camera)).and_capture())
+            }
+            _ => None,
+        }
+    }
+
+    fn draw(&self, _state: &ViewerState, renderer: &Renderer, theme: &Theme, bounds: Rectangle, _cursor: mouse::Cursor) -> Vec<Geometry> {
+        let geometry = self.cache.draw(renderer, bounds.size(), |frame| {
+            let width = bounds.width.max(1.0) as u32;
+            let height = bounds.height.max(1.0) as u32;
+            if let Some(pixels) = renderer::render_rgba(&self.mesh.mesh, Some(&self.mesh.normals), width, height, &self.camera, self.wireframe) {
+                frame.draw_image(
+                    Rectangle::new(Point::ORIGIN, bounds.size()),
+                    Image::new(Handle::from_rgba(width, height, pixels)),
+                );
+            }
+            frame.fill_text(Text {
+                content: self.dimensions_label(),
+                position: Point::new(8.0, 8.0),
+                color: theme.palette().text,
+                size: Pixels(16.0),
+                ..Text::default()
+            });
+        });
+        vec![geometry]
+    }
+
+    fn mouse_interaction(&self, state: &ViewerState, bounds: Rectangle, cursor: mouse::Cursor) -> mouse::Interaction {
+        match state.drag {
+            Some(_) => mouse::Interaction::Grabbing,
+            None if cursor.is_over(bounds) => mouse::Interaction::Grab,
+            None => mouse::Interaction::default(),
+        }
+    }
+}